@@ -1,11 +1,36 @@
+import { createHmac } from 'node:crypto';
+import { readFileSync, unlinkSync } from 'node:fs';
+import net from 'node:net';
+import { gzipSync } from 'node:zlib';
 import test from 'ava';
 import axios from 'axios';
 
-import { FileInfo, Server, cleanupRouter, forceCleanup, forceExit, getAsync, postAsync } from '../index';
+import {
+  FileInfo,
+  Methods,
+  Server,
+  addRouteGroup,
+  cancelForceExit,
+  cleanupRouter,
+  forceCleanup,
+  forceExit,
+  getAsync,
+  listRoutes,
+  newRouteFunctional,
+  newRouteMultipartStreamAsync,
+  postAsync,
+  putAsync,
+  setAfterHook,
+  setAppState,
+  setBeforeHook,
+} from '../index';
 
 const server = new Server({
   host: '127.0.0.1',
   port: 3002,
+  maxBodySize: 1024 * 1024,
+  methodOverride: true,
+  autoOptions: true,
 });
 
 test.before(async t => {
@@ -43,6 +68,160 @@ test.before(async t => {
     await req.sendTextAsync(`Echo: ${body}`);
   });
 
+  postAsync('/webhook', async (err, req) => {
+    if (err) {
+      await req.setStatusCodeAsync(500);
+      await req.sendErrorAsync('error');
+    }
+    const signature = createHmac('sha256', 'webhook-secret')
+      .update(req.getRawBody() ?? Buffer.alloc(0))
+      .digest('hex');
+    await req.sendTextAsync(signature === req.getHeader('x-signature') ? 'valid' : 'invalid');
+  });
+
+  postAsync('/echo-binary', async (err, req) => {
+    if (err) {
+      await req.setStatusCodeAsync(500);
+      await req.sendErrorAsync('error');
+    }
+    await req.sendBufferAsync(req.getBodyBuffer());
+  });
+
+  newRouteFunctional('/functional/:id', Methods.GET, (err, req) => ({
+    status: 201,
+    headers: { 'x-functional': 'yes' },
+    body: { id: req.getPathParam('id') },
+  }));
+
+  addRouteGroup('/api/v1/', [
+    {
+      method: Methods.GET,
+      pattern: '/ping',
+      callback: async (err, req) => {
+        await req.sendTextAsync('pong');
+      },
+    },
+  ]);
+
+  getAsync('/query-multi', async (err, req) => {
+    await req.sendObjectAsync({ params: req.getQueryParamsMulti(), tags: req.getQueryValues('tag') });
+  });
+
+  getAsync('/redirect', async (err, req) => {
+    await req.sendRedirectAsync('/target?from=redirect', req.getQueryParams().permanent === '1');
+  });
+
+  getAsync('/assets/*path', async (err, req) => {
+    await req.sendJsonAsync(
+      JSON.stringify({ wildcard: req.getWildcard(), params: req.getPathParams() }),
+    );
+  });
+
+  postAsync('/json-result', async (err, req) => {
+    await req.sendJsonAsync(JSON.stringify(req.getBodyJsonResult()));
+  });
+
+  getAsync('/slow', async (err, req) => {
+    await new Promise(resolve => setTimeout(resolve, 500));
+    await req.sendTextAsync('done');
+  });
+
+  getAsync('/media', async (err, req) => {
+    await req.sendBufferAsync(Buffer.from('0123456789'));
+  });
+
+  // 只发送固定的文件，不把请求中的路径直接交给 sendFileAsync
+  getAsync('/files/package', async (err, req) => {
+    await req.sendFileAsync('package.json');
+  });
+
+  getAsync('/files/missing', async (err, req) => {
+    await req.sendFileAsync('missing.bin');
+  });
+
+  getAsync('/header-pairs', async (err, req) => {
+    const pairs = req.getHeaderPairs().filter(([name]) => name === 'x-tag');
+    await req.sendJsonAsync(JSON.stringify({ pairs, header: req.getHeader('x-tag') }));
+  });
+
+  postAsync('/body-kind', async (err, req) => {
+    await req.sendJsonAsync(JSON.stringify({ json: req.isJsonRequest(), form: req.isFormRequest() }));
+  });
+
+  postAsync('/users', async (err, req) => {
+    await req.sendErrorJsonAsync(409, 'USER_EXISTS', 'User already exists', { field: 'email' });
+  });
+
+  putAsync('/override', async (err, req) => {
+    await req.sendTextAsync(req.getMethod());
+  });
+
+  getAsync('/send-once', async (err, req) => {
+    await req.sendAsync(201, '{"created":true}', 'application/json');
+  });
+
+  getAsync('/app-state', async (err, req) => {
+    const state = req.getAppState();
+    state.mutated = true;
+    await req.sendJsonAsync(JSON.stringify(req.getAppState()));
+  });
+
+  getAsync('/bad-header', async (err, req) => {
+    const rejected = await req.addHeaderAsync('x-evil\r\nset-cookie', 'a=b').then(
+      () => false,
+      () => true,
+    );
+    await req.sendTextAsync(String(rejected));
+  });
+
+  getAsync('/throws', () => {
+    throw new Error('boom');
+  });
+
+  let coalescedCalls = 0;
+  getAsync(
+    '/coalesced',
+    async (err, req) => {
+      if (err) {
+        await req.setStatusCodeAsync(500);
+        await req.sendErrorAsync('error');
+      }
+      coalescedCalls += 1;
+      await new Promise(resolve => setTimeout(resolve, 200));
+      await req.sendObjectAsync({ calls: coalescedCalls });
+    },
+    { coalesce: true },
+  );
+
+  getAsync('/sse-writer', async (err, req) => {
+    if (err) {
+      await req.setStatusCodeAsync(500);
+      await req.sendErrorAsync('error');
+    }
+    const writer = await req.startSseAsync();
+    writer.write('first');
+    writer.writeEvent('done', 'second');
+    writer.close();
+  });
+
+  getAsync('/cookies', async (err, req) => {
+    if (err) {
+      await req.setStatusCodeAsync(500);
+      await req.sendErrorAsync('error');
+    }
+    await req.setCookieAsync('session', req.getCookie('session') ?? 'new', { httpOnly: true, path: '/' });
+    await req.setCookieAsync('theme', 'dark', { maxAge: 3600, sameSite: 'Lax' });
+    await req.sendObjectAsync(req.getCookies());
+  });
+
+  getAsync('/version', async (err, req) => {
+    if (err) {
+      await req.setStatusCodeAsync(500);
+      await req.sendErrorAsync('error');
+    }
+    await req.sendTextAsync(req.getHttpVersion());
+  });
+
   // 增加一个处理表单的接口
   postAsync('/form', async (err, req) => {
     if (err) {
@@ -91,6 +270,20 @@ test.before(async t => {
     }
   });
 
+  newRouteMultipartStreamAsync('/stream-upload', Methods.POST, async (err, req) => {
+    const parts = [];
+    let part;
+    while ((part = await req.nextPartAsync())) {
+      let size = 0;
+      let chunk;
+      while ((chunk = await req.readPartChunkAsync())) {
+        size += chunk.length;
+      }
+      parts.push({ name: part.name, filename: part.filename ?? null, size });
+    }
+    await req.sendObjectAsync(parts);
+  });
+
   // 然后启动服务器
   server.start();
 
@@ -131,6 +324,55 @@ test.serial('POST /echo should echo request body', async t => {
   }
 });
 
+test.serial('POST /webhook should verify an HMAC over the raw body bytes', async t => {
+  // 包含非 UTF-8 字节，经过字符串转换后签名会不一致
+  const body = Buffer.from([0x7b, 0xff, 0xfe, 0x00, 0xc3, 0x28, 0x7d]);
+  const signature = createHmac('sha256', 'webhook-secret').update(body).digest('hex');
+
+  const response = await axios.post('http://127.0.0.1:3002/webhook', body, {
+    headers: { 'Content-Type': 'application/octet-stream', 'X-Signature': signature },
+  });
+
+  t.is(response.data, 'valid');
+});
+
+test.serial('POST /echo-binary should return the exact request body bytes', async t => {
+  const body = Buffer.from([0x89, 0x50, 0x4e, 0x47, 0x00, 0xff, 0xfe, 0x1f, 0x8b]);
+
+  const response = await axios.post('http://127.0.0.1:3002/echo-binary', body, {
+    headers: { 'Content-Type': 'application/octet-stream' },
+    responseType: 'arraybuffer',
+  });
+
+  t.true(Buffer.from(response.data).equals(body));
+});
+
+test.serial('functional routes respond with the returned object', async t => {
+  const response = await axios.get('http://127.0.0.1:3002/functional/7');
+
+  t.is(response.status, 201);
+  t.is(response.headers['x-functional'], 'yes');
+  t.deepEqual(response.data, { id: '7' });
+});
+
+test.serial('501 for methods outside the supported set', async t => {
+  try {
+    await axios.request({ method: 'TRACE', url: 'http://127.0.0.1:3002/' });
+    t.fail('Should have thrown 501');
+  } catch (error: any) {
+    t.is(error.response.status, 501);
+    t.is(error.response.data.method, 'TRACE');
+  }
+});
+
+test.serial('HEAD falls back to the GET handler without a body', async t => {
+  const response = await axios.head('http://127.0.0.1:3002/');
+
+  t.is(response.status, 200);
+  t.is(response.headers['content-length'], '11');
+  t.is(response.data, '');
+});
+
 test.serial('404 for unknown routes', async t => {
   try {
     await axios.get('http://127.0.0.1:3002/unknown');
@@ -215,12 +457,449 @@ test.serial('POST /upload should handle file upload', async t => {
     t.truthy(res.data.filename); // UUID 生成的文件名
     t.truthy(res.data.path); // 文件路径
     t.is(res.data.path.startsWith('static/'), true);
+    // 返回的路径指向真实写入的文件
+    t.is(readFileSync(res.data.path, 'utf8'), fileContent);
+    unlinkSync(res.data.path);
   } catch (error) {
     console.log('Error in POST /upload', error);
     t.fail(`Request failed: ${error}`);
   }
 });
 
+// 发送原始 HTTP/1.0 请求，返回服务器关闭连接前收到的全部数据
+function rawRequest(request: string): Promise<string> {
+  return new Promise((resolve, reject) => {
+    const socket = net.connect(3002, '127.0.0.1', () => socket.write(request));
+    let data = '';
+    socket.setEncoding('utf8');
+    socket.setTimeout(5000, () => socket.destroy(new Error('connection was not closed')));
+    socket.on('data', chunk => {
+      data += chunk;
+    });
+    socket.on('end', () => resolve(data));
+    socket.on('error', reject);
+  });
+}
+
+// 测试 HTTP/1.0：响应使用 Content-Length，并在响应后关闭连接
+test.serial('HTTP/1.0 request should be answered with Content-Length and closed', async t => {
+  const response = await rawRequest('GET /version HTTP/1.0\r\nHost: 127.0.0.1\r\n\r\n');
+  const [head, body] = response.split('\r\n\r\n');
+
+  t.regex(head, /^HTTP\/1\.[01] 200/);
+  t.regex(head, /content-length: 8/i);
+  t.notRegex(head, /transfer-encoding: chunked/i);
+  t.is(body, 'HTTP/1.0');
+});
+
+test.serial('GET /sse-writer should stream events written through the SseWriter', async t => {
+  const response = await axios.get('http://127.0.0.1:3002/sse-writer', { responseType: 'text' });
+
+  t.regex(response.headers['content-type'], /text\/event-stream/);
+  t.is(response.data, ': connected\n\ndata: first\n\nevent: done\ndata: second\n\n');
+});
+
+test.serial('GET /cookies should parse request cookies and set one header per cookie', async t => {
+  const response = await axios.get('http://127.0.0.1:3002/cookies', {
+    headers: { Cookie: 'session=abc123; theme="light"' },
+  });
+
+  t.deepEqual(response.data, { session: 'abc123', theme: 'light' });
+  t.deepEqual(response.headers['set-cookie'], [
+    'session=abc123; Path=/; HttpOnly',
+    'theme=dark; Max-Age=3600; SameSite=Lax',
+  ]);
+});
+
+test.serial('concurrent identical requests to a coalesced route share one handler call', async t => {
+  const responses = await Promise.all(
+    Array.from({ length: 3 }, () => axios.get('http://127.0.0.1:3002/coalesced?page=1')),
+  );
+
+  for (const response of responses) {
+    t.is(response.status, 200);
+    t.deepEqual(response.data, { calls: 1 });
+  }
+});
+
+test.serial('coalesce is rejected on routes with unsafe methods', t => {
+  t.throws(() => postAsync('/coalesced-post', async (err, req) => {}, { coalesce: true }), {
+    message: /coalesce/,
+  });
+});
+
+test.serial('getBodyJsonResult explains why the JSON body failed to parse', async t => {
+  const invalid = await axios.post('http://127.0.0.1:3002/json-result', '{"a": 1,\n "b": }', {
+    headers: { 'Content-Type': 'application/json' },
+  });
+  t.regex(invalid.data.error, /line 2/);
+
+  const valid = await axios.post('http://127.0.0.1:3002/json-result', { a: 1 });
+  t.deepEqual(valid.data, { value: { a: 1 } });
+
+  const empty = await axios.post('http://127.0.0.1:3002/json-result');
+  t.deepEqual(empty.data, {});
+});
+
+test.serial('getHeaderPairs keeps every value of a repeated header in order', async t => {
+  const response = await rawRequest(
+    'GET /header-pairs HTTP/1.0\r\nHost: 127.0.0.1\r\nX-Tag: first\r\nX-Tag: second\r\n\r\n',
+  );
+  const body = JSON.parse(response.split('\r\n\r\n')[1]);
+
+  t.deepEqual(body.pairs, [
+    ['x-tag', 'first'],
+    ['x-tag', 'second'],
+  ]);
+  t.is(typeof body.header, 'string');
+});
+
+test.serial('Range requests on buffer responses return 206 or 416', async t => {
+  const partial = await axios.get('http://127.0.0.1:3002/media', {
+    headers: { Range: 'bytes=2-5' },
+    responseType: 'text',
+  });
+  t.is(partial.status, 206);
+  t.is(partial.headers['content-range'], 'bytes 2-5/10');
+  t.is(partial.data, '2345');
+
+  const unsatisfiable = await axios.get('http://127.0.0.1:3002/media', {
+    headers: { Range: 'bytes=20-' },
+    validateStatus: () => true,
+  });
+  t.is(unsatisfiable.status, 416);
+  t.is(unsatisfiable.headers['content-range'], 'bytes */10');
+});
+
+test.serial('maxConcurrentRequests answers 503 with Retry-After when all slots are busy', async t => {
+  // 路由是全局注册的，第二个服务器与主服务器共享 /slow
+  const limited = new Server({ host: '127.0.0.1', port: 3003, maxConcurrentRequests: 1 });
+  limited.start();
+  await new Promise(resolve => setTimeout(resolve, 500));
+
+  try {
+    const [first, second] = await Promise.all([
+      axios.get('http://127.0.0.1:3003/slow', { validateStatus: () => true }),
+      new Promise(resolve => setTimeout(resolve, 100)).then(() =>
+        axios.get('http://127.0.0.1:3003/slow', { validateStatus: () => true }),
+      ),
+    ]);
+
+    t.is(first.status, 200);
+    t.is(first.data, 'done');
+    t.is(second.status, 503);
+    t.is(second.headers['retry-after'], '1');
+  } finally {
+    await limited.stop();
+  }
+});
+
+test.serial('sendFileAsync streams the file with type and length from disk', async t => {
+  const expected = readFileSync('package.json', 'utf8');
+  const response = await axios.get('http://127.0.0.1:3002/files/package', { responseType: 'text' });
+
+  t.is(response.data, expected);
+  t.regex(response.headers['content-type'], /^application\/json/);
+  t.is(Number(response.headers['content-length']), Buffer.byteLength(expected));
+
+  const missing = await axios.get('http://127.0.0.1:3002/files/missing', { validateStatus: () => true });
+  t.is(missing.status, 404);
+});
+
+test.serial('isJsonRequest and isFormRequest follow the Content-Type', async t => {
+  const json = await axios.post('http://127.0.0.1:3002/body-kind', { a: 1 });
+  t.deepEqual(json.data, { json: true, form: false });
+
+  const form = await axios.post('http://127.0.0.1:3002/body-kind', 'a=1', {
+    headers: { 'Content-Type': 'application/x-www-form-urlencoded' },
+  });
+  t.deepEqual(form.data, { json: false, form: true });
+});
+
+test.serial('autoOptions answers CORS preflight requests without an OPTIONS route', async t => {
+  const response = await axios.options('http://127.0.0.1:3002/json-result', {
+    headers: {
+      Origin: 'https://app.example.com',
+      'Access-Control-Request-Method': 'POST',
+      'Access-Control-Request-Headers': 'content-type, x-token',
+    },
+  });
+
+  t.is(response.status, 204);
+  t.is(response.headers['allow'], 'POST, OPTIONS');
+  t.is(response.headers['access-control-allow-methods'], 'POST');
+  t.is(response.headers['access-control-allow-headers'], 'content-type, x-token');
+  // 允许的来源由应用决定，自动应答不回显 Origin
+  t.is(response.headers['access-control-allow-origin'], undefined);
+
+  const missing = await axios.options('http://127.0.0.1:3002/no-such-route', { validateStatus: () => true });
+  t.is(missing.status, 404);
+});
+
+test.serial('newRouteMultipartStreamAsync reads parts chunk by chunk', async t => {
+  const formData = new FormData();
+  formData.append('title', 'hello');
+  formData.append('file', new Blob(['x'.repeat(200 * 1024)], { type: 'text/plain' }), 'big.txt');
+
+  const response = await axios.post('http://127.0.0.1:3002/stream-upload', formData);
+
+  t.deepEqual(response.data, [
+    { name: 'title', filename: null, size: 5 },
+    { name: 'file', filename: 'big.txt', size: 200 * 1024 },
+  ]);
+});
+
+test.serial('cancelForceExit aborts a pending forceExit', async t => {
+  forceExit(1, 100);
+  cancelForceExit();
+
+  // 取消失败时进程会以退出码 1 结束
+  await new Promise(resolve => setTimeout(resolve, 200));
+  t.pass();
+});
+
+test.serial('sendErrorJsonAsync sends a typed error envelope', async t => {
+  const response = await axios.post('http://127.0.0.1:3002/users', {}, { validateStatus: () => true });
+
+  t.is(response.status, 409);
+  t.regex(response.headers['content-type'], /^application\/json/);
+  t.deepEqual(response.data, {
+    error: 'User already exists',
+    code: 'USER_EXISTS',
+    details: { field: 'email' },
+  });
+});
+
+test.serial('gzip request bodies are decompressed before parsing', async t => {
+  const response = await axios.post('http://127.0.0.1:3002/json', gzipSync(JSON.stringify({ message: 'zipped' })), {
+    headers: { 'Content-Type': 'application/json', 'Content-Encoding': 'gzip' },
+  });
+
+  t.deepEqual(response.data, { message: 'zipped' });
+});
+
+test.serial('decompressed bodies over maxBodySize are rejected with 413', async t => {
+  // 约 2MB 的重复内容压缩后只有几 KB，上限按解压后的大小计算
+  const response = await axios.post('http://127.0.0.1:3002/echo', gzipSync('x'.repeat(2 * 1024 * 1024)), {
+    headers: { 'Content-Type': 'text/plain', 'Content-Encoding': 'gzip' },
+    validateStatus: () => true,
+  });
+
+  t.is(response.status, 413);
+});
+
+test.serial('request bodies over maxBodySize are rejected with 413', async t => {
+  const response = await axios.post('http://127.0.0.1:3002/echo', 'x'.repeat(2 * 1024 * 1024), {
+    headers: { 'Content-Type': 'text/plain' },
+    validateStatus: () => true,
+  });
+
+  t.is(response.status, 413);
+  t.deepEqual(response.data, { error: 'Payload too large', maxBodySize: 1024 * 1024 });
+});
+
+test.serial('route groups register patterns under the shared prefix', async t => {
+  const response = await axios.get('http://127.0.0.1:3002/api/v1/ping');
+
+  t.is(response.data, 'pong');
+});
+
+test.serial('repeated query keys keep every value', async t => {
+  const response = await axios.get('http://127.0.0.1:3002/query-multi?tag=a&tag=b&page=1');
+
+  t.deepEqual(response.data, { params: { tag: ['a', 'b'], page: ['1'] }, tags: ['a', 'b'] });
+});
+
+test.serial('starting a running server again throws', t => {
+  t.throws(() => server.start(), { message: '服务器已在运行' });
+});
+
+test.serial('sendRedirectAsync sets the status and Location header', async t => {
+  const options = { maxRedirects: 0, validateStatus: () => true };
+  const temporary = await axios.get('http://127.0.0.1:3002/redirect', options);
+  t.is(temporary.status, 302);
+  t.is(temporary.headers.location, '/target?from=redirect');
+
+  const permanent = await axios.get('http://127.0.0.1:3002/redirect?permanent=1', options);
+  t.is(permanent.status, 301);
+});
+
+test.serial('a path registered for other methods answers 405 with an Allow header', async t => {
+  const response = await axios.post('http://127.0.0.1:3002/redirect', null, {
+    validateStatus: () => true,
+  });
+
+  t.is(response.status, 405);
+  t.is(response.headers.allow, 'GET, HEAD');
+  t.deepEqual(response.data.allowed, ['GET', 'HEAD']);
+});
+
+test.serial('getWildcard returns the catch-all segment of the path', async t => {
+  const response = await axios.get('http://127.0.0.1:3002/assets/a/b/c.png');
+
+  t.deepEqual(response.data, { wildcard: 'a/b/c.png', params: { path: 'a/b/c.png' } });
+});
+
+test.serial('POST requests can override the method when methodOverride is enabled', async t => {
+  const byHeader = await axios.post('http://127.0.0.1:3002/override', null, {
+    headers: { 'X-HTTP-Method-Override': 'PUT' },
+  });
+  t.is(byHeader.data, 'PUT');
+
+  const byQuery = await axios.post('http://127.0.0.1:3002/override?_method=put');
+  t.is(byQuery.data, 'PUT');
+
+  // 无效的覆盖值按原始的 POST 处理
+  const invalid = await axios.post('http://127.0.0.1:3002/override', null, {
+    headers: { 'X-HTTP-Method-Override': 'TRACE' },
+    validateStatus: () => true,
+  });
+  t.is(invalid.status, 405);
+});
+
+test.serial('sendAsync sets status, content type and body in one call', async t => {
+  const response = await axios.get('http://127.0.0.1:3002/send-once');
+
+  t.is(response.status, 201);
+  t.is(response.headers['content-type'], 'application/json');
+  t.deepEqual(response.data, { created: true });
+});
+
+test.serial('handlers read a read-only copy of the shared app state', async t => {
+  setAppState({ db: { host: 'localhost', pool: 4 } });
+
+  const response = await axios.get('http://127.0.0.1:3002/app-state');
+
+  t.deepEqual(response.data, { db: { host: 'localhost', pool: 4 } });
+});
+
+test.serial('before hook can short-circuit and after hook sees the final status', async t => {
+  const statuses: Array<number | null> = [];
+  setBeforeHook(async (err, req) => {
+    if (req.getHeader('authorization') === 'secret') {
+      req.next();
+      return;
+    }
+    await req.setStatusCodeAsync(401);
+    await req.sendTextAsync('unauthorized');
+  });
+  setAfterHook((err, req) => {
+    statuses.push(req.getResponseStatus());
+  });
+
+  try {
+    const denied = await axios.get('http://127.0.0.1:3002/', { validateStatus: () => true });
+    t.is(denied.status, 401);
+    t.is(denied.data, 'unauthorized');
+
+    const allowed = await axios.get('http://127.0.0.1:3002/', {
+      headers: { authorization: 'secret' },
+    });
+    t.is(allowed.data, 'hello world');
+  } finally {
+    setBeforeHook(null);
+    setAfterHook(null);
+  }
+
+  // after 钩子不等待执行完成，给回调队列一点时间
+  await new Promise(resolve => setTimeout(resolve, 50));
+  t.deepEqual(statuses, [401, 200]);
+});
+
+test.serial('a before hook that returns without sending continues to the handler', async t => {
+  const seen: string[] = [];
+  setBeforeHook(async (err, req) => {
+    await new Promise(resolve => setTimeout(resolve, 10));
+    seen.push(req.getPath());
+  });
+
+  try {
+    const response = await axios.get('http://127.0.0.1:3002/');
+    t.is(response.data, 'hello world');
+    t.deepEqual(seen, ['/']);
+  } finally {
+    setBeforeHook(null);
+  }
+});
+
+test.serial('coalesced requests each pass their own before and after hooks', async t => {
+  const statuses: Array<number | null> = [];
+  setBeforeHook(async (err, req) => {
+    if (req.getHeader('authorization') === 'secret') {
+      return;
+    }
+    await req.setStatusCodeAsync(401);
+    await req.sendTextAsync('unauthorized');
+  });
+  setAfterHook((err, req) => {
+    statuses.push(req.getResponseStatus());
+  });
+
+  try {
+    const url = 'http://127.0.0.1:3002/coalesced?page=hooks';
+    const authorized = { headers: { authorization: 'secret' } };
+    const [leader, follower, denied] = await Promise.all([
+      axios.get(url, authorized),
+      axios.get(url, authorized),
+      axios.get(url, { validateStatus: () => true }),
+    ]);
+
+    // 未通过 before 钩子的请求不会复用 leader 的受保护响应
+    t.is(denied.status, 401);
+    t.is(leader.status, 200);
+    t.deepEqual(follower.data, leader.data);
+  } finally {
+    setBeforeHook(null);
+    setAfterHook(null);
+  }
+
+  await new Promise(resolve => setTimeout(resolve, 50));
+  t.deepEqual([...statuses].sort(), [200, 200, 401]);
+});
+
+test.serial('getElapsedMs in the after hook covers the handler time', async t => {
+  const elapsed: number[] = [];
+  setAfterHook((err, req) => {
+    elapsed.push(req.getElapsedMs());
+  });
+
+  try {
+    // 处理函数会等待 200ms 再发送响应
+    await axios.get('http://127.0.0.1:3002/coalesced');
+  } finally {
+    setAfterHook(null);
+  }
+
+  await new Promise(resolve => setTimeout(resolve, 50));
+  t.is(elapsed.length, 1);
+  t.true(elapsed[0] >= 150);
+});
+
+test.serial('addHeaderAsync rejects header names with control characters', async t => {
+  const response = await axios.get('http://127.0.0.1:3002/bad-header');
+
+  t.is(response.data, true);
+  t.is(response.headers['set-cookie'], undefined);
+});
+
+test.serial('a handler that throws synchronously gets a fast 500', async t => {
+  const started = Date.now();
+  const response = await axios.get('http://127.0.0.1:3002/throws', { validateStatus: () => true });
+
+  t.is(response.status, 500);
+  t.is(response.data, 'Internal Server Error');
+  t.true(Date.now() - started < 5000);
+});
+
+test.serial('listRoutes lists each registered method and pattern once', t => {
+  const routes = listRoutes();
+
+  t.true(routes.some(route => route.method === 'GET' && route.pattern === '/'));
+  t.true(routes.some(route => route.method === 'POST' && route.pattern === '/echo'));
+  const keys = routes.map(route => `${route.method} ${route.pattern}`);
+  t.is(new Set(keys).size, keys.length);
+});
+
 test.after(async t => {
   cleanupRouter();
   await server.stop(); // 添加await等待服务器真正停止
@@ -29,6 +29,16 @@ getAsync('/json', async (err, req) => {
   await req.sendObjectAsync({ message: 'Hello JSON', timestamp: Date.now() });
 });
 
+// 只读取路径参数的路由，用于衡量请求构建本身的开销（查询参数和请求体按需解析）
+getAsync('/users/:id', async (err, req) => {
+  if (err) {
+    await req.setStatusCodeAsync(500);
+    await req.sendErrorAsync('Internal Server Error');
+    return;
+  }
+  await req.sendTextAsync(req.getPathParam('id') ?? '');
+});
+
 postAsync('/echo', async (err, req) => {
   if (err) {
     await req.setStatusCodeAsync(500);
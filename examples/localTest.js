@@ -41,7 +41,7 @@ get('/api/test/:id', (err, req) => {
 const result = server.start();
 console.log('✅ 服务器启动结果:', result);
 
-console.log('🌐 服务器运行在 http://127.0.0.1:3002');
+console.log(`🌐 服务器运行在 ${result.url}`);
 console.log('📝 访问 http://127.0.0.1:3002/ 来测试路由');
 console.log('⏹  按 Ctrl+C 停止服务器');
 
@@ -65,8 +65,8 @@ const server = new Server({
 
 try {
   const result = server.start();
-  console.log(`   ✅ 服务器启动成功: ${result}`);
-  console.log('   🌐 服务器运行在 http://127.0.0.1:3001');
+  console.log('   ✅ 服务器启动成功');
+  console.log(`   🌐 服务器运行在 ${result.url}`);
 } catch (error) {
   console.log(`   ❌ 服务器启动失败: ${error.message}`);
 }
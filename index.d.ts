@@ -9,24 +9,145 @@ export declare class DetachedRequestWrapper {
   getQueryString(): string;
   /** 获取URI */
   getUri(): string;
-  /** 获取查询参数作为对象 - 零拷贝优化：使用预计算缓存，无运行时开销 */
+  /** 获取请求的 HTTP 版本，例如 HTTP/1.0、HTTP/1.1、HTTP/2.0 */
+  getHttpVersion(): string;
+  /**
+   * 一次取回路径、方法、查询参数、请求头、路径参数、请求体大小和 Content-Type，
+   * 处理函数需要其中多项时可以减少 FFI 调用次数
+   */
+  getRequestInfo(): RequestInfo;
+  /** 获取查询参数作为对象 - 第一次调用时解析并缓存 */
   getQueryParams(): { [key: string]: string };
+  /** 获取查询参数，保留重复的键，例如 ?tag=a&tag=b 返回 { tag: ["a", "b"] } */
+  getQueryParamsMulti(): Record<string, Array<string>>;
+  /** 获取查询参数 name 的所有值，按出现顺序排列，不存在时返回空数组 */
+  getQueryValues(name: string): Array<string>;
+  /**
+   * 按规格读取并转换查询参数，例如 ?active=true 转为布尔值、?ids=1,2,3 转为数组
+   * 缺失的参数使用默认值；转换失败的参数同样使用默认值，并在 errors 中列出
+   */
+  getQueryTyped(spec: Record<string, QueryParamSpec>): TypedQuery;
   /** 获取原始请求体字符串 */
   getBodyString(): string;
+  /**
+   * 获取收到的原始请求体字节，未做任何解码或转换，没有请求体时返回 null
+   * 唯一的例外是 Content-Encoding（gzip / deflate / br / zstd）压缩的请求体，这里得到的是解压后的字节
+   * 校验 Webhook 签名（如 Stripe、GitHub 的 HMAC）时必须使用此方法，getBodyString 会替换非 UTF-8 字节导致签名不匹配
+   */
+  getRawBody(): Buffer | null;
+  /** 与 getRawBody 相同，只是没有请求体时返回空 Buffer 而不是 null，省去调用方的空值判断 */
+  getBodyBuffer(): Buffer;
+  /**
+   * 校验 Webhook 签名：对原始请求体计算 HMAC-SHA256，与 headerName 请求头中的十六进制签名做常量时间比较
+   * prefix 用于带前缀的格式，例如 GitHub 的 X-Hub-Signature-256 传入 "sha256="；请求头缺失或格式不符时返回 false
+   */
+  verifyHmacSha256(headerName: string, secret: string, prefix?: string | undefined | null): boolean;
+  /**
+   * 校验 Stripe Webhook 的 Stripe-Signature 请求头（t=时间戳,v1=签名），签名内容为 "时间戳.原始请求体"
+   * 时间戳与当前时间相差超过 toleranceSeconds（默认 300 秒）时视为重放，返回 false
+   */
+  verifyStripeSignature(secret: string, toleranceSeconds?: number | undefined | null): boolean;
   /** 检查请求体是否为空 */
   hasBody(): boolean;
   /** 获取请求体大小 */
   getBodySize(): number;
-  /** 尝试将请求体解析为JSON对象 - 零拷贝优化：使用预计算缓存，无运行时开销 */
-  getBodyJson(): { [key: string]: any };
+  /**
+   * 获取请求声明的 Content-Length，请求头缺失或无法解析时返回 null
+   * 与 getBodySize 不同，它不依赖请求体是否已读取；分块传输的请求没有 Content-Length，两者可能不一致
+   */
+  getContentLength(): number | null;
+  /**
+   * 尝试将请求体解析为JSON对象 - 第一次调用时解析并缓存
+   * 默认使用 SIMD 解析器，超出 2^53 的整数转换为 JavaScript number 时会丢失精度，
+   * 需要精确的 64 位整数时传入 { bigIntAsString: true }
+   */
+  getBodyJson(options?: JsonBodyOptions | undefined | null): { [key: string]: any };
+  /**
+   * 解析 JSON 请求体并返回结果或失败原因，用于区分"没有请求体"和"请求体不是合法的 JSON"
+   * 没有请求体时 value 和 error 都为 null；Content-Type 不是 application/json 或解析失败时 error 为原因
+   */
+  getBodyJsonResult(): JsonBodyResult;
+  /**
+   * 🚀 流式遍历 JSON 数组请求体：逐个解析顶层元素并交给回调，返回元素个数
+   * 解析失败时抛出错误，错误信息中包含出错元素的下标
+   * 请求体不会被整体解析为 JSON，同一时间只保留一个元素，内存占用与单个元素大小相关而与数组长度无关
+   */
+  forEachJsonElement(callback: (element: any) => void): number;
+  /** 检查是否为 JSON 请求（Content-Type 包含 application/json），只检查请求头，不解析请求体 */
+  isJsonRequest(): boolean;
+  /** 检查是否为表单请求（application/x-www-form-urlencoded 或 multipart/form-data），只检查请求头，不解析请求体 */
+  isFormRequest(): boolean;
+  /** 获取请求体类型，只根据 Content-Type 和请求体是否存在判断，不会触发解析 */
+  getBodyType(): 'json' | 'urlencoded' | 'multipart' | 'text' | 'binary' | 'empty';
+  /** 按字段规格校验 JSON 请求体，返回缺失或类型不匹配的字段名，全部通过时返回空数组 */
+  checkRequiredFields(spec: Record<string, 'string' | 'number' | 'boolean' | 'object' | 'array'>): Array<string>;
   /** 获取指定的请求头 */
   getHeader(name: string): string | null;
   /** 获取所有请求头 */
   getHeaders(): { [key: string]: string };
+  /**
+   * 获取所有请求头的 [名称, 值] 列表，同名请求头的每一次出现都会保留，并按收到的顺序排列；名称为小写
+   * getHeaders 中同名请求头只保留一个值；不同名称之间的顺序由 actix 的 HeaderMap 决定，与线上顺序不一定一致
+   * 值不是合法的 UTF-8 时其中的无效字节被替换为 U+FFFD
+   */
+  getHeaderPairs(): Array<[string, string]>;
+  /** 获取 Cookie 请求头解析后的所有 Cookie，第一次调用时解析；同名 Cookie 以第一个为准 */
+  getCookies(): { [key: string]: string };
+  /** 获取指定名称的 Cookie，不存在时返回 null */
+  getCookie(name: string): string | null;
+  /**
+   * 根据 Range 请求头和调用方提供的资源总大小计算字节区间，没有 Range 头时返回 null
+   * 区间超出资源范围时抛出错误，处理函数应返回 416；否则由处理函数自行返回 206 和 Content-Range
+   */
+  getByteRange(totalSize: number): ByteRange | null;
+  /**
+   * 乐观锁检查：没有 If-Match 头或其中的 ETag 与当前版本匹配时返回 true
+   * 返回 false 时处理函数应调用 sendPreconditionFailedAsync 返回 412
+   */
+  checkIfMatch(currentEtag: string): boolean;
+  /**
+   * 乐观锁检查：没有 If-Unmodified-Since 头或资源在该时间之后未被修改时返回 true
+   * lastModified 为资源最后修改时间的毫秒时间戳；请求同时带有 If-Match 时应以 checkIfMatch 为准
+   */
+  checkIfUnmodifiedSince(lastModified: number): boolean;
+  /**
+   * 为会话生成 CSRF 令牌（会话 ID + 过期时间的 HMAC），默认 3600 秒后过期
+   * 双重提交时把令牌同时放在 Cookie 和表单字段中，提交后用 verifyCsrfToken 校验
+   */
+  generateCsrfToken(sessionId: string, ttlSeconds?: number | undefined | null): string;
+  /** 校验 CSRF 令牌的签名和有效期，签名比较是常量时间的 */
+  verifyCsrfToken(sessionId: string, token: string): boolean;
   /** 获取路径参数作为对象 */
   getPathParams(): { [key: string]: string };
   /** 获取指定名称的路径参数值 */
   getPathParam(name: string): string | null;
+  /**
+   * 获取通配参数的值，例如路由 /static/*path 匹配请求 /static/a/b/c.png 时返回 "a/b/c.png"
+   * 匹配到的路由没有通配参数时返回 null
+   */
+  getWildcard(): string | null;
+  /**
+   * 在 before 钩子中调用：不发送响应，立即继续执行路由处理函数，不必等钩子执行完毕
+   * 不在 before 钩子中、已经调用过或已经发送响应时抛出错误
+   */
+  next(): void;
+  /**
+   * 获取 setAppState 设置的共享应用状态，未设置时返回 null
+   * 返回的是副本，对每个请求只读，修改它不会影响其他请求
+   */
+  getAppState(): any;
+  /** 在 after 钩子中获取最终响应状态码，其他情况下返回 null */
+  getResponseStatus(): number | null;
+  /**
+   * 获取从请求被分发（请求体读取完成、包装器创建）到现在经过的毫秒数，带小数
+   * 使用单调时钟，不受系统时间调整影响；在 after 钩子中返回响应就绪时记录的固定值，不包含钩子自身的排队时间
+   */
+  getElapsedMs(): number;
+  /**
+   * 获取客户端 IP；配置了可信代理（trustedProxies）时会沿 Forwarded / X-Forwarded-For / X-Real-IP 解析真实地址
+   * 在分发时解析一次；Unix 域套接字连接没有对端地址，返回 null
+   */
+  getRemoteAddr(): string | null;
   /**
    * 异步发送文本响应 - 返回Promise，支持await
    *
@@ -51,6 +172,25 @@ export declare class DetachedRequestWrapper {
    * 函数内部只进行JSON序列化和响应发送操作，不涉及内存安全问题。
    */
   sendObjectAsync(obj: any): Promise<void>;
+  /**
+   * 在 Rust 侧用 serde_json 序列化并发送 JSON 响应，与 sendObjectAsync 的结果相同 - 返回Promise，支持await
+   * 名称沿用最初的 simd-json 实现；实测 simd-json 序列化 serde_json::Value 约慢一倍，因此实际不使用 SIMD
+   *
+   * # Safety
+   * 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+   * 函数内部只进行JSON序列化和响应发送操作，不涉及内存安全问题。
+   */
+  sendJsonSimdAsync(value: any): Promise<void>;
+  /**
+   * 一次调用完成状态码、Content-Type 和响应体的发送，减少 FFI 往返次数
+   * contentType 为 JSON 类型（application/json、application/*+json）时按 JSON 响应发送（会应用 jsonEnvelope），
+   * 其他类型按文本发送；未指定时为 text/plain; charset=utf-8。状态码或 contentType 无效时抛出错误
+   *
+   * # Safety
+   * 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+   * 函数内部只进行响应发送操作，不涉及内存安全问题。
+   */
+  sendAsync(status: number, body: string, contentType?: string | undefined | null): Promise<void>;
   /**
    * 异步发送空响应 - 返回Promise，支持await
    *
@@ -59,6 +199,109 @@ export declare class DetachedRequestWrapper {
    * 函数内部只进行响应发送操作，不涉及内存安全问题。
    */
   sendEmptyAsync(): Promise<void>;
+  /**
+   * 异步发送重定向：设置 Location 响应头并发送空响应体
+   * 默认 302；permanent 为 true 时 GET/HEAD 请求使用 301，其他方法使用 308（保留请求方法和请求体）
+   * url 含有控制字符等不能作为响应头值的字符时抛出错误
+   *
+   * # Safety
+   * 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+   * 函数内部只进行响应发送操作，不涉及内存安全问题。
+   */
+  sendRedirectAsync(url: string, permanent?: boolean | undefined | null): Promise<void>;
+  /**
+   * 异步发送二进制响应：未通过 addHeaderAsync 设置 Content-Type 时使用 application/octet-stream，
+   * 开启 ServerOptions.sniffContentType 时根据内容推断
+   * GET / HEAD 请求带单个区间的 Range 头时自动返回 206 和对应的片段，区间超出范围时返回 416
+   *
+   * # Safety
+   * 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+   * 函数内部只进行响应发送操作，不涉及内存安全问题。
+   */
+  sendBufferAsync(body: Buffer): Promise<void>;
+  /**
+   * 异步发送磁盘上的文件：分块流式读取，不会把整个文件读入内存；相对路径相对于进程的工作目录
+   * Content-Type 根据扩展名确定（通过 addHeaderAsync 设置的优先），Content-Length 取自文件大小；
+   * 文件不存在时返回 404；GET / HEAD 请求带单个区间的 Range 头时返回 206 和对应的片段
+   * 路径原样使用，不做目录限制，不要直接拼接客户端传入的路径
+   *
+   * # Safety
+   * 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+   * 函数内部只进行响应发送操作，不涉及内存安全问题。
+   */
+  sendFileAsync(path: string): Promise<void>;
+  /**
+   * 异步发送缓存的响应 - 命中 cachePut 写入的缓存时直接发送并返回 true，未命中返回 false
+   *
+   * # Safety
+   * 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+   * 函数内部只进行缓存读取和响应发送操作，不涉及内存安全问题。
+   */
+  sendCachedAsync(key: string): Promise<boolean>;
+  /**
+   * 把当前请求（方法、路径、查询字符串、请求头、请求体）转发到上游服务，并把上游响应以流的方式返回给客户端
+   * 逐跳头部不会转发，同名请求头全部保留；等待上游响应头超过 requestTimeoutMs 的 80% 时返回 504，连接失败等其他错误返回 502
+   *
+   * # Safety
+   * 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+   * 函数内部只进行上游请求和响应发送操作，不涉及内存安全问题。
+   */
+  forwardAsync(upstreamBaseUrl: string): Promise<void>;
+  /**
+   * 以 text/event-stream 响应当前请求，并把连接加入 Rust 侧 SSE 广播，之后通过 broadcastSseEvent 推送事件
+   * 连接会一直保持到客户端断开、服务器停止，或因积压过多事件被断开
+   *
+   * # Safety
+   * 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+   * 函数内部只进行响应发送操作，不涉及内存安全问题。
+   */
+  sendSseStreamAsync(): Promise<void>;
+  /**
+   * 以 text/event-stream 响应当前请求，返回只属于这个连接的 SseWriter，通过 write / writeEvent 推送事件、close 结束
+   * 每个事件到达后立即写出，不会等到关闭；客户端断开后 write 返回 false
+   *
+   * # Safety
+   * 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+   * 函数内部只进行响应发送操作，不涉及内存安全问题。
+   */
+  startSseAsync(): Promise<SseWriter>;
+  /**
+   * 低层发送接口：使用给定的状态码、仅使用给定的头部并原样发送请求体
+   * 不会自动补充 Content-Type，之前通过 addHeaderAsync 添加的头部也会被忽略；Content-Length、Date 等仍由 HTTP 层生成
+   *
+   * # Safety
+   * 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+   * 函数内部只进行响应发送操作，不涉及内存安全问题。
+   */
+  sendRawAsync(status: number, headers: Record<string, string> | undefined | null, body: Buffer): Promise<void>;
+  /**
+   * 异步发送模板响应：{{key}} 替换为 HTML 转义后的值，{{{key}}} 原样输出，缺失的键替换为空字符串
+   * 未设置 Content-Type 时以 text/html; charset=utf-8 发送
+   *
+   * # Safety
+   * 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+   * 函数内部只进行字符串替换和响应发送操作，不涉及内存安全问题。
+   */
+  sendTemplateAsync(template: string, values: Record<string, any>): Promise<void>;
+  /**
+   * 发送 gRPC Trailers-Only 响应：只有头部、没有响应体，grpc-status / grpc-message 随头部一起发送
+   * HTTP/2 上这是一个带 END_STREAM 的 HEADERS 帧，gRPC 规范将其视为只包含 trailers 的错误响应；
+   * actix 不支持在响应体之后单独发送 trailers，因此不能先发送数据再补发状态
+   * 仅支持 HTTP/2 连接，HTTP/1.x 请求上调用时不发送任何内容并抛出错误
+   *
+   * # Safety
+   * 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+   * 函数内部只进行响应发送操作，不涉及内存安全问题。
+   */
+  sendGrpcTrailersOnlyAsync(grpcStatus: number, grpcMessage?: string | undefined | null): Promise<void>;
+  /**
+   * 异步发送 412 Precondition Failed 响应，用于 checkIfMatch / checkIfUnmodifiedSince 不成立时
+   *
+   * # Safety
+   * 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+   * 函数内部只进行响应发送操作，不涉及内存安全问题。
+   */
+  sendPreconditionFailedAsync(): Promise<void>;
   /**
    * 异步发送服务器错误响应 - 返回Promise，支持await
    *
@@ -68,7 +311,16 @@ export declare class DetachedRequestWrapper {
    */
   sendErrorAsync(message?: string | undefined | null): Promise<void>;
   /**
-   * 异步设置响应状态码 - 返回Promise，支持await
+   * 异步以指定的 4xx / 5xx 状态码发送统一格式的 JSON 错误响应 {"error": message, "code": code, "details": details}
+   * 没有 details 时省略该字段；状态码不是 4xx / 5xx 时抛出错误
+   *
+   * # Safety
+   * 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+   * 函数内部只进行响应发送操作，不涉及内存安全问题。
+   */
+  sendErrorJsonAsync(status: number, code: string, message: string, details?: any | undefined | null): Promise<void>;
+  /**
+   * 异步设置响应状态码 - 返回Promise，支持await；超出 100-599 的状态码（例如 600）不会被设置，返回 false
    *
    * # Safety
    * 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
@@ -76,16 +328,45 @@ export declare class DetachedRequestWrapper {
    */
   setStatusCodeAsync(status: number): Promise<boolean>;
   /**
-   * 异步添加响应头 - 返回Promise，支持await
+   * 异步添加响应头 - 返回Promise，支持await；名称或值不合法（包含空格、换行等字符）时抛出错误
    *
    * # Safety
    * 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
    * 函数内部只进行响应头添加操作，不涉及内存安全问题。
    */
   addHeaderAsync(key: string, value: string): Promise<void>;
+  /**
+   * 设置 Cookie：按 options 中的属性生成一个 Set-Cookie 响应头，多次调用会产生多个 Set-Cookie 头
+   * 值需要由调用方自行编码（例如 encodeURIComponent）；名称、值或属性不合法时抛出错误
+   *
+   * # Safety
+   * 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+   * 函数内部只进行响应头添加操作，不涉及内存安全问题。
+   */
+  setCookieAsync(name: string, value: string, options?: CookieOptions | undefined | null): Promise<void>;
+  /**
+   * 在最终响应上添加预加载 Link 头部，例如 "</style.css>; rel=preload; as=style"，只给出 URL 时按 rel=preload 处理
+   * 不会发送 103 Early Hints 中间响应（actix-web 不支持），浏览器在收到最终响应后才开始预加载；
+   * Cloudflare 等 CDN/代理可以根据这些 Link 头部自行向浏览器发送 103
+   *
+   * # Safety
+   * 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+   * 函数内部只进行响应头添加操作，不涉及内存安全问题。
+   */
+  addPreloadLinksAsync(links: Array<string>): Promise<void>;
+  /**
+   * 设置响应优先级提示（RFC 9218）：urgency 为 0-7，数值越小越优先，默认 3；incremental 表示客户端可以边接收边处理
+   * 只对 HTTP/2 请求生效，以 Priority 响应头的形式发送，供支持 RFC 9218 的 CDN/代理调度；HTTP/1.x 请求忽略并返回 false
+   * actix-web 使用的 h2 不提供设置流权重/依赖（PRIORITY 帧）的接口，RFC 9113 也已弃用该方案，本连接自身的帧调度不受影响
+   *
+   * # Safety
+   * 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+   * 函数内部只进行响应头添加操作，不涉及内存安全问题。
+   */
+  setPriorityAsync(urgency: number, incremental?: boolean | undefined | null): Promise<boolean>;
   /**
    * 异步获取表单数据参数，支持 application/x-www-form-urlencoded 和 multipart/form-data 格式
-   * 对于文件字段，直接返回文件信息对象 - 零拷贝优化：使用预计算缓存，无运行时开销
+   * 对于文件字段，直接返回文件信息对象 - 第一次调用时解析并缓存
    *
    * # Safety
    * 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
@@ -93,13 +374,30 @@ export declare class DetachedRequestWrapper {
    */
   getFormDataAsync(): Promise<any>;
   /**
-   * 异步获取表单数据中指定键的值 - 零拷贝优化：使用预计算缓存，无运行时开销
+   * 异步获取表单数据中指定键的值 - 第一次调用时解析并缓存
    *
    * # Safety
    * 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
    * 函数内部只进行缓存数据读取操作，不涉及内存安全问题。
    */
   getFormValueAsync(key: string): Promise<any | null>;
+  /**
+   * 流式 multipart：前进到下一个 part 并返回其元信息，没有更多 part 时返回 null
+   * 仅对 newRouteMultipartStreamAsync 注册的路由可用，上一个 part 未读完的数据会被跳过
+   *
+   * # Safety
+   * 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+   * 函数内部只进行请求体流的读取操作，不涉及内存安全问题。
+   */
+  nextPartAsync(): Promise<MultipartPartInfo | null>;
+  /**
+   * 流式 multipart：读取当前 part 的下一个数据块，当前 part 读完时返回 null
+   *
+   * # Safety
+   * 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+   * 函数内部只进行请求体流的读取操作，不涉及内存安全问题。
+   */
+  readPartChunkAsync(): Promise<Buffer | null>;
 }
 
 export declare class RequestWrapper {
@@ -113,12 +411,27 @@ export declare class RequestWrapper {
   getUri(): string;
   /** 获取查询参数作为对象 - 零拷贝优化：延迟解析，只计算一次 */
   getQueryParams(): { [key: string]: string };
+  /** 获取查询参数，保留重复的键，例如 ?tag=a&tag=b 返回 { tag: ["a", "b"] } */
+  getQueryParamsMulti(): Record<string, Array<string>>;
+  /** 获取查询参数 name 的所有值，按出现顺序排列，不存在时返回空数组 */
+  getQueryValues(name: string): Array<string>;
   /** 获取原始请求体字符串 - 零拷贝优化：直接使用 Bytes 的零拷贝特性 */
   getBodyString(): string;
+  /** 获取原始请求体作为 Buffer，不做 UTF-8 解码，适用于 protobuf、图片、gzip 等二进制请求体；没有请求体时返回空 Buffer */
+  getBodyBuffer(): Buffer;
   /** 检查请求体是否为空 - 零拷贝优化：直接检查，不解析内容 */
   hasBody(): boolean;
   /** 获取请求体大小 - 零拷贝优化：直接返回字节长度 */
   getBodySize(): number;
+  /**
+   * 获取请求声明的 Content-Length，请求头缺失或无法解析时返回 null
+   * 与 getBodySize 不同，它不依赖请求体是否已读取；分块传输的请求没有 Content-Length，两者可能不一致
+   */
+  getContentLength(): number | null;
+  /** 检查是否为JSON请求 - 零拷贝优化：只检查Content-Type，不解析内容 */
+  isJsonRequest(): boolean;
+  /** 检查是否为表单请求 - 零拷贝优化：只检查Content-Type，不解析内容 */
+  isFormRequest(): boolean;
   /**
    * 🚀 SIMD 优化的 JSON 解析 - 使用 simd-json 提升 2-3 倍性能
    * 尝试将请求体解析为JSON对象 - 零拷贝优化：延迟解析，只计算一次
@@ -148,28 +461,163 @@ export declare class RequestWrapper {
   sendJson(json: string): void;
   /** 🚀 SIMD 优化的对象序列化 - 发送对象作为JSON响应 */
   sendObject(obj: any): void;
+  /**
+   * 在 Rust 侧用 serde_json 序列化并发送 JSON 响应，与 sendObject 的结果相同
+   * 名称沿用最初的 simd-json 实现；实测 simd-json 序列化 serde_json::Value 约慢一倍，因此实际不使用 SIMD
+   */
+  sendJsonSimd(value: any): void;
   /** 发送空响应 */
   sendEmpty(): void;
   /** 发送服务器错误响应 */
   sendError(message?: string | undefined | null): void;
-  /** 设置响应状态码 */
+  /**
+   * 以指定的 4xx / 5xx 状态码发送统一格式的 JSON 错误响应 {"error": message, "code": code, "details": details}
+   * 没有 details 时省略该字段；状态码不是 4xx / 5xx 时抛出错误
+   */
+  sendErrorJson(status: number, code: string, message: string, details?: any | undefined | null): void;
+  /** 设置响应状态码；超出 100-599 的状态码（例如 600）不会被设置，返回 false */
   setStatusCode(status: number): boolean;
-  /** 添加响应头 */
+  /** 添加响应头；名称或值不合法（包含空格、换行等字符）时抛出错误 */
   addHeader(key: string, value: string): void;
 }
 
 export declare class Server {
   constructor(options: ServerOptions);
-  start(): string;
-  stop(): Promise<string>;
+  /**
+   * 启动服务器，返回实际监听的地址；port 为 0 时由操作系统分配空闲端口
+   * 同一个实例已经在运行（或正在启动）时返回错误，需要先调用 stop()
+   */
+  start(): ServerStartInfo;
+  /**
+   * 停止服务器；传入 { waitForHandlers: true } 时会继续等待所有已分发的处理函数发送响应或被放弃
+   * 返回值说明是优雅关闭，还是超过 gracefulTimeoutMs 后强制关闭了剩余连接：本实例有请求在生成响应之前被中断即视为强制关闭
+   */
+  stop(options?: StopOptions | undefined | null): Promise<string>;
+  /** 暂停接受新连接，已建立的连接继续处理请求，用于维护窗口 */
+  pause(): Promise<void>;
+  /** 恢复接受新连接 */
+  resume(): Promise<void>;
 }
 
-/** 清理所有路由 */
+/** 单个 SSE 连接的写入端，由 startSseAsync 返回 */
+export declare class SseWriter {
+  /**
+   * 发送一条只有 data 的事件，多行数据会拆分为多个 data 字段
+   * 连接已关闭（客户端断开或已调用 close）时返回 false；积压超过 256 个事件时连接被断开并返回 false
+   */
+  write(data: string): boolean;
+  /** 发送一条带事件名的事件，返回值与 write 相同 */
+  writeEvent(event: string, data: string): boolean;
+  /** 结束事件流，已发送的事件仍会写出；可以重复调用 */
+  close(): void;
+  /** 连接是否仍然打开 */
+  isOpen(): boolean;
+}
+
+/**
+ * 为响应添加签名头：值为响应体的 HMAC-SHA256 十六进制签名，在 Rust 侧计算，不经过 JavaScript
+ * route 为路由注册时的模式（例如 "/users/:id"），为 null 时作用于所有路由；流式响应不签名
+ * 签名针对最终发送的响应体，即 jsonEnvelope 包装和 Range 截取之后的内容
+ */
+export declare function addResponseSignature(route: string | undefined | null, headerName: string, secret: string): void;
+
+/**
+ * 以同一个前缀批量注册路由，例如 addRouteGroup("/api/v1", [{ method: Methods.GET, pattern: "/users", callback }])
+ * 前缀与模式之间多余的斜杠会被合并；路由模式按 newRouteAsync 的规则校验，
+ * 某条路由注册失败时返回错误，排在它之前的路由保持已注册
+ */
+export declare function addRouteGroup(prefix: string, routes: Array<GroupRoute>): void;
+
+/**
+ * 为路由的流式响应（例如 forwardAsync 转发的日志）逐行添加行号前缀，例如 "1 "；行号按响应从 1 开始计数
+ * route 为路由注册时的模式（例如 "/logs/:id"）；转换后的响应改为分块传输，text/event-stream 响应不做转换
+ */
+export declare function addStreamLineNumbers(route: string): void;
+
+/**
+ * 为路由的流式响应逐行添加时间戳前缀，例如 "[1700000000000] "，时间戳为该行开始写出时的 Unix 毫秒时间戳
+ * route 为路由注册时的模式（例如 "/logs/:id"）；转换后的响应改为分块传输，text/event-stream 响应不做转换
+ */
+export declare function addStreamTimestamps(route: string): void;
+
+/** 绑定端口被占用时的重试选项 */
+export interface BindRetryOptions {
+  /** 最多重试次数（不含第一次绑定） */
+  attempts: number;
+  /** 第一次重试前的等待时间（毫秒），之后每次翻倍 */
+  delayMs: number;
+}
+
+/**
+ * 向所有通过 sendSseStreamAsync 建立的 SSE 连接广播一个事件，返回当前连接数
+ * event 为空字符串时只发送 data；积压超过 256 个事件的慢连接会被断开
+ */
+export declare function broadcastSseEvent(event: string, data: string): number;
+
+/** 字节区间，start 和 end 都包含在内 */
+export interface ByteRange {
+  start: number;
+  end: number;
+}
+
+/** 清空响应缓存 */
+export declare function cacheClear(): void;
+
+/** 缓存的响应 */
+export interface CachedResponse {
+  status: number;
+  headers: Record<string, string>;
+  body: Buffer;
+}
+
+/** 读取响应缓存，未命中或已过期时返回 null */
+export declare function cacheGet(key: string): CachedResponse | null;
+
+/** 写入响应缓存，ttlSecs 为 0 时不缓存 */
+export declare function cachePut(
+  key: string,
+  status: number,
+  headers: Record<string, string>,
+  body: Buffer,
+  ttlSecs: number,
+): void;
+
+/** 回调队列统计 */
+export interface CallbackQueueStats {
+  /** 成功加入回调队列的请求数 */
+  queued: number;
+  /** 加入回调队列失败、直接返回 503 的请求数 */
+  dropped: number;
+  /** 已分发但尚未发送响应的回调数 */
+  inFlight: number;
+}
+
+/** 清理所有路由，同时移除 setBeforeHook / setAfterHook 设置的全局钩子 */
 export declare function cleanupRouter(): void;
 
+/** 移除所有已注册的响应后处理器 */
+export declare function clearResponsePostProcessors(): void;
+
 /** 清理路由缓存 - 在需要强制刷新缓存时调用 */
 export declare function clearRouterCache(): void;
 
+/** 移除所有已注册的流式响应块转换 */
+export declare function clearStreamTransforms(): void;
+
+/** setCookieAsync 的 Cookie 属性 */
+export interface CookieOptions {
+  /** 有效期（秒），0 或负数表示立即过期（删除 Cookie）；未设置时为会话 Cookie */
+  maxAge?: number;
+  path?: string;
+  domain?: string;
+  /** 禁止 JavaScript 通过 document.cookie 读取 */
+  httpOnly?: boolean;
+  /** 只通过 HTTPS 发送 */
+  secure?: boolean;
+  /** Strict / Lax / None（不区分大小写），None 必须同时设置 secure */
+  sameSite?: string;
+}
+
 /** 注册DELETE路由（兼容旧版本） */
 export declare function del(route: string, callback: (err: Error | null, arg: RequestWrapper) => any): void;
 
@@ -177,20 +625,41 @@ export declare function del(route: string, callback: (err: Error | null, arg: Re
 export declare function delAsync(
   route: string,
   callback: (err: Error | null, arg: DetachedRequestWrapper) => any,
+  options?: RouteOptions | undefined | null,
 ): void;
 
 export interface FileInfo {
   type: string;
   originalName: string;
   filename: string;
-  path: string;
+  /** 保存路径，内存模式下不存在 */
+  path?: string;
   contentType?: string;
   size: number;
+  /** 内存模式（setMultipartInMemory）下 base64 编码的文件内容 */
+  data?: string;
 }
 
 export declare function forceCleanup(): void;
 
-export declare function forceExit(): void;
+/**
+ * 在 delayMs 毫秒（默认 100）后以 exitCode（默认 0）强制退出进程，不等待任何清理
+ * 延迟期间可以调用 cancelForceExit 取消；重复调用时只有最后一次生效
+ */
+export declare function forceExit(exitCode?: number | undefined | null, delayMs?: number | undefined | null): void;
+
+/** 取消尚未执行的 forceExit，没有等待中的 forceExit 时不做任何事 */
+export declare function cancelForceExit(): void;
+
+/** 函数式路由处理函数的返回值 */
+export interface FunctionalResponse {
+  /** 状态码，默认 200 */
+  status?: number;
+  /** 响应头 */
+  headers?: Record<string, string>;
+  /** 字符串作为文本发送，其他值序列化为 JSON，缺省时为空响应 */
+  body?: any;
+}
 
 /** 注册GET路由（兼容旧版本） */
 export declare function get(route: string, callback: (err: Error | null, arg: RequestWrapper) => any): void;
@@ -199,8 +668,63 @@ export declare function get(route: string, callback: (err: Error | null, arg: Re
 export declare function getAsync(
   route: string,
   callback: (err: Error | null, arg: DetachedRequestWrapper) => any,
+  options?: RouteOptions | undefined | null,
+): void;
+
+/** 获取回调队列统计，用于观察高负载下是否有请求因队列已满被丢弃 */
+export declare function getCallbackQueueStats(): CallbackQueueStats;
+
+/** 路由分组中的一条路由 */
+export interface GroupRoute {
+  method: Methods;
+  /** 相对于分组前缀的路由模式，例如 "/users/:id" */
+  pattern: string;
+  callback: (err: Error | null, arg: DetachedRequestWrapper) => any;
+  options?: RouteOptions;
+}
+
+/**
+ * 注册HEAD路由（异步版本）
+ * 没有注册 HEAD 路由时，HEAD 请求由同一路径的 GET 处理函数处理，发送时去掉响应体
+ */
+export declare function headAsync(
+  route: string,
+  callback: (err: Error | null, arg: DetachedRequestWrapper) => any,
+  options?: RouteOptions | undefined | null,
 ): void;
 
+/** getBodyJson 的解析选项 */
+export interface JsonBodyOptions {
+  /**
+   * 为 true 时超出 ±(2^53 - 1) 的整数以十进制字符串返回，保证 64 位 ID 不丢失精度
+   * 此模式使用 serde_json 标准解析器而不是 SIMD 解析器，且每次调用都会重新解析；
+   * 超出 64 位范围的整数和高精度浮点数仍会丢失精度
+   */
+  bigIntAsString?: boolean;
+}
+
+/** getBodyJsonResult 的结果：value 和 error 都为 null 表示没有请求体 */
+export interface JsonBodyResult {
+  /** 解析得到的 JSON 值 */
+  value?: any;
+  /** 解析失败的原因，例如 "expected value at line 3 column 8" */
+  error?: string;
+}
+
+/** JSON 响应信封选项 */
+export interface JsonEnvelopeOptions {
+  /** 存放处理函数响应体的字段名，默认 data */
+  dataKey?: string;
+  /** 存放服务端元信息（requestId、timestamp）的字段名，默认 meta */
+  metaKey?: string;
+}
+
+/** 列出所有已注册的路由，可用于生成 OpenAPI 文档或调试；返回顺序不固定 */
+export declare function listRoutes(): Array<RouteInfo>;
+
+/** 列出上传目录中的文件，按修改时间从旧到新排序；目录不存在时返回空数组 */
+export declare function listUploads(): Array<UploadedFile>;
+
 /** HTTP方法枚举 */
 export declare const enum Methods {
   GET = 0,
@@ -208,6 +732,15 @@ export declare const enum Methods {
   PUT = 2,
   PATCH = 3,
   DELETE = 4,
+  OPTIONS = 5,
+  HEAD = 6,
+}
+
+/** 流式 multipart 中单个 part 的元信息 */
+export interface MultipartPartInfo {
+  name?: string;
+  filename?: string;
+  contentType?: string;
 }
 
 /** 注册新路由（兼容旧版本） */
@@ -222,6 +755,35 @@ export declare function newRouteAsync(
   route: string,
   method: Methods,
   callback: (err: Error | null, arg: DetachedRequestWrapper) => any,
+  options?: RouteOptions | undefined | null,
+): void;
+
+/**
+ * 注册函数式路由：处理函数同步返回 {status, headers, body} 对象或字符串，由 Rust 转换为响应，不需要调用 send 系列方法
+ * 返回值不是字符串或响应对象（包括返回 Promise）、或处理函数抛出异常时返回 500
+ */
+export declare function newRouteFunctional(
+  route: string,
+  method: Methods,
+  callback: (err: Error | null, arg: DetachedRequestWrapper) => FunctionalResponse | string,
+  options?: RouteOptions | undefined | null,
+): void;
+
+/**
+ * 注册流式 multipart 路由（异步版本）
+ * 请求体不会被缓冲或写入磁盘，处理函数通过 nextPartAsync / readPartChunkAsync 逐个读取 part
+ */
+export declare function newRouteMultipartStreamAsync(
+  route: string,
+  method: Methods,
+  callback: (err: Error | null, arg: DetachedRequestWrapper) => any,
+): void;
+
+/** 注册OPTIONS路由（异步版本），例如应答 CORS 预检请求 */
+export declare function optionsAsync(
+  route: string,
+  callback: (err: Error | null, arg: DetachedRequestWrapper) => any,
+  options?: RouteOptions | undefined | null,
 ): void;
 
 /** 注册PATCH路由（兼容旧版本） */
@@ -231,6 +793,7 @@ export declare function patch(route: string, callback: (err: Error | null, arg:
 export declare function patchAsync(
   route: string,
   callback: (err: Error | null, arg: DetachedRequestWrapper) => any,
+  options?: RouteOptions | undefined | null,
 ): void;
 
 /** 注册POST路由（兼容旧版本） */
@@ -240,8 +803,12 @@ export declare function post(route: string, callback: (err: Error | null, arg: R
 export declare function postAsync(
   route: string,
   callback: (err: Error | null, arg: DetachedRequestWrapper) => any,
+  options?: RouteOptions | undefined | null,
 ): void;
 
+/** 删除上传目录中最后修改时间早于 olderThanSecs 秒之前的文件，返回删除的文件数 */
+export declare function purgeUploads(olderThanSecs: number): number;
+
 /** 注册PUT路由（兼容旧版本） */
 export declare function put(route: string, callback: (err: Error | null, arg: RequestWrapper) => any): void;
 
@@ -249,11 +816,258 @@ export declare function put(route: string, callback: (err: Error | null, arg: Re
 export declare function putAsync(
   route: string,
   callback: (err: Error | null, arg: DetachedRequestWrapper) => any,
+  options?: RouteOptions | undefined | null,
 ): void;
 
+/** 单个查询参数的规格 */
+export interface QueryParamSpec {
+  /** 参数类型：int / float / bool / string / csv（逗号分隔的字符串数组） */
+  type: string;
+  /** 参数缺失或转换失败时使用的默认值，未设置时为 null */
+  default?: any;
+}
+
+/** 录制选项 */
+export interface RecordOptions {
+  /** 请求体/响应体最多记录的字节数，默认 65536 */
+  maxBodyBytes?: number;
+  /** 需要脱敏的头部名称，默认 authorization、proxy-authorization、cookie、set-cookie */
+  redactHeaders?: Array<string>;
+}
+
+/** 开始录制请求/响应到指定目录，每个请求生成一个 JSON 文件；可以在运行时随时开启或关闭 */
+export declare function recordToDir(path: string, options?: RecordOptions | undefined | null): void;
+
+/** 请求元数据快照，一次 FFI 调用取回处理函数常用的全部信息 */
+export interface RequestInfo {
+  path: string;
+  method: string;
+  queryString: string;
+  queryParams: Record<string, string>;
+  headers: Record<string, string>;
+  pathParams: Record<string, string>;
+  bodySize: number;
+  contentType?: string;
+}
+
+/** 已注册的路由 */
+export interface RouteInfo {
+  /** 请求方法，例如 "GET" */
+  method: string;
+  /** 注册时使用的路由模式，例如 "/users/:id" */
+  pattern: string;
+}
+
+/** 路由注册选项 */
+export interface RouteOptions {
+  /** 路由名称，用于通过 urlFor 反向生成 URL，例如 "user.show" */
+  name?: string;
+  /**
+   * 合并相同的并发请求（方法、路径和查询字符串都相同），只调用一次处理函数并把结果共享给其余请求，默认 false
+   * 适用于开销大、结果可缓存的 GET 路由，避免缓存失效时大量请求同时打到后端；流式响应无法共享，其余请求会各自调用处理函数
+   * 只能用于 GET / HEAD 路由，其他方法设置为 true 时注册失败
+   * 状态码和响应头（包括 Set-Cookie）会原样共享，不要用于按用户返回不同内容的路由
+   */
+  coalesce?: boolean;
+}
+
 export interface ServerOptions {
   host: string;
   port: number;
+  /** 慢请求阈值（毫秒），处理耗时超过该值的请求会输出一条结构化警告日志 */
+  slowRequestThresholdMs?: number;
+  /**
+   * 可信代理列表（CIDR 或单个 IP），设置后会根据 Forwarded / X-Forwarded-For / X-Real-IP 解析真实客户端 IP
+   * 只有直连对端在列表中时才采信这些头部，未设置时始终使用直连地址，避免客户端伪造
+   */
+  trustedProxies?: Array<string>;
+  /** IP 白名单（CIDR 或单个 IP），非空时只允许列表内的客户端访问 */
+  allowedIps?: Array<string>;
+  /** IP 黑名单（CIDR 或单个 IP），命中时在执行任何 JavaScript 之前返回 403 */
+  blockedIps?: Array<string>;
+  /** 响应体大小上限（字节），send 系列方法发送超出上限的响应体时会返回错误，提示改用分页或流式响应 */
+  maxResponseBodySize?: number;
+  /**
+   * 请求体大小上限（字节），默认 262144（256KB），超出时在调用处理函数之前返回 413
+   * 流式 multipart 路由不缓冲请求体，不受此限制
+   */
+  maxBodySize?: number;
+  /**
+   * 空闲连接超时（毫秒），同时作用于两个阶段：
+   * - keep-alive 超时：连接上一个请求完成后，等待下一个请求的最长时间
+   * - 首个请求超时：新建立的连接在该时间内没有发来完整请求头时直接关闭
+   *
+   * 未设置时沿用 actix 默认值（keep-alive 5 秒，首个请求 5 秒）
+   */
+  idleConnectionTimeoutMs?: number;
+  /**
+   * keep-alive 超时（秒）：连接上一个请求完成后等待下一个请求的最长时间，0 表示关闭 keep-alive
+   * 优先于 idleConnectionTimeoutMs；两者都未设置时沿用 actix 默认值 5 秒
+   */
+  keepAliveSecs?: number;
+  /**
+   * 读取请求头的超时（毫秒）：连接建立后在该时间内没有收到完整请求头时返回 408 并关闭，0 表示不限制
+   * 高延迟的移动网络可以适当调大；优先于 idleConnectionTimeoutMs，两者都未设置时沿用 actix 默认值 5000 毫秒
+   */
+  clientTimeoutMs?: number;
+  /** 启用后，成功的 JSON 响应会自动包装为 {"data": ..., "meta": {"requestId", "timestamp"}}，错误响应不受影响 */
+  jsonEnvelope?: JsonEnvelopeOptions;
+  /** 请求路径只差末尾斜杠（多一个或少一个）时，返回 308 重定向到已注册的路径，默认关闭 */
+  redirectTrailingSlash?: boolean;
+  /** 没有显式注册 HEAD 路由时，由同一路径的 GET 处理函数应答 HEAD 请求（去掉响应体，保留 Content-Length），默认开启 */
+  autoHead?: boolean;
+  /**
+   * 没有显式注册 OPTIONS 路由时，对路径在其他请求方法下存在的 OPTIONS 请求（如 CORS 预检）直接返回 204 和 Allow 头，默认关闭
+   * 同时回显 Access-Control-Request-Method（仅当该方法已注册）和 Access-Control-Request-Headers 到对应的 Access-Control-Allow-* 头；不设置 Access-Control-Allow-Origin，允许哪些来源由应用决定，需要放行跨域预检时请自行注册 OPTIONS 路由
+   */
+  autoOptions?: boolean;
+  /**
+   * 允许 POST 请求通过 X-HTTP-Method-Override 请求头或 _method 查询参数模拟 PUT、DELETE 等方法，默认关闭
+   * 只接受支持的请求方法，无效的覆盖值按原始的 POST 处理；处理函数中 getMethod() 返回覆盖后的方法
+   */
+  methodOverride?: boolean;
+  /**
+   * Rust 侧 JSON Lines 访问日志输出目标："stdout" 或文件路径，未设置时不记录
+   * 每行格式为 {ts, method, path, status, durMs, queueMs, handlerMs, ip, bytes, contentEncoding}，通过缓冲的异步写入器输出，不阻塞请求处理
+   */
+  accessLog?: string;
+  /**
+   * 启用 actix Logger 中间件并按该格式输出到标准错误，例如 "%a \"%r\" %s %b %T"（格式占位符见 actix-web 文档）
+   * 为 "default" 时使用 actix 的默认格式；未设置时不启用，与 accessLog 互不影响
+   */
+  logFormat?: string;
+  /** 回调队列已满时阻塞等待入队（会占用 actix worker 线程），默认关闭：入队失败的请求立即返回 503 */
+  blockingCallbackQueue?: boolean;
+  /** CSRF 令牌签名密钥，设置后处理函数可以使用 generateCsrfToken / verifyCsrfToken */
+  csrfSecret?: string;
+  /**
+   * Host 白名单，例如 ["example.com", "*.example.com"]（通配符只匹配子域名，不区分大小写，忽略端口）
+   * 非空时 Host 不在列表中的请求在路由匹配之前返回 421 Misdirected Request，为空或未设置时不检查
+   */
+  allowedHosts?: Array<string>;
+  /**
+   * 原始字节响应没有设置 Content-Type 时，根据魔数推断 PNG / JPEG / GIF / WebP / PDF / HTML / JSON / 纯文本，默认关闭
+   * 无法识别时仍使用 application/octet-stream；推断成功时附带 X-Content-Type-Options: nosniff
+   */
+  sniffContentType?: boolean;
+  /**
+   * 端口被占用（EADDRINUSE）时按指数退避重试绑定，适用于开发时快速重启、旧进程的连接尚未释放的情况
+   * 重试在 start() 中同步进行，期间会阻塞 Node.js 主线程，所有重试的等待时间合计不超过 5 秒；其他绑定错误不重试，所有重试失败后返回最后一次的错误
+   */
+  bindRetry?: BindRetryOptions;
+  /** 等待处理函数发送响应的超时时间（毫秒），默认 10000，超时返回 504；为 0 时不限制 */
+  requestTimeoutMs?: number;
+  /**
+   * 同时分发给处理函数的请求数上限，避免突发流量在 Node.js 侧堆积无限的待处理请求；未设置或为 0 时不限制
+   * 名额在读取完请求体之后才占用；达到上限时新请求最多等待 100 毫秒，仍没有空闲名额则返回 503 和 Retry-After；处理函数发送响应或超时后释放名额
+   */
+  maxConcurrentRequests?: number;
+  /**
+   * actix 工作线程数，不设置时使用 actix 的默认值（CPU 核心数）
+   * 请求处理都在 Node.js 事件循环上执行，工作线程只负责网络 IO 和分发，通常 1-2 个就足够
+   */
+  workers?: number;
+  /**
+   * stop() 时等待进行中请求的最长时间（毫秒，按秒向上取整），超过后强制关闭剩余连接，默认沿用 actix 的 30 秒
+   * stop() 的返回值会说明是优雅关闭还是强制关闭
+   */
+  gracefulTimeoutMs?: number;
+  /**
+   * 监听 Unix 域套接字（例如 nginx 通过 UDS 转发），设置后忽略 host 和 port；stop() 时删除套接字文件
+   * 所在目录必须已经存在；仅支持 Unix 类系统
+   */
+  unixSocket?: string;
+  /**
+   * PEM 格式的 TLS 证书链路径，与 tlsKeyPath 同时设置时以 HTTPS 提供服务（同一个实例不再提供 HTTP）
+   * 证书或私钥无法解析、或两者不匹配时 start() 返回错误；不能与 unixSocket 同时使用
+   * 需要启用 tls 特性构建（npm run build:tls），未启用时 start() 返回错误
+   */
+  tlsCertPath?: string;
+  /** PEM 格式的 TLS 私钥路径（PKCS#8、PKCS#1 或 SEC1） */
+  tlsKeyPath?: string;
+}
+
+/** 服务器启动结果 */
+export interface ServerStartInfo {
+  /** 监听的主机，Unix 域套接字模式下为套接字路径 */
+  host: string;
+  /** 实际监听的端口，port 为 0 时是操作系统分配的端口；Unix 域套接字模式下为 0 */
+  port: number;
+  /** 服务器地址，例如 http://127.0.0.1:3000（HTTPS 模式下为 https://），Unix 域套接字模式下为 unix:/path/to/server.sock */
+  url: string;
+}
+
+/**
+ * 设置全局 after 钩子：响应生成之后以同一个请求调用，通过 req.getResponseStatus() 读取最终状态码
+ * 钩子不能再修改响应，适合记录日志；传入 null 移除钩子
+ */
+export declare function setAfterHook(callback?: ((err: Error | null, arg: DetachedRequestWrapper) => any) | undefined | null): void;
+
+/**
+ * 设置共享应用状态（任意可以序列化为 JSON 的值），处理函数通过 req.getAppState() 读取
+ * 对每个请求只读：处理函数得到的是副本，需要更新时再次调用 setAppState 替换整个状态，之后开始的请求读到新值
+ */
+export declare function setAppState(value: any): void;
+
+/**
+ * 设置全局 before 钩子：在每个路由处理函数之前以同一个请求调用
+ * 钩子发送响应（例如 401）时跳过处理函数；执行完毕（异步钩子的 Promise 完成）时没有发送响应则继续执行处理函数，
+ * 也可以调用 req.next() 提前继续；钩子抛出异常时返回 500；传入 null 移除钩子
+ * 钩子同样受 requestTimeoutMs 限制；合并请求（coalesce）的每个请求都先经过自己的 before 钩子，放行之后才复用 leader 的结果，after 钩子同样对每个请求调用
+ */
+export declare function setBeforeHook(callback?: ((err: Error | null, arg: DetachedRequestWrapper) => any) | undefined | null): void;
+
+/**
+ * 设置 multipart 文件字段是否只保存在内存中（默认 false，写入上传目录）
+ * 开启后文件信息不包含 path，文件内容以 base64 放在 data 字段中，由处理函数自行决定保存到哪里；
+ * 适用于 serverless 等没有持久磁盘的环境，注意整个文件会随请求一起保存在内存中
+ */
+export declare function setMultipartInMemory(enabled: boolean): void;
+
+/**
+ * 设置每个请求方法的路由 LRU 缓存容量（默认 1000），0 表示禁用缓存，每次都直接进行路由匹配
+ * 必须在服务器启动前调用；缓存在第一次路由匹配时创建，创建后再调用会返回错误
+ */
+export declare function setRouteCacheCapacity(capacity: number): void;
+
+/**
+ * 设置 multipart 上传文件的保存目录（默认为工作目录下的 static），相对路径相对于进程工作目录
+ * 目录在保存文件时按需创建；无法创建时对应的文件字段返回 { type: "error", originalName, error }
+ */
+export declare function setUploadDir(path: string): void;
+
+/** 停止服务器选项 */
+export interface StopOptions {
+  /** 连接关闭后继续等待已分发但尚未发送响应的处理函数，默认 false */
+  waitForHandlers?: boolean;
+  /** 等待处理函数的超时时间（毫秒），默认 10000 */
+  handlerTimeoutMs?: number;
 }
 
+/** 停止录制 */
+export declare function stopRecording(): void;
+
 export declare function sum(a: number, b: number): number;
+
+/** getQueryTyped 的返回值 */
+export interface TypedQuery {
+  /** 转换后的参数对象 */
+  values: Record<string, any>;
+  /** 转换失败、已改用默认值的参数名 */
+  errors: Array<string>;
+}
+
+/** 上传目录中的文件 */
+export interface UploadedFile {
+  filename: string;
+  /** 文件大小（字节） */
+  size: number;
+  /** 最后修改时间的毫秒时间戳 */
+  modified: number;
+}
+
+/**
+ * 根据路由名称反向生成 URL，例如 urlFor("user.show", { id: 42 }) 返回 /users/42
+ * 名称未注册或缺少路径参数时抛出错误
+ */
+export declare function urlFor(name: string, params?: Record<string, string | number | boolean>): string;
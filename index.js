@@ -359,21 +359,50 @@ module.exports = nativeBinding;
 module.exports.DetachedRequestWrapper = nativeBinding.DetachedRequestWrapper;
 module.exports.RequestWrapper = nativeBinding.RequestWrapper;
 module.exports.Server = nativeBinding.Server;
+module.exports.SseWriter = nativeBinding.SseWriter;
+module.exports.addResponseSignature = nativeBinding.addResponseSignature;
+module.exports.addRouteGroup = nativeBinding.addRouteGroup;
+module.exports.addStreamLineNumbers = nativeBinding.addStreamLineNumbers;
+module.exports.addStreamTimestamps = nativeBinding.addStreamTimestamps;
+module.exports.broadcastSseEvent = nativeBinding.broadcastSseEvent;
+module.exports.cacheClear = nativeBinding.cacheClear;
+module.exports.cacheGet = nativeBinding.cacheGet;
+module.exports.cachePut = nativeBinding.cachePut;
+module.exports.cancelForceExit = nativeBinding.cancelForceExit;
 module.exports.cleanupRouter = nativeBinding.cleanupRouter;
+module.exports.clearResponsePostProcessors = nativeBinding.clearResponsePostProcessors;
 module.exports.clearRouterCache = nativeBinding.clearRouterCache;
+module.exports.clearStreamTransforms = nativeBinding.clearStreamTransforms;
 module.exports.del = nativeBinding.del;
 module.exports.delAsync = nativeBinding.delAsync;
 module.exports.forceCleanup = nativeBinding.forceCleanup;
 module.exports.forceExit = nativeBinding.forceExit;
 module.exports.get = nativeBinding.get;
 module.exports.getAsync = nativeBinding.getAsync;
+module.exports.getCallbackQueueStats = nativeBinding.getCallbackQueueStats;
+module.exports.headAsync = nativeBinding.headAsync;
+module.exports.listRoutes = nativeBinding.listRoutes;
+module.exports.listUploads = nativeBinding.listUploads;
 module.exports.Methods = nativeBinding.Methods;
 module.exports.newRoute = nativeBinding.newRoute;
 module.exports.newRouteAsync = nativeBinding.newRouteAsync;
+module.exports.newRouteFunctional = nativeBinding.newRouteFunctional;
+module.exports.newRouteMultipartStreamAsync = nativeBinding.newRouteMultipartStreamAsync;
+module.exports.optionsAsync = nativeBinding.optionsAsync;
 module.exports.patch = nativeBinding.patch;
 module.exports.patchAsync = nativeBinding.patchAsync;
 module.exports.post = nativeBinding.post;
 module.exports.postAsync = nativeBinding.postAsync;
+module.exports.purgeUploads = nativeBinding.purgeUploads;
 module.exports.put = nativeBinding.put;
 module.exports.putAsync = nativeBinding.putAsync;
+module.exports.recordToDir = nativeBinding.recordToDir;
+module.exports.setAfterHook = nativeBinding.setAfterHook;
+module.exports.setAppState = nativeBinding.setAppState;
+module.exports.setBeforeHook = nativeBinding.setBeforeHook;
+module.exports.setMultipartInMemory = nativeBinding.setMultipartInMemory;
+module.exports.setRouteCacheCapacity = nativeBinding.setRouteCacheCapacity;
+module.exports.setUploadDir = nativeBinding.setUploadDir;
+module.exports.stopRecording = nativeBinding.stopRecording;
 module.exports.sum = nativeBinding.sum;
+module.exports.urlFor = nativeBinding.urlFor;
@@ -0,0 +1,211 @@
+use actix_web::body::{BodySize, MessageBody};
+use actix_web::http::Method;
+use actix_web::HttpResponse;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncWrite, AsyncWriteExt, BufWriter};
+use tokio::sync::mpsc;
+
+use crate::response::response_content_encoding;
+use crate::timing::HandlerTiming;
+
+// Rust 侧访问日志模块
+// 请求处理只负责格式化一行 JSON 并投递到通道，由独立任务批量写入文件或标准输出，
+// 通道写满时直接丢弃日志而不是阻塞请求
+
+/// 通道容量，超过后新的日志会被丢弃
+const ACCESS_LOG_CHANNEL_CAPACITY: usize = 8192;
+
+/// 日志输出目标："stdout" 或文件路径
+enum AccessLogSink {
+  Stdout,
+  File(PathBuf),
+}
+
+impl AccessLogSink {
+  fn parse(sink: &str) -> Self {
+    if sink.eq_ignore_ascii_case("stdout") {
+      AccessLogSink::Stdout
+    } else {
+      AccessLogSink::File(PathBuf::from(sink))
+    }
+  }
+}
+
+/// 请求处理侧持有的日志句柄
+#[derive(Clone)]
+pub struct AccessLogger {
+  sender: mpsc::Sender<String>,
+}
+
+/// 后台写入任务
+pub struct AccessLogWriter {
+  receiver: mpsc::Receiver<String>,
+  sink: AccessLogSink,
+}
+
+/// 创建访问日志通道，写入任务需要在 tokio 运行时中通过 run 启动
+pub fn access_log_channel(sink: &str) -> (AccessLogger, AccessLogWriter) {
+  let (sender, receiver) = mpsc::channel(ACCESS_LOG_CHANNEL_CAPACITY);
+  (
+    AccessLogger { sender },
+    AccessLogWriter {
+      receiver,
+      sink: AccessLogSink::parse(sink),
+    },
+  )
+}
+
+/// 进入中间件之前记录的请求信息，拿到经过所有中间件的最终响应后写出一条访问日志
+pub struct AccessLogEntry {
+  logger: AccessLogger,
+  method: Method,
+  path: String,
+  remote_addr: Option<IpAddr>,
+  started_at: Instant,
+}
+
+impl AccessLogEntry {
+  pub fn finish<B: MessageBody>(self, response: &HttpResponse<B>) {
+    self.logger.log(
+      &self.method,
+      &self.path,
+      self.started_at.elapsed(),
+      self.remote_addr,
+      response,
+    );
+  }
+}
+
+impl AccessLogger {
+  /// 开始记录一次请求，计时从此刻开始
+  pub fn begin(&self, method: &Method, path: &str, remote_addr: Option<IpAddr>) -> AccessLogEntry {
+    AccessLogEntry {
+      logger: self.clone(),
+      method: method.clone(),
+      path: path.to_string(),
+      remote_addr,
+      started_at: Instant::now(),
+    }
+  }
+
+  /// 记录一次请求，格式为 {ts, method, path, status, durMs, queueMs, handlerMs, ip, bytes, contentEncoding}
+  /// queueMs / handlerMs 只在请求进入 JavaScript 处理函数时存在，其他情况为 null
+  fn log<B: MessageBody>(
+    &self,
+    method: &Method,
+    path: &str,
+    elapsed: Duration,
+    remote_addr: Option<IpAddr>,
+    response: &HttpResponse<B>,
+  ) {
+    let ts = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|now| now.as_millis() as u64)
+      .unwrap_or_default();
+    let bytes = match response.body().size() {
+      BodySize::Sized(size) => Some(size),
+      BodySize::None => Some(0),
+      BodySize::Stream => None,
+    };
+
+    let timing = HandlerTiming::from_response(response);
+    let record = serde_json::json!({
+      "ts": ts,
+      "method": method.as_str(),
+      "path": path,
+      "status": response.status().as_u16(),
+      "durMs": elapsed.as_secs_f64() * 1000.0,
+      "queueMs": timing.map(|t| t.queue_ms()),
+      "handlerMs": timing.map(|t| t.handler_ms()),
+      "ip": remote_addr.map(|ip| ip.to_string()),
+      "bytes": bytes,
+      "contentEncoding": response_content_encoding(response),
+    });
+
+    // 不阻塞请求：通道已满或写入任务已退出时丢弃这条日志
+    let _ = self.sender.try_send(record.to_string());
+  }
+}
+
+impl AccessLogWriter {
+  /// 持续写入日志，所有 AccessLogger 被释放（服务器停止）后退出
+  pub async fn run(self) {
+    let AccessLogWriter { receiver, sink } = self;
+    match sink {
+      AccessLogSink::Stdout => write_lines(receiver, tokio::io::stdout()).await,
+      AccessLogSink::File(path) => {
+        let file = tokio::fs::OpenOptions::new()
+          .create(true)
+          .append(true)
+          .open(&path)
+          .await;
+        match file {
+          Ok(file) => write_lines(receiver, file).await,
+          Err(e) => eprintln!("❌ 无法打开访问日志文件 {}: {}", path.display(), e),
+        }
+      }
+    }
+  }
+}
+
+/// 把 actix Logger 中间件的输出写到标准错误，供 ServerOptions.logFormat 使用
+/// 只输出 actix_web::middleware::logger 的日志，避免依赖库的其他日志混入
+struct MiddlewareLogSink;
+
+impl log::Log for MiddlewareLogSink {
+  fn enabled(&self, metadata: &log::Metadata) -> bool {
+    metadata.level() <= log::Level::Info
+      && metadata
+        .target()
+        .starts_with("actix_web::middleware::logger")
+  }
+
+  fn log(&self, record: &log::Record) {
+    if self.enabled(record.metadata()) {
+      eprintln!("{}", record.args());
+    }
+  }
+
+  fn flush(&self) {}
+}
+
+static MIDDLEWARE_LOG_SINK: MiddlewareLogSink = MiddlewareLogSink;
+
+/// 安装 MiddlewareLogSink；进程中已经安装了其他 log 实现（例如宿主程序的 env_logger）时保留原有实现
+pub fn install_middleware_log_sink() {
+  if log::set_logger(&MIDDLEWARE_LOG_SINK).is_ok() {
+    log::set_max_level(log::LevelFilter::Info);
+  }
+}
+
+async fn write_lines<W: AsyncWrite + Unpin>(mut receiver: mpsc::Receiver<String>, output: W) {
+  let mut writer = BufWriter::new(output);
+
+  while let Some(line) = receiver.recv().await {
+    let mut result = write_line(&mut writer, &line).await;
+
+    // 🚀 批量写入：把已经排队的日志一起写完再 flush，减少系统调用
+    while result.is_ok() {
+      match receiver.try_recv() {
+        Ok(line) => result = write_line(&mut writer, &line).await,
+        Err(_) => break,
+      }
+    }
+
+    if let Err(e) = result.and(writer.flush().await) {
+      eprintln!("❌ 写入访问日志失败: {}", e);
+    }
+  }
+
+  let _ = writer.flush().await;
+}
+
+async fn write_line<W: AsyncWrite + Unpin>(
+  writer: &mut BufWriter<W>,
+  line: &str,
+) -> std::io::Result<()> {
+  writer.write_all(line.as_bytes()).await?;
+  writer.write_all(b"\n").await
+}
@@ -0,0 +1,20 @@
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+// 共享应用状态
+// 启动时创建一次的配置等数据以 JSON 形式保存在 Rust 侧，每个处理函数通过 req.getAppState() 读取，
+// 不需要在每个路由的闭包中重复获取；处理函数拿到的是副本，修改副本不会影响其他请求
+
+static APP_STATE: RwLock<Option<Arc<serde_json::Value>>> = RwLock::new(None);
+
+/// 当前的应用状态，未设置时为 None
+pub fn app_state() -> Option<Arc<serde_json::Value>> {
+  APP_STATE.read().clone()
+}
+
+#[napi]
+/// 设置共享应用状态（任意可以序列化为 JSON 的值），处理函数通过 req.getAppState() 读取
+/// 对每个请求只读：处理函数得到的是副本，需要更新时再次调用 setAppState 替换整个状态，之后开始的请求读到新值
+pub fn set_app_state(value: serde_json::Value) {
+  *APP_STATE.write() = Some(Arc::new(value));
+}
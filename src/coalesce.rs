@@ -0,0 +1,129 @@
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use tokio::sync::oneshot;
+
+use crate::response::JsResponse;
+
+// 请求合并（singleflight）
+// 开启合并的路由上，方法、路径和查询字符串都相同的并发请求只调用一次处理函数：
+// 第一个请求（leader）正常分发，其余请求（follower）等待并复用它的结果
+
+/// 等待处理函数响应的结果
+pub enum HandlerOutcome {
+  Response(JsResponse),
+  /// 处理函数没有发送响应就释放了请求
+  NoResponse,
+  /// 超过 requestTimeoutMs 仍未发送响应
+  TimedOut,
+}
+
+impl HandlerOutcome {
+  /// 复制一份结果给 follower，流式响应体无法复制时返回 None
+  fn try_clone(&self) -> Option<Self> {
+    Some(match self {
+      HandlerOutcome::Response(response) => HandlerOutcome::Response(response.try_clone()?),
+      HandlerOutcome::NoResponse => HandlerOutcome::NoResponse,
+      HandlerOutcome::TimedOut => HandlerOutcome::TimedOut,
+    })
+  }
+}
+
+/// 正在由 leader 处理的请求，值为等待结果的 follower
+static PENDING: LazyLock<Mutex<HashMap<String, Vec<oneshot::Sender<HandlerOutcome>>>>> =
+  LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 请求在合并中的角色
+pub enum Coalesced {
+  /// 负责调用处理函数，完成后通过 finish 分发结果
+  Leader(LeaderGuard),
+  /// 等待 leader 的结果；接收失败说明 leader 被取消或结果无法复制，需要自行调用处理函数
+  Follower(oneshot::Receiver<HandlerOutcome>),
+}
+
+/// 合并键：方法 + 路径 + 查询字符串
+pub fn coalesce_key(method: &str, path: &str, query: &str) -> String {
+  format!("{} {}?{}", method, path, query)
+}
+
+/// 加入合并：没有相同键的请求在处理时成为 leader，否则成为 follower
+pub fn join(key: String) -> Coalesced {
+  let mut pending = PENDING.lock();
+  if let Some(waiters) = pending.get_mut(&key) {
+    let (tx, rx) = oneshot::channel();
+    waiters.push(tx);
+    return Coalesced::Follower(rx);
+  }
+  pending.insert(key.clone(), Vec::new());
+  Coalesced::Leader(LeaderGuard { key: Some(key) })
+}
+
+/// leader 的占位，释放时（例如客户端断开导致请求被取消）移除占位，follower 会收到接收错误
+pub struct LeaderGuard {
+  key: Option<String>,
+}
+
+impl LeaderGuard {
+  /// 把结果分发给等待中的 follower，之后到达的相同请求会重新调用处理函数
+  pub fn finish(mut self, outcome: &HandlerOutcome) {
+    let Some(key) = self.key.take() else {
+      return;
+    };
+    let waiters = PENDING.lock().remove(&key).unwrap_or_default();
+    for waiter in waiters {
+      // 结果无法复制时直接丢弃发送器，follower 自行调用处理函数
+      if let Some(outcome) = outcome.try_clone() {
+        let _ = waiter.send(outcome);
+      }
+    }
+  }
+}
+
+impl Drop for LeaderGuard {
+  fn drop(&mut self) {
+    if let Some(key) = self.key.take() {
+      PENDING.lock().remove(&key);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::response::InnerResp;
+
+  #[test]
+  fn test_followers_share_leader_outcome() {
+    let key = coalesce_key("GET", "/coalesce-test", "q=1");
+    let Coalesced::Leader(leader) = join(key.clone()) else {
+      panic!("第一个请求应成为 leader");
+    };
+    let Coalesced::Follower(mut follower) = join(key.clone()) else {
+      panic!("并发的相同请求应成为 follower");
+    };
+
+    leader.finish(&HandlerOutcome::Response(JsResponse {
+      inner: InnerResp::Text("shared".to_string()),
+      status_code: Some(201),
+      headers: None,
+    }));
+    match follower.try_recv() {
+      Ok(HandlerOutcome::Response(response)) => {
+        assert_eq!(response.status_code, Some(201));
+        assert_eq!(response.inner.body_bytes(), Some(b"shared".as_slice()));
+      }
+      _ => panic!("follower 应收到 leader 的响应"),
+    }
+
+    // leader 被取消时 follower 收到接收错误，之后的请求重新成为 leader
+    let Coalesced::Leader(leader) = join(key.clone()) else {
+      panic!("上一轮结束后应重新成为 leader");
+    };
+    let Coalesced::Follower(mut follower) = join(key.clone()) else {
+      panic!("并发的相同请求应成为 follower");
+    };
+    drop(leader);
+    assert!(follower.try_recv().is_err());
+    assert!(matches!(join(key), Coalesced::Leader(_)));
+  }
+}
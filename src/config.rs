@@ -0,0 +1,547 @@
+use actix_web::http::header::HOST;
+use actix_web::http::Method;
+use actix_web::{HttpRequest, HttpResponse};
+use ipnet::IpNet;
+use napi::Result;
+use std::io;
+use std::net::{IpAddr, TcpListener};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use crate::access_log::AccessLogger;
+use crate::forwarded::{forwarded_chain, parse_ip_networks, resolve_client_ip};
+use crate::response::JsonEnvelope;
+use crate::router::node_functions::Methods;
+use crate::timing::HandlerTiming;
+use crate::{BindRetryOptions, ServerOptions};
+
+/// 等待处理函数发送响应的默认超时（毫秒）
+pub const DEFAULT_REQUEST_TIMEOUT_MS: u32 = 10_000;
+
+/// 缓冲请求体的默认上限，与 actix PayloadConfig 的默认值保持一致（256KB）
+pub const DEFAULT_BODY_LIMIT: usize = 262_144;
+
+/// 绑定重试的总等待上限：重试在 start() 中同步进行，会阻塞 Node.js 主线程
+const MAX_BIND_RETRY_WAIT: Duration = Duration::from_secs(5);
+
+/// 达到并发上限时等待空闲许可的宽限时间，超过后返回 503
+const CONCURRENCY_GRACE_PERIOD: Duration = Duration::from_millis(100);
+
+/// 请求分发阶段使用的运行时配置，在服务器启动时由 ServerOptions 构建
+/// 通过 actix 的 app_data 共享给所有 worker
+pub struct DispatchConfig {
+  /// 慢请求阈值，None 表示不记录慢请求
+  pub slow_request_threshold: Option<Duration>,
+  /// 可信代理网段，为空时直接使用 TCP 对端地址
+  pub trusted_proxies: Vec<IpNet>,
+  /// IP 白名单，非空时只允许列表内的客户端访问
+  pub allowed_ips: Vec<IpNet>,
+  /// IP 黑名单，列表内的客户端直接返回 403
+  pub blocked_ips: Vec<IpNet>,
+  /// 响应体大小上限（字节），None 表示不限制
+  pub max_response_body_size: Option<usize>,
+  /// 缓冲请求体的大小上限（字节）
+  pub max_body_size: usize,
+  /// JSON 响应信封，None 表示不包装
+  pub json_envelope: Option<JsonEnvelope>,
+  /// 是否对只差末尾斜杠的请求返回 308 重定向
+  pub redirect_trailing_slash: bool,
+  /// 是否允许 POST 请求通过 X-HTTP-Method-Override / _method 覆盖请求方法
+  pub method_override: bool,
+  /// 没有显式注册 HEAD 路由时是否回退到 GET 处理函数
+  pub auto_head: bool,
+  /// 没有显式注册 OPTIONS 路由时是否自动应答 OPTIONS 请求
+  pub auto_options: bool,
+  /// Rust 侧访问日志，由 Server::start 在创建写入任务后设置
+  pub access_log: Option<AccessLogger>,
+  /// 回调队列已满时是否阻塞等待，而不是直接返回 503
+  pub blocking_callback_queue: bool,
+  /// CSRF 令牌签名密钥
+  pub csrf_secret: Option<Arc<[u8]>>,
+  /// Host 白名单（小写），为空时不检查
+  pub allowed_hosts: Vec<String>,
+  /// 是否为没有 Content-Type 的原始字节响应推断内容类型
+  pub sniff_content_type: bool,
+  /// 等待处理函数发送响应的超时时间，None 表示不限制
+  pub request_timeout: Option<Duration>,
+  /// 同时分发给 JavaScript 的请求数上限，None 表示不限制
+  pub concurrency_limit: Option<ConcurrencyLimit>,
+}
+
+/// 分发给 JavaScript 的并发请求上限，许可在处理函数发送响应或等待超时后归还
+pub struct ConcurrencyLimit {
+  semaphore: Semaphore,
+  grace_period: Duration,
+}
+
+impl ConcurrencyLimit {
+  fn new(max_concurrent: usize, grace_period: Duration) -> Self {
+    Self {
+      semaphore: Semaphore::new(max_concurrent),
+      grace_period,
+    }
+  }
+
+  /// 获取一个许可，宽限时间内仍没有空闲许可时返回 None
+  pub async fn acquire(&self) -> Option<SemaphorePermit<'_>> {
+    tokio::time::timeout(self.grace_period, self.semaphore.acquire())
+      .await
+      .ok()?
+      .ok()
+  }
+}
+
+fn parse_ip_list(entries: &Option<Vec<String>>, field: &str) -> Result<Vec<IpNet>> {
+  match entries {
+    Some(entries) => parse_ip_networks(entries)
+      .map_err(|e| napi::Error::from_reason(format!("{} 中存在无效地址 {}", field, e))),
+    None => Ok(Vec::new()),
+  }
+}
+
+/// 绑定 TCP 监听地址；配置了重试时，地址被占用会按指数退避重试
+/// 重试期间阻塞调用线程（即调用 start() 的 Node.js 主线程），所有重试的等待时间合计不超过 5 秒
+pub fn bind_with_retry(addr: &str, retry: Option<&BindRetryOptions>) -> io::Result<TcpListener> {
+  bind_with_retry_capped(addr, retry, MAX_BIND_RETRY_WAIT)
+}
+
+fn bind_with_retry_capped(
+  addr: &str,
+  retry: Option<&BindRetryOptions>,
+  max_wait: Duration,
+) -> io::Result<TcpListener> {
+  let (attempts, mut delay) = retry.map_or((0, Duration::ZERO), |retry| {
+    (retry.attempts, Duration::from_millis(retry.delay_ms.into()))
+  });
+
+  let mut attempt = 0;
+  let mut waited = Duration::ZERO;
+  loop {
+    match TcpListener::bind(addr) {
+      Err(e) if e.kind() == io::ErrorKind::AddrInUse && attempt < attempts && waited < max_wait => {
+        attempt += 1;
+        // 最后一次等待截断到剩余的总等待时间
+        delay = delay.min(max_wait - waited);
+        eprintln!(
+          "警告：{} 已被占用，{} 毫秒后第 {}/{} 次重试绑定",
+          addr,
+          delay.as_millis(),
+          attempt,
+          attempts
+        );
+        std::thread::sleep(delay);
+        waited += delay;
+        delay = delay.saturating_mul(2);
+      }
+      result => return result,
+    }
+  }
+}
+
+/// 服务器的监听套接字
+pub enum ServerListener {
+  Tcp(TcpListener),
+  /// HTTPS，与普通 HTTP 互斥
+  #[cfg(feature = "tls")]
+  Tls(TcpListener, rustls::ServerConfig),
+  #[cfg(unix)]
+  Unix(std::os::unix::net::UnixListener),
+}
+
+/// 加载 PEM 格式的证书链和私钥，私钥与证书不匹配时返回错误
+#[cfg(feature = "tls")]
+pub fn load_tls_config(cert_path: &str, key_path: &str) -> Result<rustls::ServerConfig> {
+  use rustls::pki_types::pem::PemObject;
+  use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+
+  let certs = CertificateDer::pem_file_iter(cert_path)
+    .and_then(|certs| certs.collect::<std::result::Result<Vec<_>, _>>())
+    .map_err(|e| napi::Error::from_reason(format!("无法读取 TLS 证书 {}：{}", cert_path, e)))?;
+  if certs.is_empty() {
+    return Err(napi::Error::from_reason(format!(
+      "TLS 证书文件 {} 中没有 PEM 格式的证书",
+      cert_path
+    )));
+  }
+  let key = PrivateKeyDer::from_pem_file(key_path)
+    .map_err(|e| napi::Error::from_reason(format!("无法读取 TLS 私钥 {}：{}", key_path, e)))?;
+
+  // 显式指定加密实现，不依赖进程级默认值
+  rustls::ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+    .with_safe_default_protocol_versions()
+    .map_err(|e| napi::Error::from_reason(format!("无法初始化 TLS：{}", e)))?
+    .with_no_client_auth()
+    .with_single_cert(certs, key)
+    .map_err(|e| napi::Error::from_reason(format!("TLS 证书或私钥无效（可能不匹配）：{}", e)))
+}
+
+/// 绑定 Unix 域套接字；路径上残留的套接字文件（例如进程异常退出时留下的）会先被删除
+/// 只有连接被拒绝（没有进程在监听）时才视为残留，仍有进程监听时返回地址已被占用
+#[cfg(unix)]
+pub fn bind_unix_socket(path: &str) -> Result<ServerListener> {
+  use std::os::unix::fs::FileTypeExt;
+  use std::path::Path;
+
+  let socket_path = Path::new(path);
+  let parent = match socket_path.parent() {
+    Some(parent) if !parent.as_os_str().is_empty() => parent,
+    _ => Path::new("."),
+  };
+  if !parent.is_dir() {
+    return Err(napi::Error::from_reason(format!(
+      "Unix 套接字所在目录不存在：{}",
+      parent.display()
+    )));
+  }
+
+  match std::fs::symlink_metadata(socket_path) {
+    Ok(metadata) if metadata.file_type().is_socket() => {
+      match std::os::unix::net::UnixStream::connect(socket_path) {
+        Ok(_) => {
+          return Err(napi::Error::from_reason(format!(
+            "无法绑定到 Unix 套接字 {}：{}（另一个进程正在监听）",
+            path,
+            io::Error::from(io::ErrorKind::AddrInUse)
+          )));
+        }
+        Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => {
+          std::fs::remove_file(socket_path).map_err(|e| {
+            napi::Error::from_reason(format!("无法删除残留的套接字文件 {}：{}", path, e))
+          })?;
+        }
+        // 其他错误（例如没有权限）无法确认套接字已经失效，不删除，由下面的绑定报告错误
+        Err(_) => {}
+      }
+    }
+    // 不是套接字的文件不删除，由下面的绑定报告地址已被占用
+    _ => {}
+  }
+
+  std::os::unix::net::UnixListener::bind(socket_path)
+    .map(ServerListener::Unix)
+    .map_err(|e| napi::Error::from_reason(format!("无法绑定到 Unix 套接字 {}：{}", path, e)))
+}
+
+#[cfg(not(unix))]
+pub fn bind_unix_socket(_path: &str) -> Result<ServerListener> {
+  Err(napi::Error::from_reason("当前平台不支持 Unix 域套接字"))
+}
+
+/// 按 tlsCertPath / tlsKeyPath 包装 TCP 监听套接字，两者都未设置时使用普通 HTTP
+pub fn tcp_listener(
+  listener: TcpListener,
+  tls_cert_path: Option<&str>,
+  tls_key_path: Option<&str>,
+) -> Result<ServerListener> {
+  match (tls_cert_path, tls_key_path) {
+    (None, None) => Ok(ServerListener::Tcp(listener)),
+    (Some(cert_path), Some(key_path)) => tls_listener(listener, cert_path, key_path),
+    _ => Err(napi::Error::from_reason(
+      "tlsCertPath 和 tlsKeyPath 必须同时设置",
+    )),
+  }
+}
+
+#[cfg(feature = "tls")]
+fn tls_listener(listener: TcpListener, cert_path: &str, key_path: &str) -> Result<ServerListener> {
+  Ok(ServerListener::Tls(
+    listener,
+    load_tls_config(cert_path, key_path)?,
+  ))
+}
+
+#[cfg(not(feature = "tls"))]
+fn tls_listener(
+  _listener: TcpListener,
+  _cert_path: &str,
+  _key_path: &str,
+) -> Result<ServerListener> {
+  Err(napi::Error::from_reason(
+    "当前构建未启用 tls 特性，不支持 HTTPS",
+  ))
+}
+
+/// 去掉 Host 中的端口，保留 IPv6 字面量的方括号
+fn strip_port(host: &str) -> &str {
+  if host.starts_with('[') {
+    return host.find(']').map_or(host, |end| &host[..=end]);
+  }
+  host.rsplit_once(':').map_or(host, |(name, _)| name)
+}
+
+/// 主机名与白名单条目比较（不区分大小写），"*.example.com" 匹配任意子域名但不匹配 example.com 本身
+fn host_matches(host: &str, pattern: &str) -> bool {
+  match pattern.strip_prefix("*.") {
+    Some(suffix) => {
+      host.len() > suffix.len() + 1
+        && host[host.len() - suffix.len()..].eq_ignore_ascii_case(suffix)
+        && host.as_bytes()[host.len() - suffix.len() - 1] == b'.'
+    }
+    None => host.eq_ignore_ascii_case(pattern),
+  }
+}
+
+impl DispatchConfig {
+  pub fn from_options(options: &ServerOptions) -> Result<Self> {
+    Ok(Self {
+      slow_request_threshold: options
+        .slow_request_threshold_ms
+        .map(|ms| Duration::from_millis(ms as u64)),
+      trusted_proxies: parse_ip_list(&options.trusted_proxies, "trustedProxies")?,
+      allowed_ips: parse_ip_list(&options.allowed_ips, "allowedIps")?,
+      blocked_ips: parse_ip_list(&options.blocked_ips, "blockedIps")?,
+      max_response_body_size: options.max_response_body_size.map(|size| size as usize),
+      max_body_size: options
+        .max_body_size
+        .map_or(DEFAULT_BODY_LIMIT, |size| size as usize),
+      json_envelope: options.json_envelope.as_ref().map(|envelope| JsonEnvelope {
+        data_key: envelope
+          .data_key
+          .clone()
+          .unwrap_or_else(|| "data".to_string()),
+        meta_key: envelope
+          .meta_key
+          .clone()
+          .unwrap_or_else(|| "meta".to_string()),
+      }),
+      redirect_trailing_slash: options.redirect_trailing_slash.unwrap_or(false),
+      method_override: options.method_override.unwrap_or(false),
+      auto_head: options.auto_head.unwrap_or(true),
+      auto_options: options.auto_options.unwrap_or(false),
+      access_log: None,
+      blocking_callback_queue: options.blocking_callback_queue.unwrap_or(false),
+      csrf_secret: options
+        .csrf_secret
+        .as_ref()
+        .map(|secret| Arc::from(secret.as_bytes())),
+      allowed_hosts: options
+        .allowed_hosts
+        .iter()
+        .flatten()
+        .map(|host| host.trim().to_ascii_lowercase())
+        .filter(|host| !host.is_empty())
+        .collect(),
+      sniff_content_type: options.sniff_content_type.unwrap_or(false),
+      concurrency_limit: options
+        .max_concurrent_requests
+        .filter(|&max| max > 0)
+        .map(|max| ConcurrencyLimit::new(max as usize, CONCURRENCY_GRACE_PERIOD)),
+      request_timeout: match options
+        .request_timeout_ms
+        .unwrap_or(DEFAULT_REQUEST_TIMEOUT_MS)
+      {
+        0 => None,
+        ms => Some(Duration::from_millis(ms.into())),
+      },
+    })
+  }
+
+  /// 解析客户端地址：只有直连对端是可信代理时才采信转发头
+  pub fn resolve_remote_addr(&self, req: &HttpRequest) -> Option<IpAddr> {
+    let peer = req.peer_addr()?.ip();
+    if self.trusted_proxies.is_empty() {
+      Some(peer)
+    } else {
+      Some(resolve_client_ip(
+        peer,
+        &forwarded_chain(req.headers()),
+        &self.trusted_proxies,
+      ))
+    }
+  }
+
+  /// 检查客户端是否允许访问；配置了白名单但无法获取客户端地址时拒绝
+  #[inline]
+  pub fn is_ip_allowed(&self, remote_addr: Option<IpAddr>) -> bool {
+    if self.allowed_ips.is_empty() && self.blocked_ips.is_empty() {
+      return true;
+    }
+
+    match remote_addr {
+      Some(ip) => {
+        let allowed =
+          self.allowed_ips.is_empty() || self.allowed_ips.iter().any(|net| net.contains(&ip));
+        allowed && !self.blocked_ips.iter().any(|net| net.contains(&ip))
+      }
+      None => self.allowed_ips.is_empty(),
+    }
+  }
+
+  /// 检查请求的 Host 是否在白名单中，白名单为空时总是允许
+  /// HTTP/2 请求没有 Host 头时使用 :authority（即 URI 中的主机名）
+  pub fn is_host_allowed(&self, req: &HttpRequest) -> bool {
+    if self.allowed_hosts.is_empty() {
+      return true;
+    }
+
+    let host = req
+      .headers()
+      .get(HOST)
+      .and_then(|value| value.to_str().ok())
+      .or_else(|| req.uri().host());
+    match host {
+      Some(host) => {
+        let host = strip_port(host);
+        self
+          .allowed_hosts
+          .iter()
+          .any(|pattern| host_matches(host, pattern))
+      }
+      None => false,
+    }
+  }
+
+  /// 开启 methodOverride 时，POST 请求可以通过 X-HTTP-Method-Override 头或 _method 查询参数模拟其他请求方法
+  /// 其他请求方法、未开启或覆盖值无效时返回 None，继续使用原始方法
+  pub fn override_method(&self, req: &HttpRequest) -> Option<Method> {
+    if !self.method_override || req.method() != Method::POST {
+      return None;
+    }
+    let header = req
+      .headers()
+      .get("x-http-method-override")
+      .and_then(|value| value.to_str().ok());
+    parse_method_override(header, req.query_string())
+  }
+
+  /// 🚀 慢请求日志：只有超过阈值时才会格式化输出，快速请求只付出一次比较的开销
+  #[inline(always)]
+  pub fn log_if_slow(
+    &self,
+    method: &Method,
+    route: &str,
+    elapsed: Duration,
+    response: &HttpResponse,
+  ) {
+    if let Some(threshold) = self.slow_request_threshold {
+      if elapsed > threshold {
+        let timing = HandlerTiming::from_response(response);
+        let record = serde_json::json!({
+          "level": "warn",
+          "event": "slow_request",
+          "method": method.as_str(),
+          "route": route,
+          "durationMs": elapsed.as_millis() as u64,
+          "thresholdMs": threshold.as_millis() as u64,
+          "status": response.status().as_u16(),
+          "queueMs": timing.map(|t| t.queue_ms()),
+          "handlerMs": timing.map(|t| t.handler_ms()),
+        });
+        eprintln!("{}", record);
+      }
+    }
+  }
+}
+
+/// 解析方法覆盖：请求头优先，其次是 _method 查询参数；只接受 Methods 支持的方法（不区分大小写）
+fn parse_method_override(header: Option<&str>, query_string: &str) -> Option<Method> {
+  let value = header.map(str::to_string).or_else(|| {
+    query_string
+      .split('&')
+      .find_map(|pair| pair.strip_prefix("_method="))
+      .map(str::to_string)
+  })?;
+  let method = Methods::convert_from_str(&value.trim().to_ascii_uppercase())?;
+  Method::from_bytes(method.as_str().as_bytes()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_host_matches() {
+    assert!(host_matches(strip_port("Example.com:8080"), "example.com"));
+    assert!(host_matches("api.example.com", "*.example.com"));
+    assert!(host_matches("a.b.example.com", "*.example.com"));
+    assert!(!host_matches("example.com", "*.example.com"));
+    assert!(!host_matches("evilexample.com", "*.example.com"));
+    assert!(!host_matches("example.com.evil.net", "example.com"));
+    assert_eq!(strip_port("[::1]:3000"), "[::1]");
+  }
+
+  #[tokio::test]
+  async fn test_concurrency_limit() {
+    let limit = ConcurrencyLimit::new(1, Duration::from_millis(10));
+    let first = limit.acquire().await;
+    assert!(first.is_some());
+    assert!(limit.acquire().await.is_none());
+
+    drop(first);
+    assert!(limit.acquire().await.is_some());
+  }
+
+  #[test]
+  fn test_parse_method_override() {
+    assert_eq!(
+      parse_method_override(Some("delete"), ""),
+      Some(Method::DELETE)
+    );
+    assert_eq!(
+      parse_method_override(Some("PUT"), "_method=PATCH"),
+      Some(Method::PUT)
+    );
+    assert_eq!(
+      parse_method_override(None, "a=1&_method=patch"),
+      Some(Method::PATCH)
+    );
+    assert_eq!(parse_method_override(Some("TRACE"), ""), None);
+    assert_eq!(parse_method_override(None, "a=1"), None);
+  }
+
+  #[test]
+  fn test_bind_with_retry_returns_last_error() {
+    let taken = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = taken.local_addr().unwrap().to_string();
+    let retry = BindRetryOptions {
+      attempts: 2,
+      delay_ms: 1,
+    };
+
+    let error = bind_with_retry(&addr, Some(&retry)).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::AddrInUse);
+
+    // 重试次数很多时，总等待时间仍然受上限约束
+    let many = BindRetryOptions {
+      attempts: 1000,
+      delay_ms: 1,
+    };
+    let started_at = std::time::Instant::now();
+    let error = bind_with_retry_capped(&addr, Some(&many), Duration::from_millis(20)).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::AddrInUse);
+    assert!(started_at.elapsed() < Duration::from_secs(1));
+
+    drop(taken);
+    assert!(bind_with_retry(&addr, Some(&retry)).is_ok());
+  }
+
+  #[test]
+  fn test_tcp_listener_requires_both_tls_paths() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    assert!(matches!(
+      tcp_listener(listener.try_clone().unwrap(), None, None),
+      Ok(ServerListener::Tcp(_))
+    ));
+    assert!(tcp_listener(listener, Some("cert.pem"), None).is_err());
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_bind_unix_socket() {
+    let dir = std::env::temp_dir();
+    let path = dir.join(format!("actix-js-{}.sock", uuid::Uuid::new_v4()));
+    let path = path.to_str().unwrap();
+
+    let first = bind_unix_socket(path).unwrap();
+    // 仍有监听者时不删除套接字文件
+    assert!(bind_unix_socket(path).is_err());
+    drop(first);
+    // 监听器释放后套接字文件仍然存在，再次绑定时会被清理
+    assert!(bind_unix_socket(path).is_ok());
+    std::fs::remove_file(path).unwrap();
+
+    let missing = dir.join("actix-js-missing-dir").join("server.sock");
+    assert!(bind_unix_socket(missing.to_str().unwrap()).is_err());
+  }
+}
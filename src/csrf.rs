@@ -0,0 +1,87 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+// CSRF 令牌模块
+// 令牌格式为 "{过期时间戳}.{HMAC-SHA256 十六进制}"，签名内容是会话 ID 和过期时间，
+// 校验时不需要服务端保存任何状态
+
+pub(crate) type HmacSha256 = Hmac<Sha256>;
+
+/// 令牌默认有效期（秒）
+pub const DEFAULT_CSRF_TTL_SECS: u64 = 3600;
+
+fn mac_for(secret: &[u8], session_id: &str, expires: u64) -> HmacSha256 {
+  let mut mac = hmac_sha256(secret);
+  mac.update(session_id.as_bytes());
+  mac.update(b".");
+  mac.update(expires.to_string().as_bytes());
+  mac
+}
+
+/// 为会话生成在 now + ttl_secs 过期的令牌
+pub fn generate_token(secret: &[u8], session_id: &str, now: u64, ttl_secs: u64) -> String {
+  let expires = now.saturating_add(ttl_secs);
+  let signature = mac_for(secret, session_id, expires).finalize().into_bytes();
+
+  let mut token = format!("{}.", expires);
+  for byte in signature {
+    token.push_str(&format!("{:02x}", byte));
+  }
+  token
+}
+
+/// 校验令牌的签名和有效期，签名比较是常量时间的
+pub fn verify_token(secret: &[u8], session_id: &str, token: &str, now: u64) -> bool {
+  let Some((expires, signature)) = token.split_once('.') else {
+    return false;
+  };
+  let Ok(expires) = expires.parse::<u64>() else {
+    return false;
+  };
+  let Some(signature) = decode_hex(signature) else {
+    return false;
+  };
+
+  expires > now
+    && mac_for(secret, session_id, expires)
+      .verify_slice(&signature)
+      .is_ok()
+}
+
+/// 以 secret 为密钥创建 HMAC-SHA256，CSRF 令牌和 webhook 签名校验共用
+pub(crate) fn hmac_sha256(secret: &[u8]) -> HmacSha256 {
+  // HMAC 接受任意长度的密钥，new_from_slice 不会失败
+  HmacSha256::new_from_slice(secret).expect("HMAC 密钥长度无效")
+}
+
+pub(crate) fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+  if !hex.len().is_multiple_of(2) {
+    return None;
+  }
+  (0..hex.len())
+    .step_by(2)
+    .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_csrf_token_roundtrip() {
+    let secret = b"csrf-secret";
+    let token = generate_token(secret, "session-1", 1_000, 60);
+
+    assert!(verify_token(secret, "session-1", &token, 1_000));
+    assert!(!verify_token(secret, "session-1", &token, 1_060));
+    assert!(!verify_token(secret, "session-2", &token, 1_000));
+    assert!(!verify_token(b"other-secret", "session-1", &token, 1_000));
+    assert!(!verify_token(secret, "session-1", "1060.zz", 1_000));
+
+    // 篡改过期时间后签名不再匹配
+    let (_, signature) = token.split_once('.').unwrap();
+    let forged = format!("9999999.{}", signature);
+    assert!(!verify_token(secret, "session-1", &forged, 1_000));
+  }
+}
@@ -0,0 +1,189 @@
+use actix_web::http::header::HeaderMap;
+use ipnet::IpNet;
+use std::net::{IpAddr, SocketAddr};
+
+// 代理链解析模块
+// 支持 RFC 7239 `Forwarded`、传统 `X-Forwarded-For` 以及 nginx 常用的 `X-Real-IP`，
+// 根据可信代理列表从右向左剥离代理跳数，得到真实的客户端 IP
+
+/// 解析 IP 网段配置，支持 CIDR（10.0.0.0/8）和单个 IP（127.0.0.1）
+pub fn parse_ip_networks(entries: &[String]) -> Result<Vec<IpNet>, String> {
+  entries
+    .iter()
+    .map(|entry| {
+      let entry = entry.trim();
+      entry
+        .parse::<IpNet>()
+        .or_else(|_| entry.parse::<IpAddr>().map(IpNet::from))
+        .map_err(|e| format!("{}: {}", entry, e))
+    })
+    .collect()
+}
+
+/// 解析单个节点标识，兼容 `1.2.3.4`、`1.2.3.4:80`、`[::1]:80`、`"[::1]"` 等写法
+/// 无法识别的标识（如 `unknown`、`_hidden`）返回 None
+fn parse_node(node: &str) -> Option<IpAddr> {
+  let node = node.trim().trim_matches('"');
+
+  if let Some(rest) = node.strip_prefix('[') {
+    let end = rest.find(']')?;
+    return rest[..end].parse().ok();
+  }
+
+  if let Ok(ip) = node.parse::<IpAddr>() {
+    return Some(ip);
+  }
+
+  node.parse::<SocketAddr>().ok().map(|addr| addr.ip())
+}
+
+/// 解析 `X-Forwarded-For: client, proxy1, proxy2`
+fn parse_x_forwarded_for(value: &str) -> impl Iterator<Item = Option<IpAddr>> + '_ {
+  value
+    .split(',')
+    .filter(|hop| !hop.trim().is_empty())
+    .map(parse_node)
+}
+
+/// 解析 `Forwarded: for=192.0.2.60;proto=http, for="[2001:db8::17]:4711"`
+fn parse_forwarded(value: &str) -> impl Iterator<Item = Option<IpAddr>> + '_ {
+  value
+    .split(',')
+    .filter(|element| !element.trim().is_empty())
+    .map(|element| {
+      element.split(';').find_map(|pair| {
+        let (key, node) = pair.split_once('=')?;
+        if key.trim().eq_ignore_ascii_case("for") {
+          Some(parse_node(node))
+        } else {
+          None
+        }
+      })?
+    })
+}
+
+/// 合并所有代理跳数，按报文顺序（最左侧为最初的客户端）返回
+/// 优先级为 `Forwarded` > `X-Forwarded-For` > `X-Real-IP`，多条同名头部按出现顺序拼接
+/// `X-Real-IP` 只记录一跳，仅在前两种头部都不存在时使用
+pub fn forwarded_chain(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+  let mut chain = Vec::new();
+
+  for value in headers.get_all("forwarded") {
+    if let Ok(value) = value.to_str() {
+      chain.extend(parse_forwarded(value));
+    }
+  }
+
+  if chain.is_empty() {
+    for value in headers.get_all("x-forwarded-for") {
+      if let Ok(value) = value.to_str() {
+        chain.extend(parse_x_forwarded_for(value));
+      }
+    }
+  }
+
+  if chain.is_empty() {
+    if let Some(value) = headers
+      .get("x-real-ip")
+      .and_then(|value| value.to_str().ok())
+    {
+      chain.push(parse_node(value));
+    }
+  }
+
+  chain
+}
+
+#[inline]
+fn is_trusted(ip: &IpAddr, trusted: &[IpNet]) -> bool {
+  trusted.iter().any(|net| net.contains(ip))
+}
+
+/// 从直连地址开始，自右向左跳过可信代理，返回第一个不可信的地址
+/// 代理链中出现无法解析的节点时停止，返回当前已知最靠近客户端的地址
+pub fn resolve_client_ip(peer: IpAddr, chain: &[Option<IpAddr>], trusted: &[IpNet]) -> IpAddr {
+  let mut client = peer;
+
+  for hop in chain.iter().rev() {
+    if !is_trusted(&client, trusted) {
+      break;
+    }
+    match hop {
+      Some(ip) => client = *ip,
+      None => break,
+    }
+  }
+
+  client
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn ip(s: &str) -> IpAddr {
+    s.parse().unwrap()
+  }
+
+  #[test]
+  fn test_parse_forwarded_formats() {
+    let xff: Vec<_> = parse_x_forwarded_for("203.0.113.7, 10.0.0.2:8080").collect();
+    assert_eq!(xff, vec![Some(ip("203.0.113.7")), Some(ip("10.0.0.2"))]);
+
+    let fwd: Vec<_> =
+      parse_forwarded(r#"for=192.0.2.60;proto=http, For="[2001:db8:cafe::17]:4711", for=unknown"#)
+        .collect();
+    assert_eq!(
+      fwd,
+      vec![Some(ip("192.0.2.60")), Some(ip("2001:db8:cafe::17")), None]
+    );
+  }
+
+  #[test]
+  fn test_resolve_skips_trusted_hops() {
+    let trusted = parse_ip_networks(&["10.0.0.0/8".to_string(), "127.0.0.1".to_string()]).unwrap();
+    let chain = vec![
+      Some(ip("198.51.100.1")),
+      Some(ip("203.0.113.7")),
+      Some(ip("10.0.0.2")),
+    ];
+
+    // 伪造的最左侧地址不会被采信
+    assert_eq!(
+      resolve_client_ip(ip("127.0.0.1"), &chain, &trusted),
+      ip("203.0.113.7")
+    );
+    // 直连地址不可信时忽略转发头
+    assert_eq!(
+      resolve_client_ip(ip("192.0.2.1"), &chain, &trusted),
+      ip("192.0.2.1")
+    );
+  }
+
+  #[test]
+  fn test_x_real_ip_fallback() {
+    use actix_web::http::header::{HeaderName, HeaderValue};
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+      HeaderName::from_static("x-real-ip"),
+      HeaderValue::from_static("203.0.113.9"),
+    );
+    assert_eq!(forwarded_chain(&headers), vec![Some(ip("203.0.113.9"))]);
+
+    // 同时存在 X-Forwarded-For 时以完整的代理链为准
+    headers.insert(
+      HeaderName::from_static("x-forwarded-for"),
+      HeaderValue::from_static("198.51.100.1, 10.0.0.2"),
+    );
+    assert_eq!(
+      forwarded_chain(&headers),
+      vec![Some(ip("198.51.100.1")), Some(ip("10.0.0.2"))]
+    );
+  }
+
+  #[test]
+  fn test_invalid_trusted_proxy() {
+    assert!(parse_ip_networks(&["not-an-ip".to_string()]).is_err());
+  }
+}
@@ -0,0 +1,138 @@
+use napi::bindgen_prelude::Either;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use parking_lot::{Mutex, RwLock};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+use crate::request::DetachedRequestWrapper;
+use crate::response::{InnerResp, JsResponse};
+use crate::router::node_functions::{
+  execute_hook_callback, CallBackFunction, HookCallBackFunction, HookReturn,
+};
+use crate::timing::DispatchTiming;
+
+// 全局请求钩子
+// before 钩子在每个路由处理函数之前执行，可以直接发送响应（例如 401）跳过处理函数；
+// 执行完毕（异步钩子的 Promise 完成）时没有发送响应即继续，req.next() 可以在钩子执行完毕之前提前继续；
+// after 钩子在响应生成之后执行，通过 req.getResponseStatus() 读取最终状态码，只用于日志等不影响响应的逻辑
+
+static BEFORE_HOOK: RwLock<Option<HookCallBackFunction>> = RwLock::new(None);
+static AFTER_HOOK: RwLock<Option<CallBackFunction>> = RwLock::new(None);
+
+/// before 钩子的结果
+pub enum BeforeHookOutcome {
+  /// 钩子调用了 next()，或执行完毕时没有发送响应，继续执行处理函数
+  Continue,
+  /// 钩子发送了响应（或抛出异常），跳过处理函数
+  Respond(JsResponse),
+  /// 钩子的回调没有被执行，也没有作出任何决定
+  Abandoned,
+  /// 钩子没能进入回调队列
+  Dropped,
+  /// 钩子在 requestTimeoutMs 内没有作出决定
+  TimedOut,
+}
+
+/// 是否注册了 after 钩子，用于决定是否需要为它保留一份请求数据
+pub fn has_after_hook() -> bool {
+  AFTER_HOOK.read().is_some()
+}
+
+/// 执行 before 钩子，没有注册时直接继续
+pub async fn run_before_hook(
+  wrapper: &DetachedRequestWrapper,
+  blocking: bool,
+  timeout: Option<Duration>,
+  timing: Arc<DispatchTiming>,
+) -> BeforeHookOutcome {
+  let Some(hook) = BEFORE_HOOK.read().clone() else {
+    return BeforeHookOutcome::Continue;
+  };
+
+  let (response_tx, mut response_rx) = oneshot::channel::<JsResponse>();
+  let (next_tx, mut next_rx) = oneshot::channel::<()>();
+  let mut hook_wrapper = wrapper.hook_copy();
+  hook_wrapper.set_response_sender(Arc::new(Mutex::new(Some(response_tx))));
+  hook_wrapper.set_next_sender(next_tx);
+
+  let Some(returned) = execute_hook_callback(&hook, hook_wrapper, blocking, timing) else {
+    return BeforeHookOutcome::Dropped;
+  };
+
+  let decision = async {
+    tokio::select! {
+      // 显式调用 next() 时立即继续，不必等钩子执行完毕
+      Ok(()) = &mut next_rx => BeforeHookOutcome::Continue,
+      Ok(response) = &mut response_rx => BeforeHookOutcome::Respond(response),
+      settled = hook_settled(returned) => match response_rx.try_recv() {
+        // 钩子在执行完毕之前发送的响应已经在通道中
+        Ok(response) => BeforeHookOutcome::Respond(response),
+        Err(_) => match settled {
+          Some(Ok(())) => BeforeHookOutcome::Continue,
+          Some(Err(e)) => {
+            // 异常信息只写入服务端日志，客户端收到通用的 500
+            eprintln!("❌ before 钩子抛出异常: {}", e.reason);
+            BeforeHookOutcome::Respond(JsResponse {
+              inner: InnerResp::ServerError,
+              status_code: None,
+              headers: None,
+            })
+          }
+          None => BeforeHookOutcome::Abandoned,
+        },
+      },
+    }
+  };
+  match timeout {
+    Some(limit) => tokio::time::timeout(limit, decision)
+      .await
+      .unwrap_or(BeforeHookOutcome::TimedOut),
+    None => decision.await,
+  }
+}
+
+/// 等待钩子执行完毕：同步钩子在返回时完成，异步钩子在 Promise 完成时完成
+/// 抛出异常或 Promise 被拒绝时返回错误，回调没有被执行时返回 None
+async fn hook_settled(
+  returned: oneshot::Receiver<napi::Result<HookReturn>>,
+) -> Option<napi::Result<()>> {
+  match returned.await.ok()? {
+    Ok(Either::A(promise)) => Some(promise.await.map(|_| ())),
+    Ok(Either::B(_)) => Some(Ok(())),
+    Err(e) => Some(Err(e)),
+  }
+}
+
+/// 以最终状态码调用 after 钩子，不等待钩子执行完成
+pub fn run_after_hook(mut wrapper: DetachedRequestWrapper, status: u16) {
+  let Some(hook) = AFTER_HOOK.read().clone() else {
+    return;
+  };
+  wrapper.set_response_status(status);
+  // 在放入 NonBlocking 队列之前冻结耗时，钩子排队等待事件循环的时间不计入请求耗时
+  wrapper.freeze_elapsed();
+  let _ = hook.call(Ok(wrapper), ThreadsafeFunctionCallMode::NonBlocking);
+}
+
+#[napi]
+/// 设置全局 before 钩子：在每个路由处理函数之前以同一个请求调用
+/// 钩子发送响应（例如 401）时跳过处理函数；执行完毕（异步钩子的 Promise 完成）时没有发送响应则继续执行处理函数，
+/// 也可以调用 req.next() 提前继续；钩子抛出异常时返回 500；传入 null 移除钩子
+/// 钩子同样受 requestTimeoutMs 限制；合并请求（coalesce）的每个请求都先经过自己的 before 钩子，放行之后才复用 leader 的结果，after 钩子同样对每个请求调用
+pub fn set_before_hook(callback: Option<ThreadsafeFunction<DetachedRequestWrapper, HookReturn>>) {
+  *BEFORE_HOOK.write() = callback.map(Arc::new);
+}
+
+#[napi]
+/// 设置全局 after 钩子：响应生成之后以同一个请求调用，通过 req.getResponseStatus() 读取最终状态码
+/// 钩子不能再修改响应，适合记录日志；传入 null 移除钩子
+pub fn set_after_hook(callback: Option<ThreadsafeFunction<DetachedRequestWrapper>>) {
+  *AFTER_HOOK.write() = callback.map(Arc::new);
+}
+
+/// 移除所有请求钩子，随路由一起清理
+pub fn clear_hooks() {
+  *BEFORE_HOOK.write() = None;
+  *AFTER_HOOK.write() = None;
+}
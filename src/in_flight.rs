@@ -0,0 +1,126 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+// 进行中的 JavaScript 回调跟踪
+// 回调在 Node 侧异步执行，actix 的连接全部结束并不代表处理函数已经全部完成，
+// 这里按请求计数：分发时加一，发送响应或请求被放弃（包装器被释放）时减一
+
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+static DRAINED: Notify = Notify::const_new();
+
+/// 一个进行中的回调，释放时计数减一
+pub struct InFlightCallback(());
+
+impl InFlightCallback {
+  /// 在把回调放入队列之前创建
+  pub fn begin() -> Self {
+    IN_FLIGHT.fetch_add(1, Ordering::AcqRel);
+    InFlightCallback(())
+  }
+}
+
+impl Drop for InFlightCallback {
+  fn drop(&mut self) {
+    if IN_FLIGHT.fetch_sub(1, Ordering::AcqRel) == 1 {
+      DRAINED.notify_waiters();
+    }
+  }
+}
+
+/// 当前进行中的回调数
+pub fn in_flight_count() -> usize {
+  IN_FLIGHT.load(Ordering::Acquire)
+}
+
+/// 等待进行中的回调数归零，超时返回 false
+pub async fn wait_until_idle(timeout: Duration) -> bool {
+  tokio::time::timeout(timeout, async {
+    loop {
+      // 先注册通知再检查计数，避免错过检查之后、等待之前的归零
+      let drained = DRAINED.notified();
+      tokio::pin!(drained);
+      drained.as_mut().enable();
+      if in_flight_count() == 0 {
+        return;
+      }
+      drained.await;
+    }
+  })
+  .await
+  .is_ok()
+}
+
+/// 单个 Server 实例的请求跟踪，stop() 据此判断关闭期间是否有请求在生成响应之前被中断
+/// 只统计本实例的连接：其他实例的请求、已经返回 504 但仍持有包装器的处理函数都不影响结果
+#[derive(Default)]
+pub struct RequestTracker {
+  interrupted: AtomicUsize,
+}
+
+impl RequestTracker {
+  /// 请求进入时创建，生成响应后调用 finish
+  pub fn begin(self: &Arc<Self>) -> TrackedRequest {
+    TrackedRequest {
+      tracker: self.clone(),
+      finished: false,
+    }
+  }
+
+  /// 累计被中断的请求数（响应生成之前请求被丢弃，例如连接被强制关闭）
+  pub fn interrupted_count(&self) -> usize {
+    self.interrupted.load(Ordering::Acquire)
+  }
+}
+
+/// 一个进行中的请求，未调用 finish 就被释放时记为中断
+pub struct TrackedRequest {
+  tracker: Arc<RequestTracker>,
+  finished: bool,
+}
+
+impl TrackedRequest {
+  pub fn finish(mut self) {
+    self.finished = true;
+  }
+}
+
+impl Drop for TrackedRequest {
+  fn drop(&mut self) {
+    if !self.finished {
+      self.tracker.interrupted.fetch_add(1, Ordering::AcqRel);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_in_flight_guard() {
+    let first = InFlightCallback::begin();
+    let second = InFlightCallback::begin();
+    assert!(in_flight_count() >= 2);
+
+    let before = in_flight_count();
+    drop(first);
+    assert_eq!(in_flight_count(), before - 1);
+    drop(second);
+  }
+
+  #[test]
+  fn test_request_tracker_counts_interrupted_requests() {
+    let tracker = Arc::new(RequestTracker::default());
+    tracker.begin().finish();
+    assert_eq!(tracker.interrupted_count(), 0);
+
+    drop(tracker.begin());
+    assert_eq!(tracker.interrupted_count(), 1);
+
+    // 其他实例的请求不计入
+    drop(Arc::new(RequestTracker::default()).begin());
+    assert_eq!(tracker.interrupted_count(), 1);
+  }
+}
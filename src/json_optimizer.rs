@@ -8,9 +8,21 @@ use serde::Serialize;
 #[derive(Debug)]
 pub enum JsonError {
   ParseError(simd_json::Error),
+  /// simd-json 和 serde_json 都拒绝时保留 serde_json 的错误，其中包含出错的行号和列号
+  SerdeError(serde_json::Error),
   InvalidUtf8,
 }
 
+impl std::fmt::Display for JsonError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      JsonError::ParseError(err) => write!(f, "{}", err),
+      JsonError::SerdeError(err) => write!(f, "{}", err),
+      JsonError::InvalidUtf8 => write!(f, "不是有效的 UTF-8"),
+    }
+  }
+}
+
 impl From<simd_json::Error> for JsonError {
   fn from(err: simd_json::Error) -> Self {
     JsonError::ParseError(err)
@@ -43,6 +55,12 @@ pub fn serialize_json_compact<T: Serialize>(value: &T) -> Result<String, JsonErr
   simd_json::serde::to_string(value).map_err(|e| JsonError::ParseError(e))
 }
 
+/// 响应体 JSON 序列化：输入已经是 serde_json::Value 时直接使用 serde_json
+/// simd-json 的 serde 序列化器在这种输入上反而更慢（见 bench_serialize_json_value），因此不走 SIMD 路径
+pub fn serialize_json_value(value: &serde_json::Value) -> Result<String, String> {
+  serde_json::to_string(value).map_err(|e| e.to_string())
+}
+
 /// 🚀 SIMD 优化的 JSON 序列化（美化格式）
 pub fn serialize_json_pretty<T: Serialize>(value: &T) -> Result<String, JsonError> {
   // 使用 simd-json 的 serde 模块进行美化序列化
@@ -112,6 +130,20 @@ pub fn simd_to_serde_value(simd_value: simd_json::OwnedValue) -> serde_json::Val
   }
 }
 
+/// 🚀 请求体 JSON 解析：SIMD 解析后转换为 serde_json::Value，解析失败返回 None
+/// simd-json 拒绝但 serde_json 接受的输入（例如超出 64 位范围的整数）回退到标准解析，结果与之前一致
+pub fn parse_json_body(bytes: &Bytes) -> Option<serde_json::Value> {
+  try_parse_json_body(bytes).ok()
+}
+
+/// 与 parse_json_body 相同，解析失败时返回错误详情，供处理函数返回有意义的 400 响应
+pub fn try_parse_json_body(bytes: &Bytes) -> Result<serde_json::Value, JsonError> {
+  match parse_json_from_bytes(bytes) {
+    Ok(simd_value) => Ok(simd_to_serde_value(simd_value)),
+    Err(_) => serde_json::from_slice(bytes).map_err(JsonError::SerdeError),
+  }
+}
+
 /// 🚀 批量转换：将 HashMap<String, simd_json::OwnedValue> 转换为 serde_json::Value
 pub fn simd_map_to_serde_value(
   simd_map: std::collections::HashMap<String, simd_json::OwnedValue>,
@@ -123,6 +155,158 @@ pub fn simd_map_to_serde_value(
   serde_json::Value::Object(serde_map)
 }
 
+/// JavaScript 能精确表示的最大整数 2^53 - 1
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// 保留大整数精度的 JSON 解析：超出 ±(2^53 - 1) 的整数转换为十进制字符串，
+/// 避免转换为 JavaScript number 时丢失精度（例如 64 位 ID）
+///
+/// 与默认的 SIMD 解析相比：使用 serde_json 标准解析器，速度较慢；
+/// 超出 64 位范围的整数和有效数字超过 17 位的浮点数仍按 f64 解析，无法保留原始文本
+pub fn parse_json_lossless(data: &[u8]) -> serde_json::Result<serde_json::Value> {
+  let mut value = serde_json::from_slice(data)?;
+  stringify_unsafe_integers(&mut value);
+  Ok(value)
+}
+
+fn stringify_unsafe_integers(value: &mut serde_json::Value) {
+  match value {
+    serde_json::Value::Number(number) => {
+      let unsafe_integer = match (number.as_u64(), number.as_i64()) {
+        (Some(n), _) => n > MAX_SAFE_INTEGER,
+        (None, Some(n)) => n.unsigned_abs() > MAX_SAFE_INTEGER,
+        (None, None) => false,
+      };
+      if unsafe_integer {
+        *value = serde_json::Value::String(number.to_string());
+      }
+    }
+    serde_json::Value::Array(items) => items.iter_mut().for_each(stringify_unsafe_integers),
+    serde_json::Value::Object(map) => map.values_mut().for_each(stringify_unsafe_integers),
+    _ => {}
+  }
+}
+
+/// 流式 JSON 数组解析错误，index 为出错元素的下标
+#[derive(Debug)]
+pub struct JsonArrayError {
+  pub index: usize,
+  pub message: String,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ArrayState {
+  Start,
+  Element,
+  Separator,
+  Done,
+}
+
+/// 🚀 流式 JSON 数组读取器
+/// 在已缓冲的请求体上逐个解析顶层数组元素，出错时报告元素下标
+pub struct JsonArrayElements<'a> {
+  data: &'a [u8],
+  pos: usize,
+  index: usize,
+  state: ArrayState,
+}
+
+impl<'a> JsonArrayElements<'a> {
+  pub fn new(data: &'a [u8]) -> Self {
+    Self {
+      data,
+      pos: 0,
+      index: 0,
+      state: ArrayState::Start,
+    }
+  }
+
+  fn skip_whitespace(&mut self) {
+    while self
+      .data
+      .get(self.pos)
+      .is_some_and(|byte| byte.is_ascii_whitespace())
+    {
+      self.pos += 1;
+    }
+  }
+
+  fn fail(
+    &mut self,
+    message: impl Into<String>,
+  ) -> Option<Result<serde_json::Value, JsonArrayError>> {
+    self.state = ArrayState::Done;
+    Some(Err(JsonArrayError {
+      index: self.index,
+      message: message.into(),
+    }))
+  }
+
+  /// 读到结束的 `]` 后，只允许剩余空白字符
+  fn finish(&mut self) -> Option<Result<serde_json::Value, JsonArrayError>> {
+    self.state = ArrayState::Done;
+    self.skip_whitespace();
+    if self.pos < self.data.len() {
+      return self.fail("数组结束后存在多余内容");
+    }
+    None
+  }
+}
+
+impl Iterator for JsonArrayElements<'_> {
+  type Item = Result<serde_json::Value, JsonArrayError>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      match self.state {
+        ArrayState::Done => return None,
+        ArrayState::Start => {
+          self.skip_whitespace();
+          if self.data.get(self.pos) != Some(&b'[') {
+            return self.fail("请求体不是 JSON 数组");
+          }
+          self.pos += 1;
+          self.skip_whitespace();
+          if self.data.get(self.pos) == Some(&b']') {
+            self.pos += 1;
+            return self.finish();
+          }
+          self.state = ArrayState::Element;
+        }
+        ArrayState::Element => {
+          let mut stream = serde_json::Deserializer::from_slice(&self.data[self.pos..])
+            .into_iter::<serde_json::Value>();
+          return match stream.next() {
+            Some(Ok(value)) => {
+              self.pos += stream.byte_offset();
+              self.index += 1;
+              self.state = ArrayState::Separator;
+              Some(Ok(value))
+            }
+            Some(Err(e)) => self.fail(e.to_string()),
+            None => self.fail("数组未结束"),
+          };
+        }
+        ArrayState::Separator => {
+          self.skip_whitespace();
+          match self.data.get(self.pos) {
+            Some(b',') => {
+              self.pos += 1;
+              self.state = ArrayState::Element;
+            }
+            Some(b']') => {
+              self.pos += 1;
+              return self.finish();
+            }
+            Some(_) => return self.fail("元素之间缺少逗号"),
+            None => return self.fail("数组未结束"),
+          }
+        }
+      }
+    }
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -134,6 +318,120 @@ mod tests {
     assert!(result.is_ok());
   }
 
+  #[test]
+  fn test_parse_json_body() {
+    let body = Bytes::from_static(
+      br#"{"name": "test", "array": [1, 2.5, null], "big": 100000000000000000000}"#,
+    );
+    let value = parse_json_body(&body).unwrap();
+    assert_eq!(
+      value,
+      serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+    );
+    assert!(parse_json_body(&Bytes::from_static(b"{\"a\": }")).is_none());
+    assert!(parse_json_body(&Bytes::new()).is_none());
+  }
+
+  #[test]
+  fn test_serialize_json_value() {
+    let value = serde_json::json!({"id": 1, "name": "测试", "tags": ["a", "b"], "ok": true});
+    let json = serialize_json_value(&value).unwrap();
+    assert_eq!(
+      serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+      value
+    );
+  }
+
+  /// 对比 SIMD 与 serde_json 两条响应体序列化路径的耗时
+  /// 输入已经是 serde_json::Value 时，simd-json 的 serde 序列化器并不占优（实测约为 serde_json 的两倍耗时）
+  /// 运行：cargo test --release bench_serialize_json_value -- --ignored --nocapture
+  #[test]
+  #[ignore]
+  fn bench_serialize_json_value() {
+    use std::time::Instant;
+
+    let items: Vec<serde_json::Value> = (0..200)
+      .map(|i| {
+        serde_json::json!({
+          "id": i,
+          "name": format!("user-{}", i),
+          "tags": ["a", "b"],
+          "score": i as f64 + 0.5,
+        })
+      })
+      .collect();
+    let value = serde_json::json!({ "items": items, "total": 200 });
+    let iterations = 2_000;
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+      std::hint::black_box(serialize_json_compact(std::hint::black_box(&value)).ok());
+    }
+    let simd = start.elapsed();
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+      std::hint::black_box(serde_json::to_string(std::hint::black_box(&value)).ok());
+    }
+    let serde = start.elapsed();
+
+    println!(
+      "响应体 {} 个元素 × {} 次：simd-json {:?}，serde_json {:?}",
+      200, iterations, simd, serde
+    );
+  }
+
+  #[test]
+  fn test_try_parse_json_body_reports_position() {
+    let error = try_parse_json_body(&Bytes::from("{\n  \"a\": 1,\n  \"b\": }")).unwrap_err();
+    assert!(error.to_string().contains("line 3"), "{}", error);
+    assert!(try_parse_json_body(&Bytes::from(r#"{"a": 1}"#)).is_ok());
+  }
+
+  /// 对比 SIMD 与 serde_json 两条请求体解析路径的耗时
+  /// 运行：cargo test --release bench_parse_json_body -- --ignored --nocapture
+  #[test]
+  #[ignore]
+  fn bench_parse_json_body() {
+    use std::time::Instant;
+
+    let items: Vec<String> = (0..200)
+      .map(|i| {
+        format!(
+          r#"{{"id": {}, "name": "user-{}", "tags": ["a", "b"], "score": {}.5}}"#,
+          i, i, i
+        )
+      })
+      .collect();
+    let body = Bytes::from(format!(
+      r#"{{"items": [{}], "total": 200}}"#,
+      items.join(",")
+    ));
+    let iterations = 2_000;
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+      std::hint::black_box(parse_json_body(std::hint::black_box(&body)));
+    }
+    let simd = start.elapsed();
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+      std::hint::black_box(
+        serde_json::from_slice::<serde_json::Value>(std::hint::black_box(&body)).ok(),
+      );
+    }
+    let serde = start.elapsed();
+
+    println!(
+      "请求体 {} 字节 × {} 次：simd-json {:?}，serde_json {:?}",
+      body.len(),
+      iterations,
+      simd,
+      serde
+    );
+  }
+
   #[test]
   fn test_json_validation() {
     assert!(is_valid_json(br#"{"valid": true}"#));
@@ -154,4 +452,37 @@ mod tests {
     let result = serialize_owned_value(&value);
     assert!(result.is_ok());
   }
+
+  #[test]
+  fn test_parse_json_lossless() {
+    let value =
+      parse_json_lossless(br#"{"id": 9007199254740993, "neg": -9007199254740993, "ids": [1, 18446744073709551615], "ratio": 0.5}"#)
+        .unwrap();
+    assert_eq!(value["id"], "9007199254740993");
+    assert_eq!(value["neg"], "-9007199254740993");
+    assert_eq!(value["ids"][0], 1);
+    assert_eq!(value["ids"][1], "18446744073709551615");
+    assert_eq!(value["ratio"], 0.5);
+  }
+
+  #[test]
+  fn test_json_array_elements() {
+    let values: Vec<_> = JsonArrayElements::new(br#" [1, {"a": [2, 3]}, "x"] "#)
+      .collect::<Result<_, _>>()
+      .unwrap();
+    assert_eq!(values.len(), 3);
+    assert_eq!(values[1]["a"][1], 3);
+
+    assert_eq!(JsonArrayElements::new(b"[]").count(), 0);
+
+    // 第 2 个元素（下标 1）损坏时报告其下标
+    let results: Vec<_> = JsonArrayElements::new(b"[1, {bad}, 3]").collect();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[1].as_ref().unwrap_err().index, 1);
+
+    assert!(JsonArrayElements::new(br#"{"a": 1}"#)
+      .next()
+      .unwrap()
+      .is_err());
+  }
 }
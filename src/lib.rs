@@ -3,11 +3,23 @@
 #[macro_use]
 extern crate napi_derive;
 
-use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+use actix_web::dev::{Decompress, Service};
+use actix_web::http::header::{
+  ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_REQUEST_HEADERS,
+  ACCESS_CONTROL_REQUEST_METHOD, IF_RANGE, RANGE,
+};
+use actix_web::http::{KeepAlive, Method};
+use actix_web::{middleware, web, App, HttpRequest, HttpResponse, HttpServer};
+use bytes::{Bytes, BytesMut};
+use futures::StreamExt;
 use napi::Result;
 use parking_lot::Mutex;
-use std::net::TcpListener;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::SemaphorePermit;
 
 // 导入router模块
 mod router;
@@ -25,20 +37,211 @@ pub use response::*;
 mod json_optimizer;
 pub use json_optimizer::*;
 
+// 导入config模块
+mod config;
+pub use config::*;
+
+// 导入forwarded模块
+mod forwarded;
+
+// 导入响应缓存模块
+mod response_cache;
+pub use response_cache::*;
+
+// 导入流式 multipart 模块
+mod multipart_stream;
+pub use multipart_stream::*;
+
+// 导入请求录制模块
+mod recorder;
+pub use recorder::*;
+
+// 导入访问日志模块
+mod access_log;
+
+// 导入反向代理模块
+mod proxy;
+
+// 导入 CSRF 令牌模块
+mod csrf;
+
+// 导入请求耗时拆分模块
+mod timing;
+
+// 导入 SSE 广播模块
+mod sse;
+pub use sse::*;
+
+// 导入进行中回调跟踪模块
+mod in_flight;
+
+// 导入响应内容类型嗅探模块
+mod sniff;
+
+// 导入响应后处理模块
+mod post_process;
+
+// 导入 Webhook 签名校验模块
+mod webhook;
+
+// 导入流式响应逐块转换模块
+mod stream_transform;
+
+// 导入请求合并模块
+mod coalesce;
+
+// 导入上传文件目录管理模块
+mod uploads;
+pub use uploads::*;
+
+// 导入全局请求钩子模块
+mod hooks;
+pub use hooks::*;
+
+// 导入共享应用状态模块
+mod app_state;
+pub use app_state::*;
+
 // 服务器句柄类型
-type ServerHandle = Option<actix_web::dev::ServerHandle>;
+/// 服务器运行状态
+enum ServerState {
+  Stopped,
+  /// start() 正在绑定和启动，句柄尚未保存
+  Starting,
+  Running(actix_web::dev::ServerHandle),
+}
+
+/// 等待进行中的处理函数的默认超时，与 requestTimeoutMs 的默认值一致
+const DEFAULT_HANDLER_DRAIN_TIMEOUT_MS: u32 = 10_000;
+
+#[napi(object)]
+/// 停止服务器选项
+pub struct StopOptions {
+  /// 连接关闭后继续等待已分发但尚未发送响应的处理函数，默认 false
+  pub wait_for_handlers: Option<bool>,
+  /// 等待处理函数的超时时间（毫秒），默认 10000
+  pub handler_timeout_ms: Option<u32>,
+}
+
+#[napi(object)]
+/// JSON 响应信封选项
+pub struct JsonEnvelopeOptions {
+  /// 存放处理函数响应体的字段名，默认 data
+  pub data_key: Option<String>,
+  /// 存放服务端元信息（requestId、timestamp）的字段名，默认 meta
+  pub meta_key: Option<String>,
+}
+
+#[napi(object)]
+/// 服务器启动结果
+pub struct ServerStartInfo {
+  /// 监听的主机，Unix 域套接字模式下为套接字路径
+  pub host: String,
+  /// 实际监听的端口，port 为 0 时是操作系统分配的端口；Unix 域套接字模式下为 0
+  pub port: u16,
+  /// 服务器地址，例如 http://127.0.0.1:3000（HTTPS 模式下为 https://），Unix 域套接字模式下为 unix:/path/to/server.sock
+  pub url: String,
+}
+
+#[napi(object)]
+/// 绑定端口被占用时的重试选项
+pub struct BindRetryOptions {
+  /// 最多重试次数（不含第一次绑定）
+  pub attempts: u32,
+  /// 第一次重试前的等待时间（毫秒），之后每次翻倍
+  pub delay_ms: u32,
+}
 
 #[napi(object)]
 pub struct ServerOptions {
   pub host: String,
   pub port: u16,
+  /// 慢请求阈值（毫秒），处理耗时超过该值的请求会输出一条结构化警告日志
+  pub slow_request_threshold_ms: Option<u32>,
+  /// 可信代理列表（CIDR 或单个 IP），设置后会根据 Forwarded / X-Forwarded-For / X-Real-IP 解析真实客户端 IP
+  /// 只有直连对端在列表中时才采信这些头部，未设置时始终使用直连地址，避免客户端伪造
+  pub trusted_proxies: Option<Vec<String>>,
+  /// IP 白名单（CIDR 或单个 IP），非空时只允许列表内的客户端访问
+  pub allowed_ips: Option<Vec<String>>,
+  /// IP 黑名单（CIDR 或单个 IP），命中时在执行任何 JavaScript 之前返回 403
+  pub blocked_ips: Option<Vec<String>>,
+  /// 响应体大小上限（字节），send 系列方法发送超出上限的响应体时会返回错误，提示改用分页或流式响应
+  pub max_response_body_size: Option<u32>,
+  /// 请求体大小上限（字节），默认 262144（256KB），超出时在调用处理函数之前返回 413
+  /// 流式 multipart 路由不缓冲请求体，不受此限制
+  pub max_body_size: Option<u32>,
+  /// 空闲连接超时（毫秒），同时作用于两个阶段：
+  /// - keep-alive 超时：连接上一个请求完成后，等待下一个请求的最长时间
+  /// - 首个请求超时：新建立的连接在该时间内没有发来完整请求头时直接关闭
+  ///
+  /// 未设置时沿用 actix 默认值（keep-alive 5 秒，首个请求 5 秒）
+  pub idle_connection_timeout_ms: Option<u32>,
+  /// keep-alive 超时（秒）：连接上一个请求完成后等待下一个请求的最长时间，0 表示关闭 keep-alive
+  /// 优先于 idleConnectionTimeoutMs；两者都未设置时沿用 actix 默认值 5 秒
+  pub keep_alive_secs: Option<u32>,
+  /// 读取请求头的超时（毫秒）：连接建立后在该时间内没有收到完整请求头时返回 408 并关闭，0 表示不限制
+  /// 高延迟的移动网络可以适当调大；优先于 idleConnectionTimeoutMs，两者都未设置时沿用 actix 默认值 5000 毫秒
+  pub client_timeout_ms: Option<u32>,
+  /// 启用后，成功的 JSON 响应会自动包装为 {"data": ..., "meta": {"requestId", "timestamp"}}，错误响应不受影响
+  pub json_envelope: Option<JsonEnvelopeOptions>,
+  /// 请求路径只差末尾斜杠（多一个或少一个）时，返回 308 重定向到已注册的路径，默认关闭
+  pub redirect_trailing_slash: Option<bool>,
+  /// 没有显式注册 HEAD 路由时，由同一路径的 GET 处理函数应答 HEAD 请求（去掉响应体，保留 Content-Length），默认开启
+  pub auto_head: Option<bool>,
+  /// 没有显式注册 OPTIONS 路由时，对路径在其他请求方法下存在的 OPTIONS 请求（如 CORS 预检）直接返回 204 和 Allow 头，默认关闭
+  /// 同时回显 Access-Control-Request-Method（仅当该方法已注册）和 Access-Control-Request-Headers 到对应的 Access-Control-Allow-* 头；不设置 Access-Control-Allow-Origin，允许哪些来源由应用决定，需要放行跨域预检时请自行注册 OPTIONS 路由
+  pub auto_options: Option<bool>,
+  /// 允许 POST 请求通过 X-HTTP-Method-Override 请求头或 _method 查询参数模拟 PUT、DELETE 等方法，默认关闭
+  /// 只接受支持的请求方法，无效的覆盖值按原始的 POST 处理；处理函数中 getMethod() 返回覆盖后的方法
+  pub method_override: Option<bool>,
+  /// Rust 侧 JSON Lines 访问日志输出目标："stdout" 或文件路径，未设置时不记录
+  /// 每行格式为 {ts, method, path, status, durMs, queueMs, handlerMs, ip, bytes, contentEncoding}，通过缓冲的异步写入器输出，不阻塞请求处理
+  pub access_log: Option<String>,
+  /// 启用 actix Logger 中间件并按该格式输出到标准错误，例如 "%a \"%r\" %s %b %T"（格式占位符见 actix-web 文档）
+  /// 为 "default" 时使用 actix 的默认格式；未设置时不启用，与 accessLog 互不影响
+  pub log_format: Option<String>,
+  /// 回调队列已满时阻塞等待入队（会占用 actix worker 线程），默认关闭：入队失败的请求立即返回 503
+  pub blocking_callback_queue: Option<bool>,
+  /// CSRF 令牌签名密钥，设置后处理函数可以使用 generateCsrfToken / verifyCsrfToken
+  pub csrf_secret: Option<String>,
+  /// Host 白名单，例如 ["example.com", "*.example.com"]（通配符只匹配子域名，不区分大小写，忽略端口）
+  /// 非空时 Host 不在列表中的请求在路由匹配之前返回 421 Misdirected Request，为空或未设置时不检查
+  pub allowed_hosts: Option<Vec<String>>,
+  /// 原始字节响应没有设置 Content-Type 时，根据魔数推断 PNG / JPEG / GIF / WebP / PDF / HTML / JSON / 纯文本，默认关闭
+  /// 无法识别时仍使用 application/octet-stream；推断成功时附带 X-Content-Type-Options: nosniff
+  pub sniff_content_type: Option<bool>,
+  /// 端口被占用（EADDRINUSE）时按指数退避重试绑定，适用于开发时快速重启、旧进程的连接尚未释放的情况
+  /// 重试在 start() 中同步进行，期间会阻塞 Node.js 主线程，所有重试的等待时间合计不超过 5 秒；其他绑定错误不重试，所有重试失败后返回最后一次的错误
+  pub bind_retry: Option<BindRetryOptions>,
+  /// 等待处理函数发送响应的超时时间（毫秒），默认 10000，超时返回 504；为 0 时不限制
+  pub request_timeout_ms: Option<u32>,
+  /// 同时分发给处理函数的请求数上限，避免突发流量在 Node.js 侧堆积无限的待处理请求；未设置或为 0 时不限制
+  /// 名额在读取完请求体之后才占用；达到上限时新请求最多等待 100 毫秒，仍没有空闲名额则返回 503 和 Retry-After；处理函数发送响应或超时后释放名额
+  pub max_concurrent_requests: Option<u32>,
+  /// actix 工作线程数，不设置时使用 actix 的默认值（CPU 核心数）
+  /// 请求处理都在 Node.js 事件循环上执行，工作线程只负责网络 IO 和分发，通常 1-2 个就足够
+  pub workers: Option<u32>,
+  /// stop() 时等待进行中请求的最长时间（毫秒，按秒向上取整），超过后强制关闭剩余连接，默认沿用 actix 的 30 秒
+  /// stop() 的返回值会说明是优雅关闭还是强制关闭
+  pub graceful_timeout_ms: Option<u32>,
+  /// 监听 Unix 域套接字（例如 nginx 通过 UDS 转发），设置后忽略 host 和 port；stop() 时删除套接字文件
+  /// 所在目录必须已经存在；仅支持 Unix 类系统
+  pub unix_socket: Option<String>,
+  /// PEM 格式的 TLS 证书链路径，与 tlsKeyPath 同时设置时以 HTTPS 提供服务（同一个实例不再提供 HTTP）
+  /// 证书或私钥无法解析、或两者不匹配时 start() 返回错误；不能与 unixSocket 同时使用
+  /// 需要启用 tls 特性构建（npm run build:tls），未启用时 start() 返回错误
+  pub tls_cert_path: Option<String>,
+  /// PEM 格式的 TLS 私钥路径（PKCS#8、PKCS#1 或 SEC1）
+  pub tls_key_path: Option<String>,
 }
 
 #[napi]
 pub struct Server {
   options: ServerOptions,
   // 使用Arc<Mutex>来存储服务器句柄，这样可以在多线程间安全共享
-  handle: Arc<Mutex<ServerHandle>>,
+  handle: Arc<Mutex<ServerState>>,
+  // 本实例的请求跟踪，用于判断 stop() 是否强制中断了请求
+  requests: Arc<in_flight::RequestTracker>,
 }
 
 #[napi]
@@ -47,130 +250,736 @@ impl Server {
   pub fn new(options: ServerOptions) -> Self {
     Server {
       options,
-      handle: Arc::new(Mutex::new(None)),
+      handle: Arc::new(Mutex::new(ServerState::Stopped)),
+      requests: Arc::new(in_flight::RequestTracker::default()),
     }
   }
 
   #[napi]
-  pub fn start(&self) -> Result<String> {
+  /// 启动服务器，返回实际监听的地址；port 为 0 时由操作系统分配空闲端口
+  /// 同一个实例已经在运行（或正在启动）时返回错误，需要先调用 stop()
+  pub fn start(&self) -> Result<ServerStartInfo> {
+    {
+      let mut state = self.handle.lock();
+      if !matches!(*state, ServerState::Stopped) {
+        return Err(napi::Error::from_reason("服务器已在运行"));
+      }
+      // 先占位，避免两次 start() 在句柄保存之前交错执行
+      *state = ServerState::Starting;
+    }
+
+    let result = self.launch();
+    if result.is_err() {
+      *self.handle.lock() = ServerState::Stopped;
+    }
+    result
+  }
+
+  #[napi]
+  /// 停止服务器；传入 { waitForHandlers: true } 时会继续等待所有已分发的处理函数发送响应或被放弃
+  /// 返回值说明是优雅关闭，还是超过 gracefulTimeoutMs 后强制关闭了剩余连接：本实例有请求在生成响应之前被中断即视为强制关闭
+  pub async fn stop(&self, options: Option<StopOptions>) -> Result<String> {
+    // 先取出handle，避免在持有锁时await
+    let handle = {
+      let mut state = self.handle.lock();
+      match std::mem::replace(&mut *state, ServerState::Stopped) {
+        ServerState::Running(handle) => Some(handle),
+        // 正在启动时保持原状态
+        other => {
+          *state = other;
+          None
+        }
+      }
+    };
+
+    if let Some(handle) = handle {
+      let interrupted_before = self.requests.interrupted_count();
+      // 直接await服务器停止，确保完全停止后才返回
+      // 超过 gracefulTimeoutMs 时由 actix 强制关闭剩余连接（启动时通过 shutdown_timeout 配置）
+      handle.stop(true).await;
+      // 优雅关闭时每个请求都已生成响应；关闭期间被中断的请求说明它们的连接是被强制关闭的
+      let interrupted = self.requests.interrupted_count() - interrupted_before;
+      let graceful = interrupted == 0;
+
+      if let Some(options) = options.filter(|options| options.wait_for_handlers == Some(true)) {
+        let timeout = options
+          .handler_timeout_ms
+          .unwrap_or(DEFAULT_HANDLER_DRAIN_TIMEOUT_MS);
+        if !in_flight::wait_until_idle(Duration::from_millis(timeout.into())).await {
+          eprintln!(
+            "警告：等待处理函数超时，仍有 {} 个处理函数未发送响应",
+            in_flight::in_flight_count()
+          );
+        }
+      }
+      // 删除 Unix 域套接字文件，下次启动时可以直接绑定
+      if let Some(path) = &self.options.unix_socket {
+        if let Err(e) = std::fs::remove_file(path) {
+          if e.kind() != std::io::ErrorKind::NotFound {
+            eprintln!("警告：删除 Unix 套接字文件 {} 失败: {}", path, e);
+          }
+        }
+      }
+
+      if graceful {
+        println!("✅ 服务器已完全停止");
+        Ok("服务器已停止".to_string())
+      } else {
+        println!("⚠️ 优雅关闭超时，剩余连接已被强制关闭");
+        Ok(format!(
+          "服务器已强制停止：{} 个进行中的请求在生成响应之前被中断，其连接已被关闭",
+          interrupted
+        ))
+      }
+    } else {
+      Err(napi::Error::from_reason("服务器未运行"))
+    }
+  }
+
+  #[napi]
+  /// 暂停接受新连接，已建立的连接继续处理请求，用于维护窗口
+  pub async fn pause(&self) -> Result<()> {
+    self.running_handle()?.pause().await;
+    println!("⏸️ 服务器已暂停接受新连接");
+    Ok(())
+  }
+
+  #[napi]
+  /// 恢复接受新连接
+  pub async fn resume(&self) -> Result<()> {
+    self.running_handle()?.resume().await;
+    println!("▶️ 服务器已恢复接受新连接");
+    Ok(())
+  }
+}
+
+impl Server {
+  /// 绑定并监听地址，成功后保存句柄并在后台运行 actix 服务器
+  fn launch(&self) -> Result<ServerStartInfo> {
     let host = self.options.host.clone();
-    let port = self.options.port;
 
-    // 检查端口是否可用
-    if let Err(e) = TcpListener::bind(format!("{}:{}", &host, port)) {
-      return Err(napi::Error::from_reason(format!(
-        "无法绑定到 {}:{}：{}",
-        &host, port, e
-      )));
+    // actix 的 workers(0) 会直接 panic，这里提前返回错误
+    let workers = self.options.workers;
+    if workers == Some(0) {
+      return Err(napi::Error::from_reason("workers 必须大于 0"));
     }
 
+    let tls = self.options.tls_cert_path.is_some() || self.options.tls_key_path.is_some();
+    let (listener, host, port, url) = match &self.options.unix_socket {
+      Some(_) if tls => {
+        return Err(napi::Error::from_reason(
+          "unixSocket 不能与 tlsCertPath / tlsKeyPath 同时使用",
+        ));
+      }
+      // Unix 域套接字模式下忽略 host 和 port
+      Some(path) => (
+        bind_unix_socket(path)?,
+        path.clone(),
+        0,
+        format!("unix:{}", path),
+      ),
+      None => {
+        // 只绑定一次，把同一个监听套接字交给 actix，避免检查和绑定之间端口被其他进程占用
+        let listener = bind_with_retry(
+          &format!("{}:{}", &host, self.options.port),
+          self.options.bind_retry.as_ref(),
+        )
+        .map_err(|e| {
+          napi::Error::from_reason(format!("无法绑定到 {}:{}：{}", &host, self.options.port, e))
+        })?;
+        let port = listener
+          .local_addr()
+          .map_err(|e| napi::Error::from_reason(format!("无法获取监听地址：{}", e)))?
+          .port();
+        let listener = tcp_listener(
+          listener,
+          self.options.tls_cert_path.as_deref(),
+          self.options.tls_key_path.as_deref(),
+        )?;
+        let scheme = if tls { "https" } else { "http" };
+        let url = format!("{}://{}:{}", scheme, host, port);
+        (listener, host, port, url)
+      }
+    };
+
     // 初始化路由读取器
     router::store::initialise_reader();
 
-    let handle_clone = self.handle.clone();
-    let host_clone = host.clone();
+    let graceful_timeout = self.graceful_timeout();
+    let idle_timeout = self
+      .options
+      .idle_connection_timeout_ms
+      .map(|ms| Duration::from_millis(ms as u64));
+    let keep_alive_secs = self.options.keep_alive_secs;
+    let client_timeout_ms = self.options.client_timeout_ms;
+    let mut dispatch_config = DispatchConfig::from_options(&self.options)?;
+    let access_log_writer = self.options.access_log.as_deref().map(|sink| {
+      let (logger, writer) = access_log::access_log_channel(sink);
+      dispatch_config.access_log = Some(logger);
+      writer
+    });
+    let dispatch_config = web::Data::new(dispatch_config);
+    let requests = self.requests.clone();
+    let log_format = self.options.log_format.clone();
+    if log_format.is_some() {
+      access_log::install_middleware_log_sink();
+    }
 
     // 使用napi的runtime检查来确保在正确的上下文中运行
+    // 监听在这里同步完成，失败时 start() 直接返回错误；监听成功后才在后台运行服务器
     napi::bindgen_prelude::within_runtime_if_available(|| {
-      napi::tokio::spawn(async move {
-        let server = HttpServer::new(|| {
-          App::new()
-            // .wrap(middleware::Logger::default())
-            // 所有路由都通过动态路由处理器处理
-            .default_service(web::route().to(handle_dynamic_route))
-        })
-        // .workers(1)
-        .bind(format!("{}:{}", host_clone, port))
-        .unwrap()
-        .run();
+      let mut server = HttpServer::new(move || {
+        let logger = match log_format.as_deref() {
+          Some("default") | None => middleware::Logger::default(),
+          Some(format) => middleware::Logger::new(format),
+        };
+        let access_log_config = dispatch_config.clone();
+        let requests = requests.clone();
+        App::new()
+          .app_data(dispatch_config.clone())
+          // Condition 保持 App 类型不变，未设置 logFormat 时直接调用内部服务
+          .wrap(middleware::Condition::new(log_format.is_some(), logger))
+          // 访问日志注册在最后即最外层，记录经过所有中间件之后的最终响应（包括 Content-Encoding）
+          // 同时跟踪本实例的请求：响应生成之前被丢弃（例如 stop() 超时强制关闭连接）的请求记为中断
+          .wrap_fn(move |req, srv| {
+            let tracked = requests.begin();
+            // 未开启访问日志时不记录任何请求信息
+            let entry = access_log_config.access_log.as_ref().map(|access_log| {
+              access_log.begin(
+                req.method(),
+                req.path(),
+                access_log_config.resolve_remote_addr(req.request()),
+              )
+            });
+            let response = srv.call(req);
+            async move {
+              let response = response.await;
+              tracked.finish();
+              let response = response?;
+              if let Some(entry) = entry {
+                entry.finish(response.response());
+              }
+              Ok(response)
+            }
+          })
+          // 所有路由都通过动态路由处理器处理
+          .default_service(web::route().to(dispatch_dynamic_route))
+      });
+
+      if let Some(workers) = workers {
+        server = server.workers(workers as usize);
+      }
+      if let Some(timeout) = graceful_timeout {
+        server = server.shutdown_timeout(timeout.as_secs());
+      }
 
-        // 存储服务器句柄
-        {
-          let mut handle_lock = handle_clone.lock();
-          *handle_lock = Some(server.handle());
+      // 空闲连接超时：主动关闭长时间没有请求的连接，释放文件描述符
+      if let Some(timeout) = idle_timeout {
+        server = server
+          .keep_alive(KeepAlive::Timeout(timeout))
+          .client_request_timeout(timeout);
+      }
+      // 单独设置的 keep-alive 和请求头读取超时覆盖 idleConnectionTimeoutMs
+      if let Some(secs) = keep_alive_secs {
+        server = server.keep_alive(match secs {
+          0 => KeepAlive::Disabled,
+          secs => KeepAlive::Timeout(Duration::from_secs(secs as u64)),
+        });
+      }
+      if let Some(ms) = client_timeout_ms {
+        // Duration::ZERO 表示不限制
+        server = server.client_request_timeout(Duration::from_millis(ms as u64));
+      }
+
+      let listening = match listener {
+        ServerListener::Tcp(listener) => server.listen(listener),
+        #[cfg(feature = "tls")]
+        ServerListener::Tls(listener, tls_config) => {
+          server.listen_rustls_0_23(listener, tls_config)
         }
+        #[cfg(unix)]
+        ServerListener::Unix(listener) => server.listen_uds(listener),
+      };
+      let server = listening
+        .map_err(|e| napi::Error::from_reason(format!("服务器监听失败：{}", e)))?
+        .run();
 
-        println!("✅ 服务器已启动：http://{}:{}", host_clone, port);
+      // 存储服务器句柄
+      *self.handle.lock() = ServerState::Running(server.handle());
+      if let Some(writer) = access_log_writer {
+        napi::tokio::spawn(writer.run());
+      }
+      println!("✅ 服务器已启动：{}", url);
 
+      napi::tokio::spawn(async move {
         // 运行服务器
         if let Err(e) = server.await {
           eprintln!("❌ 服务器错误: {}", e);
         }
       });
-    });
+      Ok(())
+    })?;
 
-    Ok(format!("服务器已启动：http://{}:{}", host, port))
+    Ok(ServerStartInfo { host, port, url })
   }
 
-  #[napi]
-  pub async fn stop(&self) -> Result<String> {
-    // 先取出handle，避免在持有锁时await
-    let handle = {
-      let mut handle_lock = self.handle.lock();
-      handle_lock.take()
-    };
+  /// 优雅关闭的等待上限，actix 以秒为单位，向上取整
+  fn graceful_timeout(&self) -> Option<Duration> {
+    self
+      .options
+      .graceful_timeout_ms
+      .map(|ms| Duration::from_secs(u64::from(ms).div_ceil(1000)))
+  }
 
-    if let Some(handle) = handle {
-      // 直接await服务器停止，确保完全停止后才返回
-      handle.stop(true).await;
-      println!("✅ 服务器已完全停止");
-      Ok("服务器已停止".to_string())
-    } else {
-      Err(napi::Error::from_reason("服务器未运行"))
+  /// 复制一份服务器句柄，避免在持有锁时 await
+  fn running_handle(&self) -> Result<actix_web::dev::ServerHandle> {
+    match &*self.handle.lock() {
+      ServerState::Running(handle) => Ok(handle.clone()),
+      _ => Err(napi::Error::from_reason("服务器未运行")),
     }
   }
 }
 
-// 动态路由处理函数 - 异步优化版本
-async fn handle_dynamic_route(req: HttpRequest, body: web::Bytes) -> HttpResponse {
+// 动态路由分发 - 异步优化版本
+async fn dispatch_dynamic_route(
+  req: HttpRequest,
+  payload: web::Payload,
+  config: web::Data<DispatchConfig>,
+) -> HttpResponse {
+  let started_at = Instant::now();
+
+  // 🚀 访问控制：在路由匹配和 JavaScript 回调之前拒绝被禁止的客户端
+  let remote_addr = config.resolve_remote_addr(&req);
+  if !config.is_ip_allowed(remote_addr) {
+    return HttpResponse::Forbidden()
+      .content_type("application/json")
+      .body(r#"{"error": "Forbidden"}"#);
+  }
+
+  // 虚拟主机：拒绝 Host 不在白名单中的请求，防止 Host 头注入
+  if !config.is_host_allowed(&req) {
+    return HttpResponse::MisdirectedRequest()
+      .content_type("application/json")
+      .body(r#"{"error": "Misdirected Request"}"#);
+  }
+
   let path = req.path();
-  let method = req.method().clone();
+  let method = config
+    .override_method(&req)
+    .unwrap_or_else(|| req.method().clone());
 
   // 🚀 优化：一次性获取回调函数和路径参数，避免重复路由匹配
-  if let Some((callback, path_params)) = router::read_only::get_route_with_params(path, method) {
-    // 创建oneshot channel用于接收响应
-    let (tx, rx) = tokio::sync::oneshot::channel::<JsResponse>();
-
-    // 🚀 关键优化：使用DetachedRequestWrapper，避免BorrowMutError
-    // 提前提取所有请求数据，不持有HttpRequest引用
-    let mut detached_wrapper = DetachedRequestWrapper::new_detached(req, Some(body), path_params);
-    detached_wrapper.set_response_sender(tx);
-
-    // 🚀 异步执行JavaScript回调，不阻塞Rust主线程
-    // JavaScript回调现在可以使用async/await语法
-    router::node_functions::execute_callback_with_detached_request(callback, detached_wrapper);
-
-    // 🚀 非阻塞等待：Rust主线程立即返回，JavaScript异步处理
-    // 设置合理的超时时间，但不阻塞其他请求
-    match tokio::time::timeout(std::time::Duration::from_secs(10), rx).await {
-      Ok(Ok(js_response)) => {
+  let mut lookup = router::read_only::get_route_with_params(path, method.clone());
+  // HEAD 请求没有显式注册时回退到同一路径的 GET 处理函数，发送前去掉响应体
+  let head_fallback = config.auto_head && method == Method::HEAD && lookup.is_err();
+  if head_fallback {
+    lookup = router::read_only::get_route_with_params(path, Method::GET);
+  }
+  if let Ok((route, path_params)) = lookup {
+    // HTTP/1.0 连接语义，同样需要在请求被移交之前判断
+    let close_connection = wants_connection_close(&req);
+
+    // 原始字节响应按 Range 头返回部分内容；带 If-Range 的条件请求不做校验，直接返回完整响应体
+    let range = (!req.headers().contains_key(IF_RANGE))
+      .then(|| req.headers().get(RANGE))
+      .flatten()
+      .and_then(|value| value.to_str().ok())
+      .map(str::to_string);
+
+    // JSON 响应信封需要请求 ID，在请求被移交给 JavaScript 之前提取
+    let envelope = config
+      .json_envelope
+      .as_ref()
+      .map(|envelope| envelope.for_request(&req));
+
+    // 录制模式：提前提取请求信息，未开启时只有一次读锁的开销
+    let recorder = active_recorder();
+    let mut request_record = recorder
+      .as_ref()
+      .map(|recorder| recorder.capture_request(&req));
+
+    // 请求合并：相同的并发请求只调用一次处理函数，在 before 钩子放行之后才加入
+    let coalesce_key = route
+      .coalesce
+      .then(|| coalesce::coalesce_key(method.as_str(), path, req.query_string()));
+
+    let (outcome, timing, after_hook) = match invoke_handler(
+      req,
+      payload,
+      &route,
+      path_params,
+      remote_addr,
+      &config,
+      request_record.as_mut(),
+      coalesce_key,
+    )
+    .await
+    {
+      Ok(result) => result,
+      Err(response) => return response,
+    };
+
+    let mut response = match outcome {
+      coalesce::HandlerOutcome::Response(mut js_response) => {
+        if config.sniff_content_type {
+          js_response.sniff_content_type();
+        }
+        // 信封包装和 Range 截取先于后处理器，签名等后处理器看到的是最终发送的响应体
+        js_response.apply_envelope(envelope.as_ref());
+        if matches!(method, Method::GET | Method::HEAD) {
+          js_response.apply_byte_range(range.as_deref());
+        }
+        post_process::apply_post_processors(&route.pattern, &mut js_response);
+        stream_transform::apply_chunk_transforms(&route.pattern, &mut js_response);
         // 将JsResponse转换为HttpResponse
-        js_response.into_http_response()
+        if head_fallback {
+          js_response.into_head_response()
+        } else {
+          js_response.into_http_response()
+        }
       }
-      Ok(Err(_)) => {
+      coalesce::HandlerOutcome::NoResponse => {
         // 发送器被丢弃，说明JavaScript代码没有发送响应
         HttpResponse::InternalServerError()
           .content_type("application/json")
           .body(r#"{"error": "JavaScript callback did not send response"}"#)
       }
-      Err(_) => {
-        // 超时 - 增加到10秒，给异步处理更多时间
-        HttpResponse::RequestTimeout()
+      coalesce::HandlerOutcome::TimedOut => {
+        // 处理函数在 requestTimeoutMs 内没有发送响应
+        let limit_ms = config.request_timeout.unwrap_or_default().as_millis();
+        HttpResponse::GatewayTimeout()
           .content_type("application/json")
-          .body(r#"{"error": "Request timeout - JavaScript callback took too long"}"#)
+          .body(
+            serde_json::json!({
+              "error": format!("JavaScript callback did not respond within {}ms", limit_ms),
+              "timeoutMs": limit_ms as u64,
+            })
+            .to_string(),
+          )
       }
+    };
+
+    if let Some(timing) = timing {
+      timing.attach_to(&mut response);
+    }
+    apply_connection_semantics(&mut response, close_connection);
+    config.log_if_slow(&method, &route.pattern, started_at.elapsed(), &response);
+    if let Some(wrapper) = after_hook {
+      hooks::run_after_hook(wrapper, response.status().as_u16());
+    }
+
+    match (recorder, request_record) {
+      (Some(recorder), Some(record)) => recorder.record(record, response),
+      _ => response,
     }
   } else {
-    // 路由未找到
-    HttpResponse::NotFound()
-      .content_type("application/json")
-      .body(format!(
-        r#"{{"error": "Route not found", "path": "{}"}}"#,
-        path
-      ))
+    match lookup {
+      // 只差末尾斜杠时，按配置重定向到修正后的路径（308 保留请求方法和请求体）
+      Err(RouteMiss::TrailingSlashRedirect(location)) if config.redirect_trailing_slash => {
+        let location = match req.query_string() {
+          "" => location,
+          query => format!("{}?{}", location, query),
+        };
+        HttpResponse::PermanentRedirect()
+          .insert_header(("Location", location))
+          .finish()
+      }
+      // 请求方法不在支持的范围内，任何路由都无法处理
+      Err(RouteMiss::MethodNotImplemented) => HttpResponse::NotImplemented()
+        .content_type("application/json")
+        .body(
+          serde_json::json!({ "error": "Method not implemented", "method": method.as_str() })
+            .to_string(),
+        ),
+      _ => {
+        // 路径在其他请求方法下存在时返回 405，并在 Allow 头中列出支持的方法
+        let allowed = router::read_only::allowed_methods(path, config.auto_head);
+        if config.auto_options && method == Method::OPTIONS && !allowed.is_empty() {
+          return preflight_response(&req, allowed);
+        }
+        if allowed.is_empty() {
+          // 路由未找到
+          HttpResponse::NotFound()
+            .content_type("application/json")
+            .body(format!(
+              r#"{{"error": "Route not found", "path": "{}"}}"#,
+              path
+            ))
+        } else {
+          HttpResponse::MethodNotAllowed()
+            .insert_header(("Allow", allowed.join(", ")))
+            .content_type("application/json")
+            .body(
+              serde_json::json!({
+                "error": "Method not allowed",
+                "method": method.as_str(),
+                "allowed": allowed,
+              })
+              .to_string(),
+            )
+        }
+      }
+    }
   }
 }
 
+/// 构造请求包装器、运行 before 钩子、把回调放入队列并等待处理函数的响应
+/// 传入合并键时，before 钩子放行之后才加入合并：follower 复用 leader 的结果，不再调用处理函数，也不返回耗时拆分
+/// 读取请求体失败或回调无法进入队列时返回对应的错误响应
+#[allow(clippy::too_many_arguments)]
+async fn invoke_handler(
+  req: HttpRequest,
+  payload: web::Payload,
+  route: &RouteEntry,
+  path_params: HashMap<String, String>,
+  remote_addr: Option<IpAddr>,
+  config: &DispatchConfig,
+  request_record: Option<&mut RequestRecord>,
+  coalesce_key: Option<String>,
+) -> std::result::Result<
+  (
+    coalesce::HandlerOutcome,
+    Option<Arc<timing::DispatchTiming>>,
+    Option<DetachedRequestWrapper>,
+  ),
+  HttpResponse,
+> {
+  // 创建oneshot channel用于接收响应
+  let (tx, rx) = tokio::sync::oneshot::channel::<JsResponse>();
+
+  // 方法覆盖需要在请求被移交给包装器之前读取
+  let method_override = config.override_method(&req);
+
+  // 🚀 关键优化：使用DetachedRequestWrapper，避免BorrowMutError
+  // 提前提取所有请求数据，不持有HttpRequest引用
+  let mut detached_wrapper = if route.multipart_stream {
+    // 流式 multipart 路由：不缓冲请求体，由 JavaScript 按 part 逐块读取
+    let multipart = match multipart_from_payload(req.headers(), payload) {
+      Ok(multipart) => multipart,
+      Err(e) => {
+        return Err(
+          HttpResponse::BadRequest()
+            .content_type("application/json")
+            .body(
+              serde_json::json!({ "error": "Invalid multipart body", "detail": e }).to_string(),
+            ),
+        );
+      }
+    };
+    let mut wrapper = DetachedRequestWrapper::new_detached(req, None, path_params, remote_addr);
+    wrapper.set_multipart_stream(multipart);
+    wrapper
+  } else {
+    let body = match read_body(&req, payload, config.max_body_size).await {
+      Ok(body) => body,
+      Err(BodyReadError::Invalid) => {
+        return Err(
+          HttpResponse::BadRequest()
+            .content_type("application/json")
+            .body(r#"{"error": "Failed to read request body"}"#),
+        );
+      }
+      Err(BodyReadError::TooLarge) => {
+        return Err(
+          HttpResponse::PayloadTooLarge()
+            .content_type("application/json")
+            .body(
+              serde_json::json!({
+                "error": "Payload too large",
+                "maxBodySize": config.max_body_size,
+              })
+              .to_string(),
+            ),
+        );
+      }
+    };
+    if let Some(record) = request_record {
+      record.set_body(&body);
+    }
+    DetachedRequestWrapper::new_detached(req, Some(body), path_params, remote_addr)
+  };
+
+  // 并发上限：在读取完请求体之后、分发给 JavaScript 之前获取，慢速上传不占用名额
+  // 许可一直持有到处理函数发送响应或等待超时
+  let mut permit = acquire_permit(config).await?;
+  if let Some(method) = method_override {
+    detached_wrapper.set_method(method.as_str());
+  }
+  detached_wrapper.set_max_response_body_size(config.max_response_body_size);
+  detached_wrapper.set_request_timeout(config.request_timeout);
+  detached_wrapper.set_csrf_secret(config.csrf_secret.clone());
+  detached_wrapper.set_wildcard_param(router::store::catch_all_param(&route.pattern));
+  let timing = timing::DispatchTiming::start();
+  detached_wrapper.set_dispatch_timing(timing.clone());
+
+  // 全局钩子：after 钩子需要在包装器移交给处理函数之前保留一份请求数据
+  let after_hook = hooks::has_after_hook().then(|| detached_wrapper.hook_copy());
+  let hook_outcome = match hooks::run_before_hook(
+    &detached_wrapper,
+    config.blocking_callback_queue,
+    config.request_timeout,
+    timing.clone(),
+  )
+  .await
+  {
+    hooks::BeforeHookOutcome::Continue => None,
+    // before 钩子已经发送响应，跳过处理函数
+    hooks::BeforeHookOutcome::Respond(js_response) => {
+      Some(coalesce::HandlerOutcome::Response(js_response))
+    }
+    hooks::BeforeHookOutcome::Abandoned => Some(coalesce::HandlerOutcome::NoResponse),
+    hooks::BeforeHookOutcome::TimedOut => Some(coalesce::HandlerOutcome::TimedOut),
+    hooks::BeforeHookOutcome::Dropped => return Err(overloaded_response()),
+  };
+  if let Some(outcome) = hook_outcome {
+    return Ok((outcome, Some(timing), after_hook));
+  }
+
+  // 请求合并：每个请求都先经过自己的 before 钩子，放行之后才可能复用 leader 的结果
+  let leader = match coalesce_key.map(coalesce::join) {
+    Some(coalesce::Coalesced::Leader(leader)) => Some(leader),
+    Some(coalesce::Coalesced::Follower(rx)) => {
+      // 等待 leader 期间不占用并发名额
+      drop(permit);
+      if let Ok(outcome) = rx.await {
+        return Ok((outcome, None, after_hook));
+      }
+      // leader 被取消或结果无法复制时自行调用处理函数
+      permit = acquire_permit(config).await?;
+      None
+    }
+    None => None,
+  };
+
+  let in_flight = in_flight::InFlightCallback::begin();
+
+  // 🚀 异步执行JavaScript回调，不阻塞Rust主线程
+  // JavaScript回调现在可以使用async/await语法
+  let queued = match &route.callback {
+    router::node_functions::RouteCallback::Handler(callback) => {
+      let response_sender = Arc::new(Mutex::new(Some(tx)));
+      detached_wrapper.set_response_sender(response_sender.clone());
+      detached_wrapper.set_in_flight(in_flight);
+      router::node_functions::execute_callback_with_detached_request(
+        callback,
+        detached_wrapper,
+        response_sender,
+        config.blocking_callback_queue,
+        timing.clone(),
+      )
+    }
+    // 函数式路由以处理函数的返回值作为响应
+    router::node_functions::RouteCallback::Functional(callback) => {
+      router::node_functions::execute_functional_callback(
+        callback,
+        detached_wrapper,
+        tx,
+        config.blocking_callback_queue,
+        timing.clone(),
+        in_flight,
+      )
+    }
+  };
+  if !queued {
+    // 回调没有进入队列，不必等到超时
+    return Err(overloaded_response());
+  }
+
+  // 🚀 非阻塞等待：Rust主线程立即返回，JavaScript异步处理
+  // 设置合理的超时时间，但不阻塞其他请求
+  let received = match config.request_timeout {
+    Some(limit) => tokio::time::timeout(limit, rx).await.ok(),
+    // 0 表示不限制等待时间
+    None => Some(rx.await),
+  };
+  let outcome = match received {
+    Some(Ok(js_response)) => coalesce::HandlerOutcome::Response(js_response),
+    Some(Err(_)) => coalesce::HandlerOutcome::NoResponse,
+    None => coalesce::HandlerOutcome::TimedOut,
+  };
+  // leader 提前返回时占位随之释放，等待中的 follower 各自调用处理函数
+  if let Some(leader) = leader {
+    leader.finish(&outcome);
+  }
+  drop(permit);
+  Ok((outcome, Some(timing), after_hook))
+}
+
+/// 获取 maxConcurrentRequests 的许可，未设置上限时返回 None，宽限时间内没有空闲名额时返回 503
+async fn acquire_permit(
+  config: &DispatchConfig,
+) -> std::result::Result<Option<SemaphorePermit<'_>>, HttpResponse> {
+  match &config.concurrency_limit {
+    Some(limit) => match limit.acquire().await {
+      Some(permit) => Ok(Some(permit)),
+      None => Err(overloaded_response()),
+    },
+    None => Ok(None),
+  }
+}
+
+/// 读取请求体失败的原因
+enum BodyReadError {
+  /// 请求体（解压后）超过 maxBodySize
+  TooLarge,
+  /// 读取或解压失败
+  Invalid,
+}
+
+/// 读取完整的请求体；Content-Encoding 为 gzip / deflate / br / zstd 时透明解压，之后的 JSON / 表单解析看到的都是解压后的内容
+/// 上限针对解压后的大小，避免压缩炸弹在内存中放大；未知的编码按原样读取
+async fn read_body(
+  req: &HttpRequest,
+  payload: web::Payload,
+  limit: usize,
+) -> std::result::Result<Bytes, BodyReadError> {
+  let mut stream = Decompress::from_headers(payload, req.headers());
+  let mut body = BytesMut::new();
+  while let Some(chunk) = stream.next().await {
+    let chunk = chunk.map_err(|_| BodyReadError::Invalid)?;
+    if body.len() + chunk.len() > limit {
+      return Err(BodyReadError::TooLarge);
+    }
+    body.extend_from_slice(&chunk);
+  }
+  Ok(body.freeze())
+}
+
+/// autoOptions 的 OPTIONS 自动应答：Allow 列出路径支持的方法，并回显预检请求的方法和头部
+/// 不回显 Origin：把任意来源写入 Access-Control-Allow-Origin 等于对所有站点放行
+fn preflight_response(req: &HttpRequest, mut allowed: Vec<&'static str>) -> HttpResponse {
+  allowed.push("OPTIONS");
+  let request_header = |name| {
+    req
+      .headers()
+      .get(name)
+      .and_then(|value| value.to_str().ok())
+  };
+
+  let mut response = HttpResponse::NoContent();
+  response.insert_header(("Allow", allowed.join(", ")));
+  // 请求的方法没有注册时不回显，浏览器会据此拒绝实际请求
+  if let Some(requested) = request_header(ACCESS_CONTROL_REQUEST_METHOD).filter(|requested| {
+    allowed
+      .iter()
+      .any(|method| method.eq_ignore_ascii_case(requested.trim()))
+  }) {
+    response.insert_header((ACCESS_CONTROL_ALLOW_METHODS, requested.trim()));
+  }
+  if let Some(headers) = request_header(ACCESS_CONTROL_REQUEST_HEADERS) {
+    response.insert_header((ACCESS_CONTROL_ALLOW_HEADERS, headers));
+  }
+  response.finish()
+}
+
+/// 回调队列已满或达到 maxConcurrentRequests 时的 503 响应
+fn overloaded_response() -> HttpResponse {
+  HttpResponse::ServiceUnavailable()
+    .insert_header(("Retry-After", "1"))
+    .content_type("application/json")
+    .body(r#"{"error": "Server is overloaded, please retry later"}"#)
+}
+
 // 强制清理所有资源的函数
 #[napi]
 pub fn force_cleanup() -> Result<()> {
@@ -184,16 +993,34 @@ pub fn force_cleanup() -> Result<()> {
 }
 
 // 强制退出进程（最后手段）
+// 每次调用和取消都会递增代数，延迟结束时代数没有变化才退出：
+// 取消只影响已经发出的调用，之后的调用不会被之前的取消影响，之前的调用也不会在重新调用后复活
+static FORCE_EXIT_GENERATION: AtomicU64 = AtomicU64::new(0);
+
 #[napi]
-pub fn force_exit() -> Result<()> {
+/// 在 delayMs 毫秒（默认 100）后以 exitCode（默认 0）强制退出进程，不等待任何清理
+/// 延迟期间可以调用 cancelForceExit 取消；重复调用时只有最后一次生效
+pub fn force_exit(exit_code: Option<i32>, delay_ms: Option<u32>) -> Result<()> {
+  let exit_code = exit_code.unwrap_or(0);
+  let delay = Duration::from_millis(delay_ms.unwrap_or(100).into());
+  let generation = FORCE_EXIT_GENERATION.fetch_add(1, Ordering::AcqRel) + 1;
+
   // 在新线程中延迟退出，给当前函数返回的时间
-  std::thread::spawn(|| {
-    std::thread::sleep(std::time::Duration::from_millis(100));
-    std::process::exit(0);
+  std::thread::spawn(move || {
+    std::thread::sleep(delay);
+    if FORCE_EXIT_GENERATION.load(Ordering::Acquire) == generation {
+      std::process::exit(exit_code);
+    }
   });
   Ok(())
 }
 
+#[napi]
+/// 取消尚未执行的 forceExit，没有等待中的 forceExit 时不做任何事
+pub fn cancel_force_exit() {
+  FORCE_EXIT_GENERATION.fetch_add(1, Ordering::AcqRel);
+}
+
 // 简单测试函数
 #[napi]
 pub fn sum(a: i32, b: i32) -> i32 {
@@ -0,0 +1,224 @@
+use actix_multipart::{Field, Multipart};
+use actix_web::error::PayloadError;
+use actix_web::http::header::HeaderMap;
+use actix_web::mime;
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use tokio::sync::mpsc;
+
+// 流式 multipart 模块
+// actix-multipart 的 Multipart / Field 不是 Send，无法跨线程交给 JavaScript 一侧；
+// 因此在 actix worker 上用一个本地任务逐个读取 part，把元信息和数据块按顺序转发到 mpsc 通道，
+// JavaScript 再从通道另一端按需拉取，整个过程不缓冲完整请求体，也不写磁盘
+
+/// 转发通道容量：JavaScript 读取变慢时 worker 会在此处等待，形成背压
+const PART_CHANNEL_CAPACITY: usize = 16;
+
+#[napi(object)]
+/// 流式 multipart 中单个 part 的元信息
+pub struct MultipartPartInfo {
+  pub name: Option<String>,
+  pub filename: Option<String>,
+  #[napi(js_name = "contentType")]
+  pub content_type: Option<String>,
+}
+
+impl MultipartPartInfo {
+  pub fn from_field(field: &Field) -> Self {
+    let filename = field.content_disposition().and_then(|disposition| {
+      disposition.get_filename().map(str::to_string).or_else(|| {
+        disposition
+          .get_filename_ext()
+          .map(|ext| String::from_utf8_lossy(&ext.value).into_owned())
+      })
+    });
+
+    Self {
+      name: field.name().map(str::to_string),
+      filename,
+      content_type: field.content_type().map(|mime| mime.to_string()),
+    }
+  }
+}
+
+/// 本地任务转发给 JavaScript 一侧的事件
+enum PartEvent {
+  Part(MultipartPartInfo),
+  Chunk(Bytes),
+  Error(String),
+}
+
+/// 可跨线程持有的流式 multipart 读取端
+pub struct MultipartStream {
+  events: mpsc::Receiver<PartEvent>,
+  // 读取数据块时遇到的下一个 part，留给下一次 next_part 返回
+  pending_part: Option<MultipartPartInfo>,
+  in_part: bool,
+}
+
+impl MultipartStream {
+  /// 前进到下一个 part，当前 part 未读完的数据块会被跳过
+  pub async fn next_part(&mut self) -> Result<Option<MultipartPartInfo>, String> {
+    if let Some(info) = self.pending_part.take() {
+      self.in_part = true;
+      return Ok(Some(info));
+    }
+
+    loop {
+      match self.events.recv().await {
+        Some(PartEvent::Part(info)) => {
+          self.in_part = true;
+          return Ok(Some(info));
+        }
+        Some(PartEvent::Chunk(_)) => continue,
+        Some(PartEvent::Error(e)) => {
+          self.in_part = false;
+          return Err(e);
+        }
+        None => {
+          self.in_part = false;
+          return Ok(None);
+        }
+      }
+    }
+  }
+
+  /// 读取当前 part 的下一个数据块，当前 part 读完时返回 None
+  pub async fn next_chunk(&mut self) -> Result<Option<Bytes>, String> {
+    if !self.in_part {
+      return Ok(None);
+    }
+
+    match self.events.recv().await {
+      Some(PartEvent::Chunk(chunk)) => Ok(Some(chunk)),
+      Some(PartEvent::Part(info)) => {
+        self.pending_part = Some(info);
+        self.in_part = false;
+        Ok(None)
+      }
+      Some(PartEvent::Error(e)) => {
+        self.in_part = false;
+        Err(e)
+      }
+      None => {
+        self.in_part = false;
+        Ok(None)
+      }
+    }
+  }
+}
+
+/// 根据请求头创建流式 multipart 读取端，Content-Type 不是带 boundary 的 multipart 时返回错误
+/// 必须在 actix worker 上调用：解析任务通过 actix_web::rt::spawn 运行在当前线程
+pub fn multipart_from_payload<S>(headers: &HeaderMap, payload: S) -> Result<MultipartStream, String>
+where
+  S: Stream<Item = Result<Bytes, PayloadError>> + 'static,
+{
+  let content_type = headers
+    .get("content-type")
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.parse::<mime::Mime>().ok())
+    .ok_or_else(|| "缺少或无法解析 Content-Type".to_string())?;
+  if content_type.type_() != mime::MULTIPART || content_type.get_param(mime::BOUNDARY).is_none() {
+    return Err("Content-Type 不是带 boundary 的 multipart".to_string());
+  }
+
+  let mut multipart = Multipart::new(headers, payload);
+  let (sender, receiver) = mpsc::channel::<PartEvent>(PART_CHANNEL_CAPACITY);
+
+  actix_web::rt::spawn(async move {
+    while let Some(field) = multipart.next().await {
+      let mut field = match field {
+        Ok(field) => field,
+        Err(e) => {
+          let _ = sender.send(PartEvent::Error(e.to_string())).await;
+          return;
+        }
+      };
+      if sender
+        .send(PartEvent::Part(MultipartPartInfo::from_field(&field)))
+        .await
+        .is_err()
+      {
+        // 处理函数已经不再读取，提前结束转发
+        return;
+      }
+
+      while let Some(chunk) = field.next().await {
+        let event = match chunk {
+          Ok(chunk) => PartEvent::Chunk(chunk),
+          Err(e) => PartEvent::Error(e.to_string()),
+        };
+        let failed = matches!(event, PartEvent::Error(_));
+        if sender.send(event).await.is_err() || failed {
+          return;
+        }
+      }
+    }
+  });
+
+  Ok(MultipartStream {
+    events: receiver,
+    pending_part: None,
+    in_part: false,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use actix_web::http::header::{HeaderValue, CONTENT_TYPE};
+
+  #[actix_web::test]
+  async fn test_multipart_stream_reads_parts_in_chunks() {
+    let body = "--b\r\n\
+      Content-Disposition: form-data; name=\"title\"\r\n\r\n\
+      hello\r\n\
+      --b\r\n\
+      Content-Disposition: form-data; name=\"file\"; filename*=UTF-8''%E6%8A%A5%E5%91%8A.txt\r\n\
+      Content-Type: text/plain\r\n\r\n\
+      0123456789\r\n\
+      --b--\r\n";
+    // 按 4 字节切分，模拟网络上逐块到达的请求体
+    let chunks: Vec<Result<Bytes, PayloadError>> = body
+      .as_bytes()
+      .chunks(4)
+      .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+      .collect();
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+      CONTENT_TYPE,
+      HeaderValue::from_static("multipart/form-data; boundary=b"),
+    );
+    let mut stream = multipart_from_payload(&headers, futures::stream::iter(chunks)).unwrap();
+
+    // 第一个 part 不读数据直接跳过
+    let title = stream.next_part().await.unwrap().unwrap();
+    assert_eq!(title.name.as_deref(), Some("title"));
+    assert_eq!(title.filename, None);
+
+    let file = stream.next_part().await.unwrap().unwrap();
+    assert_eq!(file.name.as_deref(), Some("file"));
+    assert_eq!(file.filename.as_deref(), Some("报告.txt"));
+    assert_eq!(file.content_type.as_deref(), Some("text/plain"));
+
+    let mut content = Vec::new();
+    while let Some(chunk) = stream.next_chunk().await.unwrap() {
+      content.extend_from_slice(&chunk);
+    }
+    assert_eq!(content, b"0123456789");
+
+    assert!(stream.next_part().await.unwrap().is_none());
+    assert!(stream.next_chunk().await.unwrap().is_none());
+  }
+
+  #[actix_web::test]
+  async fn test_multipart_stream_rejects_non_multipart() {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    let payload = futures::stream::iter(Vec::<Result<Bytes, PayloadError>>::new());
+
+    assert!(multipart_from_payload(&headers, payload).is_err());
+  }
+}
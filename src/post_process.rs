@@ -0,0 +1,116 @@
+use hmac::{Hmac, Mac};
+use parking_lot::RwLock;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock};
+
+use crate::response::JsResponse;
+
+// 响应后处理
+// 处理函数的响应在转换为 HttpResponse 之前依次经过全局和路由级的后处理器，
+// 后处理器可以修改状态码、头部和响应体，例如为响应体计算签名头
+
+/// 响应后处理器，在分发线程上同步执行
+pub type ResponsePostProcessor = Arc<dyn Fn(&mut JsResponse) + Send + Sync>;
+
+#[derive(Default)]
+struct PostProcessors {
+  /// 作用于所有路由，先于路由级后处理器执行
+  global: Vec<ResponsePostProcessor>,
+  /// 按注册时的路由模式（例如 "/users/:id"）索引
+  by_route: HashMap<String, Vec<ResponsePostProcessor>>,
+}
+
+static POST_PROCESSORS: LazyLock<RwLock<PostProcessors>> =
+  LazyLock::new(|| RwLock::new(PostProcessors::default()));
+
+/// 注册后处理器，route 为 None 时作用于所有路由，否则只作用于该路由模式
+pub fn register_post_processor(route: Option<&str>, processor: ResponsePostProcessor) {
+  let mut processors = POST_PROCESSORS.write();
+  match route {
+    Some(route) => processors
+      .by_route
+      .entry(route.to_string())
+      .or_default()
+      .push(processor),
+    None => processors.global.push(processor),
+  }
+}
+
+/// 按注册顺序对响应执行全局和 route_pattern 对应的后处理器
+pub fn apply_post_processors(route_pattern: &str, response: &mut JsResponse) {
+  let processors = POST_PROCESSORS.read();
+  if processors.global.is_empty() && processors.by_route.is_empty() {
+    return;
+  }
+
+  let route_processors = processors
+    .by_route
+    .get(route_pattern)
+    .map(Vec::as_slice)
+    .unwrap_or_default();
+  for processor in processors.global.iter().chain(route_processors) {
+    processor(response);
+  }
+}
+
+/// 计算响应体的 HMAC-SHA256 签名（十六进制），流式响应体无法签名，返回 None
+fn sign_body(secret: &[u8], response: &JsResponse) -> Option<String> {
+  let body = response.inner.body_bytes()?;
+  // HMAC 接受任意长度的密钥，new_from_slice 不会失败
+  let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC 密钥长度无效");
+  mac.update(body);
+  Some(
+    mac
+      .finalize()
+      .into_bytes()
+      .iter()
+      .map(|byte| format!("{:02x}", byte))
+      .collect(),
+  )
+}
+
+#[napi]
+/// 为响应添加签名头：值为响应体的 HMAC-SHA256 十六进制签名，在 Rust 侧计算，不经过 JavaScript
+/// route 为路由注册时的模式（例如 "/users/:id"），为 null 时作用于所有路由；流式响应不签名
+/// 签名针对最终发送的响应体，即 jsonEnvelope 包装和 Range 截取之后的内容
+pub fn add_response_signature(route: Option<String>, header_name: String, secret: String) {
+  let header_name = header_name.to_ascii_lowercase();
+  let secret: Arc<[u8]> = Arc::from(secret.as_bytes());
+
+  register_post_processor(
+    route.as_deref(),
+    Arc::new(move |response: &mut JsResponse| {
+      if let Some(signature) = sign_body(&secret, response) {
+        let headers = response.headers.get_or_insert_with(Vec::new);
+        headers.retain(|(name, _)| !name.eq_ignore_ascii_case(&header_name));
+        headers.push((header_name.clone(), signature));
+      }
+    }),
+  );
+}
+
+#[napi]
+/// 移除所有已注册的响应后处理器
+pub fn clear_response_post_processors() {
+  *POST_PROCESSORS.write() = PostProcessors::default();
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::response::InnerResp;
+
+  #[test]
+  fn test_sign_body() {
+    let response = JsResponse {
+      inner: InnerResp::Text("hello".to_string()),
+      status_code: None,
+      headers: None,
+    };
+    assert_eq!(
+      sign_body(b"key", &response).as_deref(),
+      Some("9307b3b915efb5171ff14d8cb55fbcc798c6c0ef1456d66ded1a6aa723a58b7b")
+    );
+  }
+}
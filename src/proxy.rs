@@ -0,0 +1,220 @@
+use bytes::Bytes;
+use futures::TryStreamExt;
+use std::net::IpAddr;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use crate::response::BodyStream;
+
+// 反向代理模块
+// 把当前请求原样转发到上游服务，上游响应体以流的方式交回 actix，不在内存中缓冲
+
+/// 建立连接的超时时间
+const UPSTREAM_CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 等待上游返回响应头的时间占 requestTimeoutMs 的比例
+/// 留出余量，保证上游超时时由代理返回 504，而不是被请求超时截断（默认 10 秒时为 8 秒）
+const UPSTREAM_RESPONSE_TIMEOUT_RATIO: f64 = 0.8;
+
+/// 逐跳头部，只对单个连接有效，不能转发（RFC 7230 6.1）
+const HOP_BY_HOP_HEADERS: [&str; 9] = [
+  "connection",
+  "keep-alive",
+  "proxy-authenticate",
+  "proxy-authorization",
+  "te",
+  "trailer",
+  "trailers",
+  "transfer-encoding",
+  "upgrade",
+];
+
+static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+  reqwest::Client::builder()
+    .connect_timeout(UPSTREAM_CONNECT_TIMEOUT)
+    // 重定向交给客户端处理
+    .redirect(reqwest::redirect::Policy::none())
+    .build()
+    .expect("创建上游 HTTP 客户端失败")
+});
+
+/// 转发失败的原因
+pub enum ProxyError {
+  /// 上游地址无效
+  InvalidUrl(String),
+  /// 上游在超时时间内没有返回响应头
+  Timeout,
+  /// 连接失败或上游返回了无效的响应
+  Upstream(reqwest::Error),
+}
+
+/// 上游返回的响应，响应体尚未读取
+pub struct UpstreamResponse {
+  pub status: u16,
+  pub headers: Vec<(String, String)>,
+  pub body: BodyStream,
+}
+
+/// 需要转发的请求内容
+pub struct ForwardRequest<'a> {
+  pub method: &'a str,
+  /// 路径和查询字符串
+  pub uri: &'a str,
+  /// 原始顺序的请求头，同名头部保留多个值
+  pub headers: &'a [(String, String)],
+  pub body: Option<Bytes>,
+  pub remote_addr: Option<IpAddr>,
+  /// 等待上游响应头的超时时间，为 None 时不限制
+  pub response_timeout: Option<Duration>,
+}
+
+/// 根据请求处理超时计算等待上游响应头的超时时间，requestTimeoutMs 为 0（不限制）时同样不限制
+pub fn upstream_response_timeout(request_timeout: Option<Duration>) -> Option<Duration> {
+  request_timeout.map(|timeout| timeout.mul_f64(UPSTREAM_RESPONSE_TIMEOUT_RATIO))
+}
+
+/// 把请求转发到 upstream_base_url，返回上游的状态码、头部和响应体流
+pub async fn forward(
+  upstream_base_url: &str,
+  request: ForwardRequest<'_>,
+) -> std::result::Result<UpstreamResponse, ProxyError> {
+  let url = upstream_url(upstream_base_url, request.uri);
+  let url =
+    reqwest::Url::parse(&url).map_err(|e| ProxyError::InvalidUrl(format!("{}: {}", url, e)))?;
+  let method = reqwest::Method::from_bytes(request.method.as_bytes())
+    .map_err(|_| ProxyError::InvalidUrl(format!("无效的请求方法 {}", request.method)))?;
+
+  let request_tokens = connection_tokens(header_value(request.headers, "connection").as_deref());
+  let mut builder = CLIENT.request(method, url);
+  for (name, value) in request.headers {
+    // Host 和 Content-Length 由客户端根据上游地址和请求体重新生成，X-Forwarded-For 在下面追加
+    if matches!(name.as_str(), "host" | "content-length" | "x-forwarded-for")
+      || is_hop_by_hop(name, &request_tokens)
+    {
+      continue;
+    }
+    builder = builder.header(name.as_str(), value.as_str());
+  }
+
+  // 追加客户端地址到 X-Forwarded-For
+  let forwarded_for = match (
+    header_value(request.headers, "x-forwarded-for"),
+    request.remote_addr,
+  ) {
+    (Some(existing), Some(ip)) => Some(format!("{}, {}", existing, ip)),
+    (Some(existing), None) => Some(existing),
+    (None, Some(ip)) => Some(ip.to_string()),
+    (None, None) => None,
+  };
+  if let Some(forwarded_for) = forwarded_for {
+    builder = builder.header("x-forwarded-for", forwarded_for);
+  }
+
+  if let Some(body) = request.body {
+    builder = builder.body(body);
+  }
+
+  let response = match request.response_timeout {
+    Some(limit) => tokio::time::timeout(limit, builder.send())
+      .await
+      .map_err(|_| ProxyError::Timeout)?,
+    None => builder.send().await,
+  }
+  .map_err(ProxyError::Upstream)?;
+
+  let response_tokens = connection_tokens(
+    response
+      .headers()
+      .get("connection")
+      .and_then(|v| v.to_str().ok()),
+  );
+  let headers = response
+    .headers()
+    .iter()
+    .filter(|(name, _)| {
+      name.as_str() != "content-length" && !is_hop_by_hop(name.as_str(), &response_tokens)
+    })
+    .filter_map(|(name, value)| {
+      value
+        .to_str()
+        .ok()
+        .map(|value| (name.to_string(), value.to_string()))
+    })
+    .collect();
+
+  Ok(UpstreamResponse {
+    status: response.status().as_u16(),
+    headers,
+    body: BodyStream::new(
+      response.content_length(),
+      response.bytes_stream().map_err(std::io::Error::other),
+    ),
+  })
+}
+
+/// 同名请求头按出现顺序用逗号合并（RFC 9110 5.3），不存在时返回 None
+fn header_value(headers: &[(String, String)], name: &str) -> Option<String> {
+  let values: Vec<&str> = headers
+    .iter()
+    .filter(|(header, _)| header == name)
+    .map(|(_, value)| value.as_str())
+    .collect();
+  (!values.is_empty()).then(|| values.join(", "))
+}
+
+/// 拼接上游地址和原始请求的路径、查询字符串
+fn upstream_url(base: &str, uri: &str) -> String {
+  format!("{}{}", base.trim_end_matches('/'), uri)
+}
+
+/// Connection 头部中列出的头部同样只对当前连接有效
+fn connection_tokens(connection: Option<&str>) -> Vec<String> {
+  connection
+    .map(|value| {
+      value
+        .split(',')
+        .map(|token| token.trim().to_ascii_lowercase())
+        .filter(|token| !token.is_empty())
+        .collect()
+    })
+    .unwrap_or_default()
+}
+
+fn is_hop_by_hop(name: &str, connection_tokens: &[String]) -> bool {
+  HOP_BY_HOP_HEADERS
+    .iter()
+    .any(|header| name.eq_ignore_ascii_case(header))
+    || connection_tokens
+      .iter()
+      .any(|token| name.eq_ignore_ascii_case(token))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_hop_by_hop_headers() {
+    let tokens = connection_tokens(Some("keep-alive, X-Internal-Token"));
+
+    assert!(is_hop_by_hop("transfer-encoding", &tokens));
+    assert!(is_hop_by_hop("x-internal-token", &tokens));
+    assert!(!is_hop_by_hop("content-type", &tokens));
+    let headers = vec![
+      ("x-tag".to_string(), "a".to_string()),
+      ("accept".to_string(), "*/*".to_string()),
+      ("x-tag".to_string(), "b".to_string()),
+    ];
+    assert_eq!(header_value(&headers, "x-tag").as_deref(), Some("a, b"));
+    assert_eq!(header_value(&headers, "connection"), None);
+    assert_eq!(
+      upstream_response_timeout(Some(Duration::from_secs(10))),
+      Some(Duration::from_secs(8))
+    );
+    assert_eq!(upstream_response_timeout(None), None);
+    assert_eq!(
+      upstream_url("http://127.0.0.1:9000/", "/api/users?page=2"),
+      "http://127.0.0.1:9000/api/users?page=2"
+    );
+  }
+}
@@ -0,0 +1,202 @@
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::{HttpRequest, HttpResponse};
+use bytes::Bytes;
+use napi::bindgen_prelude::*;
+use parking_lot::RwLock;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// 请求/响应录制模块
+// 开启后每个请求会把方法、路径、请求头、请求体以及最终的响应写成一个 JSON 文件，
+// 文件写入交给阻塞线程池完成，不占用 actix worker
+
+/// 单个请求体/响应体默认最多记录 64KB
+const DEFAULT_MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// 默认脱敏的请求头/响应头
+const DEFAULT_REDACT_HEADERS: [&str; 4] = [
+  "authorization",
+  "proxy-authorization",
+  "cookie",
+  "set-cookie",
+];
+
+const REDACTED: &str = "[REDACTED]";
+
+static RECORDER: LazyLock<RwLock<Option<Arc<Recorder>>>> = LazyLock::new(|| RwLock::new(None));
+
+/// 录制文件序号，保证同一毫秒内的文件名不冲突
+static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+pub struct Recorder {
+  dir: PathBuf,
+  max_body_bytes: usize,
+  redact_headers: Vec<String>,
+}
+
+/// 在请求交给 JavaScript 之前提取的请求信息
+pub struct RequestRecord {
+  method: String,
+  uri: String,
+  headers: Vec<(String, String)>,
+  body: Option<Bytes>,
+}
+
+impl RequestRecord {
+  pub fn set_body(&mut self, body: &Bytes) {
+    self.body = Some(body.clone());
+  }
+}
+
+/// 获取当前生效的录制器，未开启录制时返回 None
+#[inline]
+pub fn active_recorder() -> Option<Arc<Recorder>> {
+  RECORDER.read().clone()
+}
+
+impl Recorder {
+  pub fn capture_request(&self, req: &HttpRequest) -> RequestRecord {
+    RequestRecord {
+      method: req.method().to_string(),
+      uri: req.uri().to_string(),
+      headers: self.collect_headers(req.headers()),
+      body: None,
+    }
+  }
+
+  /// 记录一次请求/响应，返回内容不变的响应
+  pub fn record(&self, request: RequestRecord, response: HttpResponse) -> HttpResponse {
+    let status = response.status().as_u16();
+    let response_headers = self.collect_headers(response.headers());
+
+    // 定长响应体可以直接取出字节，记录后再原样放回
+    let (response, body) = response.into_parts();
+    let (response_body, body) = match body.try_into_bytes() {
+      Ok(bytes) => (Some(bytes.clone()), BoxBody::new(bytes)),
+      Err(body) => (None, body),
+    };
+    let response = response.set_body(body);
+
+    let record = serde_json::json!({
+      "timestamp": unix_millis(),
+      "request": {
+        "method": request.method,
+        "uri": request.uri,
+        "headers": headers_to_json(request.headers),
+        "body": self.body_to_json(request.body.as_ref()),
+      },
+      "response": {
+        "status": status,
+        "headers": headers_to_json(response_headers),
+        "body": self.body_to_json(response_body.as_ref()),
+      },
+    });
+
+    let path = self.dir.join(format!(
+      "{}-{}-{}.json",
+      unix_millis(),
+      SEQUENCE.fetch_add(1, Ordering::Relaxed),
+      request.method
+    ));
+
+    tokio::task::spawn_blocking(move || {
+      if let Err(e) = std::fs::write(&path, record.to_string()) {
+        eprintln!("警告：写入录制文件 {} 失败: {}", path.display(), e);
+      }
+    });
+
+    response
+  }
+
+  fn collect_headers(&self, headers: &actix_web::http::header::HeaderMap) -> Vec<(String, String)> {
+    headers
+      .iter()
+      .map(|(name, value)| {
+        let value = if self.redact_headers.iter().any(|h| h == name.as_str()) {
+          REDACTED.to_string()
+        } else {
+          String::from_utf8_lossy(value.as_bytes()).to_string()
+        };
+        (name.to_string(), value)
+      })
+      .collect()
+  }
+
+  fn body_to_json(&self, body: Option<&Bytes>) -> serde_json::Value {
+    match body {
+      Some(body) => {
+        let end = body.len().min(self.max_body_bytes);
+        serde_json::json!({
+          "size": body.len(),
+          "truncated": body.len() > self.max_body_bytes,
+          "text": String::from_utf8_lossy(&body[..end]),
+        })
+      }
+      None => serde_json::Value::Null,
+    }
+  }
+}
+
+fn headers_to_json(headers: Vec<(String, String)>) -> serde_json::Value {
+  serde_json::Value::Array(
+    headers
+      .into_iter()
+      .map(|(name, value)| serde_json::json!([name, value]))
+      .collect(),
+  )
+}
+
+fn unix_millis() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|elapsed| elapsed.as_millis() as u64)
+    .unwrap_or_default()
+}
+
+#[napi(object)]
+/// 录制选项
+pub struct RecordOptions {
+  /// 请求体/响应体最多记录的字节数，默认 65536
+  pub max_body_bytes: Option<u32>,
+  /// 需要脱敏的头部名称，默认 authorization、proxy-authorization、cookie、set-cookie
+  pub redact_headers: Option<Vec<String>>,
+}
+
+#[napi]
+/// 开始录制请求/响应到指定目录，每个请求生成一个 JSON 文件；可以在运行时随时开启或关闭
+pub fn record_to_dir(path: String, options: Option<RecordOptions>) -> Result<()> {
+  let dir = PathBuf::from(path);
+  std::fs::create_dir_all(&dir)
+    .map_err(|e| napi::Error::from_reason(format!("无法创建录制目录 {}: {}", dir.display(), e)))?;
+
+  let options = options.unwrap_or(RecordOptions {
+    max_body_bytes: None,
+    redact_headers: None,
+  });
+  let redact_headers = match options.redact_headers {
+    Some(headers) => headers.iter().map(|h| h.to_ascii_lowercase()).collect(),
+    None => DEFAULT_REDACT_HEADERS
+      .iter()
+      .map(|h| h.to_string())
+      .collect(),
+  };
+
+  *RECORDER.write() = Some(Arc::new(Recorder {
+    dir,
+    max_body_bytes: options
+      .max_body_bytes
+      .map(|size| size as usize)
+      .unwrap_or(DEFAULT_MAX_BODY_BYTES),
+    redact_headers,
+  }));
+
+  Ok(())
+}
+
+#[napi]
+/// 停止录制
+pub fn stop_recording() {
+  *RECORDER.write() = None;
+}
@@ -1,7 +1,23 @@
+use crate::app_state::app_state;
+use crate::csrf;
+use crate::in_flight::InFlightCallback;
 use crate::json_optimizer::{
-  estimate_json_complexity, parse_json_from_bytes, serialize_json_compact, simd_to_serde_value,
+  parse_json_body, parse_json_lossless, serialize_json_compact, serialize_json_value,
+  try_parse_json_body, JsonArrayElements,
 };
-use crate::response::{InnerResp, JsResponse};
+use crate::multipart_stream::{MultipartPartInfo, MultipartStream};
+use crate::proxy::{forward, upstream_response_timeout, ForwardRequest, ProxyError};
+use crate::response::{
+  error_json_body, parse_error_status_code, parse_status_code, resolve_byte_range, InnerResp,
+  JsResponse, RangeNotSatisfiable,
+};
+use crate::response_cache::get_cached_response;
+use crate::sse;
+use crate::timing::DispatchTiming;
+use crate::uploads::{encode_base64, multipart_in_memory, upload_dir};
+use crate::webhook;
+use actix_web::http::header::{HeaderName, HeaderValue, HttpDate};
+use actix_web::http::Version;
 use actix_web::HttpRequest;
 use bytes::Bytes;
 use napi::bindgen_prelude::*;
@@ -10,11 +26,559 @@ use std::borrow::Cow;
 use std::cell::OnceCell;
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
-use std::sync::LazyLock;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::oneshot;
 use uuid::Uuid;
 
+/// 字段规格中支持的 JSON 类型名称
+const JSON_FIELD_TYPES: [&str; 5] = ["string", "number", "boolean", "object", "array"];
+
+/// 判断 JSON 值是否符合规格中的类型名称，未知的类型名称视为不匹配
+#[inline]
+fn json_type_matches(value: &serde_json::Value, expected: &str) -> bool {
+  match expected {
+    "string" => value.is_string(),
+    "number" => value.is_number(),
+    "boolean" => value.is_boolean(),
+    "object" => value.is_object(),
+    "array" => value.is_array(),
+    _ => false,
+  }
+}
+
+/// 按规格检查 JSON 对象，返回缺失或类型不匹配的字段名（按名称排序）
+/// 请求体不是 JSON 对象时，规格中的所有字段都视为缺失
+fn find_invalid_fields(
+  json: Option<&serde_json::Value>,
+  spec: &HashMap<String, String>,
+) -> Vec<String> {
+  let object = json.and_then(|value| value.as_object());
+  let mut invalid: Vec<String> = spec
+    .iter()
+    .filter(|(field, expected)| {
+      !object
+        .and_then(|map| map.get(field.as_str()))
+        .is_some_and(|value| json_type_matches(value, expected))
+    })
+    .map(|(field, _)| field.clone())
+    .collect();
+  invalid.sort_unstable();
+  invalid
+}
+
+/// getQueryTyped 支持的参数类型
+const QUERY_PARAM_TYPES: [&str; 5] = ["int", "float", "bool", "string", "csv"];
+
+/// 按类型转换单个查询参数，无法转换时返回 None
+fn coerce_query_param(raw: &str, kind: &str) -> Option<serde_json::Value> {
+  let raw = raw.trim();
+  match kind {
+    "int" => raw.parse::<i64>().ok().map(serde_json::Value::from),
+    "float" => raw
+      .parse::<f64>()
+      .ok()
+      .and_then(serde_json::Number::from_f64)
+      .map(serde_json::Value::Number),
+    "bool" => match raw.to_ascii_lowercase().as_str() {
+      "true" | "1" | "yes" | "on" => Some(serde_json::Value::Bool(true)),
+      "false" | "0" | "no" | "off" => Some(serde_json::Value::Bool(false)),
+      _ => None,
+    },
+    "string" => Some(serde_json::Value::String(raw.to_string())),
+    "csv" => Some(serde_json::Value::Array(
+      raw
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| serde_json::Value::String(item.to_string()))
+        .collect(),
+    )),
+    _ => None,
+  }
+}
+
+/// 按规格转换查询参数：缺失的参数使用默认值，转换失败的参数使用默认值并记录参数名（按名称排序）
+fn typed_query_values(
+  params: &HashMap<String, String>,
+  spec: &HashMap<String, QueryParamSpec>,
+) -> (serde_json::Map<String, serde_json::Value>, Vec<String>) {
+  let mut values = serde_json::Map::with_capacity(spec.len());
+  let mut errors = Vec::new();
+
+  for (name, param) in spec {
+    let default = || param.default.clone().unwrap_or(serde_json::Value::Null);
+    let value = match params.get(name) {
+      Some(raw) => coerce_query_param(raw, &param.r#type).unwrap_or_else(|| {
+        errors.push(name.clone());
+        default()
+      }),
+      None => default(),
+    };
+    values.insert(name.clone(), value);
+  }
+
+  errors.sort_unstable();
+  (values, errors)
+}
+
+/// 当前 Unix 时间戳（秒）
+fn unix_now() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|elapsed| elapsed.as_secs())
+    .unwrap_or_default()
+}
+
+/// 解码查询字符串中的键或值：+ 视为空格，%XX 按字节解码后作为 UTF-8 解析，无效的转义保持原样
+fn decode_query_component(component: &str) -> String {
+  let bytes = component.as_bytes();
+  let mut decoded = Vec::with_capacity(bytes.len());
+  let mut i = 0;
+  while i < bytes.len() {
+    match bytes[i] {
+      b'+' => decoded.push(b' '),
+      b'%' => {
+        let hex = |offset: usize| {
+          bytes
+            .get(i + offset)
+            .and_then(|b| (*b as char).to_digit(16))
+        };
+        if let (Some(high), Some(low)) = (hex(1), hex(2)) {
+          decoded.push((high * 16 + low) as u8);
+          i += 3;
+          continue;
+        }
+        decoded.push(b'%');
+      }
+      byte => decoded.push(byte),
+    }
+    i += 1;
+  }
+  String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// 从 multipart 部分头部的 Content-Disposition 中提取文件名
+/// 同时出现时优先使用 RFC 5987 扩展形式 filename*=charset'lang'百分号编码，浏览器用它传递非 ASCII 文件名；
+/// 扩展形式的字符集不受支持或解码失败时回退到 filename="..."
+fn extract_disposition_filename(headers: &str) -> Option<String> {
+  let line = headers
+    .lines()
+    .find(|line| line.to_lowercase().starts_with("content-disposition:"))?;
+  // ASCII 小写不改变字节长度，下标可以直接用于原始行
+  let lower = line.to_ascii_lowercase();
+
+  if let Some(start) = lower.find("filename*=") {
+    let value = &line[start + 10..];
+    let value = value[..value.find(';').unwrap_or(value.len())]
+      .trim()
+      .trim_matches('"');
+    if let Some(filename) = decode_ext_value(value) {
+      return Some(filename);
+    }
+  }
+
+  let start = line.find("filename=\"")?;
+  let filename_part = &line[start + 10..];
+  let end = filename_part.find('"')?;
+  Some(filename_part[..end].to_string())
+}
+
+/// multipart 文件字段的值：内存模式下以 base64 放在 data 中，否则保存到上传目录；
+/// 保存失败时返回 { type: "error", originalName, error } 而不是丢弃该字段
+fn file_field_value(
+  original_name: String,
+  content_type: Option<String>,
+  content: &[u8],
+) -> Option<serde_json::Value> {
+  let saved = if multipart_in_memory() {
+    Ok(FileInfo {
+      r#type: "file".to_string(),
+      filename: original_name.clone(),
+      original_name: original_name.clone(),
+      path: None,
+      content_type,
+      size: content.len() as u32,
+      data: Some(encode_base64(content)),
+    })
+  } else {
+    save_uploaded_file(&upload_dir(), original_name.clone(), content_type, content)
+  };
+  match saved {
+    Ok(file_info) => serde_json::to_value(&file_info).ok(),
+    Err(error) => Some(serde_json::json!({
+      "type": "error",
+      "originalName": original_name,
+      "error": error,
+    })),
+  }
+}
+
+/// 以唯一文件名把上传的文件保存到 upload_dir 并返回文件信息，失败时返回错误信息
+fn save_uploaded_file(
+  upload_dir: &Path,
+  original_filename: String,
+  content_type: Option<String>,
+  content: &[u8],
+) -> std::result::Result<FileInfo, String> {
+  // 确保上传目录存在
+  if let Err(e) = fs::create_dir_all(upload_dir) {
+    return Err(format!("创建上传目录 {} 失败: {}", upload_dir.display(), e));
+  }
+
+  // 生成唯一文件名，保留原始扩展名
+  let file_extension = Path::new(&original_filename)
+    .extension()
+    .and_then(|ext| ext.to_str())
+    .unwrap_or("");
+
+  let unique_filename = if file_extension.is_empty() {
+    format!("{}", Uuid::new_v4())
+  } else {
+    format!("{}.{}", Uuid::new_v4(), file_extension)
+  };
+
+  let file_path = upload_dir.join(&unique_filename);
+
+  // 保存文件
+  if let Err(e) = fs::write(&file_path, content) {
+    return Err(format!("保存文件 {} 失败: {}", file_path.display(), e));
+  }
+
+  Ok(FileInfo {
+    r#type: "file".to_string(),
+    original_name: original_filename,
+    filename: unique_filename,
+    path: Some(file_path.to_string_lossy().into_owned()),
+    content_type,
+    size: content.len() as u32,
+    data: None,
+  })
+}
+
+/// 解码 RFC 5987 扩展参数值 charset'lang'百分号编码，支持 UTF-8 和 ISO-8859-1
+fn decode_ext_value(value: &str) -> Option<String> {
+  let mut parts = value.splitn(3, '\'');
+  let charset = parts.next()?;
+  let _language = parts.next()?;
+  let encoded = parts.next()?.as_bytes();
+
+  let mut bytes = Vec::with_capacity(encoded.len());
+  let mut i = 0;
+  while i < encoded.len() {
+    if encoded[i] == b'%' {
+      let hex = std::str::from_utf8(encoded.get(i + 1..i + 3)?).ok()?;
+      bytes.push(u8::from_str_radix(hex, 16).ok()?);
+      i += 3;
+    } else {
+      bytes.push(encoded[i]);
+      i += 1;
+    }
+  }
+
+  if charset.eq_ignore_ascii_case("utf-8") {
+    String::from_utf8(bytes).ok()
+  } else if charset.eq_ignore_ascii_case("iso-8859-1") {
+    Some(bytes.into_iter().map(char::from).collect())
+  } else {
+    None
+  }
+}
+
+/// 逐对解析查询字符串，保留重复的键：?tag=a&tag=b 得到 tag -> ["a", "b"]，值按出现顺序排列
+/// 没有 '=' 的键（例如 ?flag）对应空字符串
+fn parse_query_multi(query_string: &str) -> HashMap<String, Vec<String>> {
+  let mut params: HashMap<String, Vec<String>> = HashMap::new();
+  for (key, value) in query_pairs(query_string) {
+    params.entry(key).or_default().push(value);
+  }
+  params
+}
+
+fn query_pairs(query_string: &str) -> impl Iterator<Item = (String, String)> + '_ {
+  query_string
+    .split('&')
+    .filter(|pair| !pair.is_empty())
+    .map(|pair| {
+      let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+      (decode_query_component(key), decode_query_component(value))
+    })
+}
+
+/// 解析 Cookie 请求头 "a=1; b=\"quoted value\""，去掉值两侧的双引号
+/// 浏览器按路径从具体到宽泛的顺序发送同名 Cookie，这里保留第一个
+fn parse_cookie_header(header: &str) -> HashMap<String, String> {
+  let mut cookies = HashMap::new();
+  for pair in header.split(';') {
+    let Some((name, value)) = pair.split_once('=') else {
+      continue;
+    };
+    let name = name.trim();
+    if name.is_empty() {
+      continue;
+    }
+    let value = value.trim();
+    let value = value
+      .strip_prefix('"')
+      .and_then(|value| value.strip_suffix('"'))
+      .unwrap_or(value);
+    cookies
+      .entry(name.to_string())
+      .or_insert_with(|| value.to_string());
+  }
+  cookies
+}
+
+/// 按 RFC 6265 生成 Set-Cookie 头的值，名称或值包含非法字符时返回错误
+fn format_set_cookie(
+  name: &str,
+  value: &str,
+  options: &CookieOptions,
+) -> std::result::Result<String, String> {
+  let is_token = |c: char| c.is_ascii_graphic() && !"()<>@,;:\\\"/[]?={}".contains(c);
+  if name.is_empty() || !name.chars().all(is_token) {
+    return Err(format!("无效的 Cookie 名称：{}", name));
+  }
+  if !value
+    .chars()
+    .all(|c| c.is_ascii_graphic() && !matches!(c, '"' | ',' | ';' | '\\'))
+  {
+    return Err(format!("Cookie {} 的值包含非法字符，请先进行编码", name));
+  }
+
+  let mut cookie = format!("{}={}", name, value);
+  if let Some(max_age) = options.max_age {
+    cookie.push_str(&format!("; Max-Age={}", max_age.max(0)));
+  }
+  for (attribute, value) in [("Domain", &options.domain), ("Path", &options.path)] {
+    if let Some(value) = value {
+      if value.contains([';', '\r', '\n']) {
+        return Err(format!("Cookie 的 {} 属性包含非法字符", attribute));
+      }
+      cookie.push_str(&format!("; {}={}", attribute, value));
+    }
+  }
+  if options.secure == Some(true) {
+    cookie.push_str("; Secure");
+  }
+  if options.http_only == Some(true) {
+    cookie.push_str("; HttpOnly");
+  }
+  if let Some(same_site) = &options.same_site {
+    let same_site = match same_site.to_ascii_lowercase().as_str() {
+      "strict" => "Strict",
+      "lax" => "Lax",
+      // 浏览器会拒绝没有 Secure 的 SameSite=None
+      "none" if options.secure == Some(true) => "None",
+      "none" => return Err("SameSite=None 必须同时设置 secure".to_string()),
+      _ => return Err(format!("无效的 SameSite 值：{}", same_site)),
+    };
+    cookie.push_str("; SameSite=");
+    cookie.push_str(same_site);
+  }
+  Ok(cookie)
+}
+
+/// 校验响应头名称和值：名称必须是合法的 token，两者都不能包含换行等控制字符，避免响应头注入
+fn validate_response_header(name: &str, value: &str) -> Result<()> {
+  if HeaderName::from_bytes(name.as_bytes()).is_err() {
+    return Err(napi::Error::from_reason(format!(
+      "无效的响应头名称 {:?}",
+      name
+    )));
+  }
+  // HeaderValue::from_str 只接受可见 ASCII、空格和制表符
+  if HeaderValue::from_str(value).is_err() {
+    return Err(napi::Error::from_reason(format!(
+      "响应头 {} 的值无效",
+      name
+    )));
+  }
+  Ok(())
+}
+
+/// 解析 Content-Length 请求头的值，只接受非负十进制整数
+fn parse_content_length(value: Option<&str>) -> Option<i64> {
+  value?
+    .trim()
+    .parse::<u64>()
+    .ok()
+    .and_then(|len| i64::try_from(len).ok())
+}
+
+/// 把请求体复制为 Buffer，没有请求体时返回空 Buffer
+fn body_to_buffer(body: Option<&Bytes>) -> Buffer {
+  body.cloned().map(Vec::from).unwrap_or_default().into()
+}
+
+/// multipart 请求体中的一个部分
+struct MultipartSection<'a> {
+  /// 部分头部（Content-Disposition 等）
+  headers: Cow<'a, str>,
+  /// 部分内容的原始字节，不做任何解码
+  content: &'a [u8],
+}
+
+/// 在字节序列中查找子序列的位置
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+  if needle.is_empty() {
+    return Some(0);
+  }
+  haystack
+    .windows(needle.len())
+    .position(|window| window == needle)
+}
+
+/// 按 boundary 拆分 multipart 请求体
+/// 分隔符和头部的查找都在原始字节上进行，文件等二进制内容保持原样
+fn split_multipart<'a>(body: &'a [u8], boundary: &'a str) -> Vec<MultipartSection<'a>> {
+  // 优先使用请求体第一行中的 boundary，传入的 Content-Type 可能已被转换为小写
+  let boundary = body
+    .strip_prefix(b"--")
+    .and_then(|rest| {
+      let line = &rest[..find_bytes(rest, b"\n")?];
+      Some(line.strip_suffix(b"\r").unwrap_or(line))
+    })
+    .unwrap_or(boundary.as_bytes());
+  let delimiter = [b"--".as_slice(), boundary].concat();
+
+  let mut sections = Vec::new();
+  let Some(start) = find_bytes(body, &delimiter) else {
+    return sections;
+  };
+  let mut rest = &body[start + delimiter.len()..];
+  loop {
+    let (part, next) = match find_bytes(rest, &delimiter) {
+      Some(end) => (&rest[..end], Some(&rest[end + delimiter.len()..])),
+      None => (rest, None),
+    };
+
+    // 跳过空白部分和结束分隔符 "--boundary--" 的剩余部分
+    if !part.starts_with(b"--") && !part.trim_ascii().is_empty() {
+      let header_end = find_bytes(part, b"\r\n\r\n")
+        .map(|end| (end, 4))
+        .or_else(|| find_bytes(part, b"\n\n").map(|end| (end, 2)));
+      if let Some((header_end, separator_len)) = header_end {
+        let content = &part[header_end + separator_len..];
+        // 分隔符之前的换行属于分隔符本身，只去掉一个，内容末尾的换行保持不变
+        let content = content
+          .strip_suffix(b"\r\n")
+          .or_else(|| content.strip_suffix(b"\n"))
+          .unwrap_or(content);
+        sections.push(MultipartSection {
+          headers: String::from_utf8_lossy(&part[..header_end]),
+          content,
+        });
+      }
+    }
+
+    match next {
+      Some(next) => rest = next,
+      None => return sections,
+    }
+  }
+}
+
+/// 转义 HTML 特殊字符
+fn escape_html(text: &str, out: &mut String) {
+  for c in text.chars() {
+    match c {
+      '&' => out.push_str("&amp;"),
+      '<' => out.push_str("&lt;"),
+      '>' => out.push_str("&gt;"),
+      '"' => out.push_str("&quot;"),
+      '\'' => out.push_str("&#x27;"),
+      c => out.push(c),
+    }
+  }
+}
+
+/// 替换模板中的 {{key}}（HTML 转义）和 {{{key}}}（原样输出）占位符
+/// 字符串值原样使用，null 和缺失的键替换为空字符串，其他值使用 JSON 表示；未闭合的占位符保留原文
+fn render_template(template: &str, values: &HashMap<String, serde_json::Value>) -> String {
+  let mut out = String::with_capacity(template.len());
+  let mut rest = template;
+
+  while let Some(start) = rest.find("{{") {
+    out.push_str(&rest[..start]);
+    let after = &rest[start..];
+    let (raw, open, close) = if after.starts_with("{{{") {
+      (true, 3, "}}}")
+    } else {
+      (false, 2, "}}")
+    };
+    let Some(end) = after[open..].find(close) else {
+      rest = after;
+      break;
+    };
+
+    let key = after[open..open + end].trim();
+    let value = match values.get(key) {
+      Some(serde_json::Value::String(value)) => Cow::Borrowed(value.as_str()),
+      None | Some(serde_json::Value::Null) => Cow::Borrowed(""),
+      Some(value) => Cow::Owned(value.to_string()),
+    };
+    if raw {
+      out.push_str(&value);
+    } else {
+      escape_html(&value, &mut out);
+    }
+    rest = &after[open + end + close.len()..];
+  }
+
+  out.push_str(rest);
+  out
+}
+
+/// 按 gRPC 规范对 grpc-message 做百分号编码：可打印 ASCII（'%' 除外）保持原样，其余字节编码为 %XX
+fn encode_grpc_message(message: &str) -> String {
+  let mut encoded = String::with_capacity(message.len());
+  for byte in message.bytes() {
+    match byte {
+      0x20..=0x7E if byte != b'%' => encoded.push(byte as char),
+      _ => encoded.push_str(&format!("%{:02X}", byte)),
+    }
+  }
+  encoded
+}
+
+/// If-Match 条件是否成立：`*` 匹配任意现存资源，否则要求列表中存在与当前 ETag 强匹配的值
+/// 弱 ETag（W/ 前缀）按 RFC 7232 不参与 If-Match 的强比较
+fn if_match_satisfied(header: &str, current_etag: &str) -> bool {
+  let header = header.trim();
+  if header == "*" {
+    return true;
+  }
+  if current_etag.starts_with("W/") {
+    return false;
+  }
+
+  let current = current_etag.trim_matches('"');
+  header
+    .split(',')
+    .map(str::trim)
+    .filter(|tag| !tag.starts_with("W/"))
+    .any(|tag| tag.trim_matches('"') == current)
+}
+
+/// If-Unmodified-Since 条件是否成立：资源最后修改时间不晚于头部给出的时间（精确到秒）
+/// 无法解析的日期按 RFC 7232 忽略
+fn if_unmodified_since_satisfied(header: &str, last_modified: SystemTime) -> bool {
+  let Ok(since) = header.trim().parse::<HttpDate>() else {
+    return true;
+  };
+  let to_secs = |time: SystemTime| {
+    time
+      .duration_since(UNIX_EPOCH)
+      .map(|elapsed| elapsed.as_secs())
+      .unwrap_or_default()
+  };
+
+  to_secs(last_modified) <= to_secs(SystemTime::from(since))
+}
+
 // 字符串常量池优化 - HTTP 方法池
 static HTTP_METHODS: LazyLock<HashMap<&'static str, &'static str>> = LazyLock::new(|| {
   let mut map = HashMap::new();
@@ -82,6 +646,41 @@ fn intern_string(s: String) -> Cow<'static, str> {
   Cow::Owned(s)
 }
 
+#[napi(object)]
+/// 单个查询参数的规格
+pub struct QueryParamSpec {
+  /// 参数类型：int / float / bool / string / csv（逗号分隔的字符串数组）
+  pub r#type: String,
+  /// 参数缺失或转换失败时使用的默认值，未设置时为 null
+  pub default: Option<serde_json::Value>,
+}
+
+#[napi(object)]
+#[derive(Default)]
+/// setCookieAsync 的 Cookie 属性
+pub struct CookieOptions {
+  /// 有效期（秒），0 或负数表示立即过期（删除 Cookie）；未设置时为会话 Cookie
+  pub max_age: Option<i64>,
+  pub path: Option<String>,
+  pub domain: Option<String>,
+  /// 禁止 JavaScript 通过 document.cookie 读取
+  pub http_only: Option<bool>,
+  /// 只通过 HTTPS 发送
+  pub secure: Option<bool>,
+  /// Strict / Lax / None（不区分大小写），None 必须同时设置 secure
+  pub same_site: Option<String>,
+}
+
+#[napi(object)]
+/// getQueryTyped 的返回值
+pub struct TypedQuery {
+  /// 转换后的参数对象
+  #[napi(ts_type = "Record<string, any>")]
+  pub values: serde_json::Value,
+  /// 转换失败、已改用默认值的参数名
+  pub errors: Vec<String>,
+}
+
 #[napi(object)]
 #[derive(Debug, Clone, Serialize)]
 pub struct FileInfo {
@@ -90,11 +689,16 @@ pub struct FileInfo {
   #[serde(rename = "originalName")]
   pub original_name: String,
   pub filename: String,
-  pub path: String,
+  /// 保存路径，内存模式下不存在
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub path: Option<String>,
   #[napi(js_name = "contentType")]
   #[serde(rename = "contentType")]
   pub content_type: Option<String>,
   pub size: u32,
+  /// 内存模式（setMultipartInMemory）下 base64 编码的文件内容
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub data: Option<String>,
 }
 
 #[napi]
@@ -236,6 +840,21 @@ impl RequestWrapper {
       .clone()
   }
 
+  #[napi]
+  /// 获取查询参数，保留重复的键，例如 ?tag=a&tag=b 返回 { tag: ["a", "b"] }
+  pub fn get_query_params_multi(&self) -> HashMap<String, Vec<String>> {
+    parse_query_multi(self.request.query_string())
+  }
+
+  #[napi]
+  /// 获取查询参数 name 的所有值，按出现顺序排列，不存在时返回空数组
+  pub fn get_query_values(&self, name: String) -> Vec<String> {
+    query_pairs(self.request.query_string())
+      .filter(|(key, _)| *key == name)
+      .map(|(_, value)| value)
+      .collect()
+  }
+
   #[napi]
   /// 获取原始请求体字符串 - 零拷贝优化：直接使用 Bytes 的零拷贝特性
   pub fn get_body_string(&self) -> String {
@@ -250,6 +869,12 @@ impl RequestWrapper {
     self.body.as_ref()
   }
 
+  #[napi]
+  /// 获取原始请求体作为 Buffer，不做 UTF-8 解码，适用于 protobuf、图片、gzip 等二进制请求体；没有请求体时返回空 Buffer
+  pub fn get_body_buffer(&self) -> Buffer {
+    body_to_buffer(self.body.as_ref())
+  }
+
   #[napi]
   /// 检查请求体是否为空 - 零拷贝优化：直接检查，不解析内容
   pub fn has_body(&self) -> bool {
@@ -262,6 +887,20 @@ impl RequestWrapper {
     self.body.as_ref().map(|b| b.len() as u32).unwrap_or(0)
   }
 
+  #[napi]
+  /// 获取请求声明的 Content-Length，请求头缺失或无法解析时返回 null
+  /// 与 getBodySize 不同，它不依赖请求体是否已读取；分块传输的请求没有 Content-Length，两者可能不一致
+  pub fn get_content_length(&self) -> Option<i64> {
+    parse_content_length(
+      self
+        .request
+        .headers()
+        .get("content-length")
+        .and_then(|value| value.to_str().ok()),
+    )
+  }
+
+  #[napi]
   /// 检查是否为JSON请求 - 零拷贝优化：只检查Content-Type，不解析内容
   pub fn is_json_request(&self) -> bool {
     self
@@ -270,6 +909,7 @@ impl RequestWrapper {
       .unwrap_or(false)
   }
 
+  #[napi]
   /// 检查是否为表单请求 - 零拷贝优化：只检查Content-Type，不解析内容
   pub fn is_form_request(&self) -> bool {
     self
@@ -289,35 +929,8 @@ impl RequestWrapper {
     self
       .parsed_json
       .get_or_init(|| {
-        match &self.body {
-          Some(bytes) => {
-            // 🚀 智能选择解析策略：根据 JSON 复杂度选择最优解析器
-            let complexity = estimate_json_complexity(bytes);
-
-            if complexity > 10 {
-              // 对于复杂 JSON，使用 SIMD 优化解析
-              match parse_json_from_bytes(bytes) {
-                Ok(simd_value) => Some(simd_to_serde_value(simd_value)),
-                Err(_) => {
-                  // SIMD 解析失败，回退到标准解析
-                  if let Ok(body_str) = std::str::from_utf8(bytes) {
-                    serde_json::from_str(body_str).ok()
-                  } else {
-                    None
-                  }
-                }
-              }
-            } else {
-              // 对于简单 JSON，使用标准解析（避免 SIMD 开销）
-              if let Ok(body_str) = std::str::from_utf8(bytes) {
-                serde_json::from_str(body_str).ok()
-              } else {
-                None
-              }
-            }
-          }
-          None => None,
-        }
+        // 🚀 SIMD 优化解析
+        self.body.as_ref().and_then(parse_json_body)
       })
       .clone()
   }
@@ -391,56 +1004,22 @@ impl RequestWrapper {
     }
 
     let mut form_data: HashMap<String, serde_json::Value> = HashMap::new();
-    let body_str = String::from_utf8_lossy(bytes);
-
-    // 查找请求体中实际的 boundary（从第一行提取）
-    let actual_boundary = if body_str.starts_with("--") {
-      if let Some(first_line_end) = body_str.find("\r\n").or_else(|| body_str.find("\n")) {
-        &body_str[2..first_line_end] // 去掉开头的 "--"
-      } else {
-        boundary
-      }
-    } else {
-      boundary
-    };
-
-    let boundary_delimiter = format!("--{}", actual_boundary);
-
-    // 分割各个部分
-    let parts: Vec<&str> = body_str.split(&boundary_delimiter).collect();
-
-    for part in parts.iter().skip(1) {
-      if part.trim().is_empty() || part.starts_with("--") {
-        continue;
-      }
-
-      // 尝试不同的换行符格式
-      let header_end = part.find("\r\n\r\n").or_else(|| part.find("\n\n"));
-
-      if let Some(header_end) = header_end {
-        let headers = &part[..header_end];
-        let content_start = if part[header_end..].starts_with("\r\n\r\n") {
-          header_end + 4
-        } else {
-          header_end + 2
-        };
-        let content = &part[content_start..]
-          .trim_end_matches("\r\n")
-          .trim_end_matches("\n");
-
-        // 解析 Content-Disposition 头
-        if let Some(name) = self.extract_form_field_name(headers) {
-          if headers.contains("filename=") {
-            // 处理文件字段，保存到本地并返回文件信息
-            if let Some(file_info) = self.save_uploaded_file(headers, content) {
-              if let Ok(file_value) = serde_json::to_value(&file_info) {
-                form_data.insert(name, file_value);
-              }
-            }
-          } else {
-            // 处理文本字段
-            form_data.insert(name, serde_json::Value::String(content.to_string()));
+    for section in split_multipart(bytes, boundary) {
+      // 解析 Content-Disposition 头
+      if let Some(name) = self.extract_form_field_name(&section.headers) {
+        // 带文件名（filename= 或 filename*=）的是文件字段，按原始字节保存到本地并返回文件信息；
+        // 保存失败时返回错误信息而不是丢弃该字段
+        if let Some(original_name) = self.extract_filename(&section.headers) {
+          let content_type = self.extract_content_type(&section.headers);
+          if let Some(file_value) = file_field_value(original_name, content_type, section.content) {
+            form_data.insert(name, file_value);
           }
+        } else {
+          // 处理文本字段
+          form_data.insert(
+            name,
+            serde_json::Value::String(String::from_utf8_lossy(section.content).into_owned()),
+          );
         }
       }
     }
@@ -464,17 +1043,7 @@ impl RequestWrapper {
 
   /// 从 Content-Disposition 头中提取文件名
   fn extract_filename(&self, headers: &str) -> Option<String> {
-    for line in headers.lines() {
-      if line.to_lowercase().starts_with("content-disposition:") {
-        if let Some(filename_start) = line.find("filename=\"") {
-          let filename_part = &line[filename_start + 10..];
-          if let Some(filename_end) = filename_part.find('"') {
-            return Some(filename_part[..filename_end].to_string());
-          }
-        }
-      }
-    }
-    None
+    extract_disposition_filename(headers)
   }
 
   /// 从头部中提取 Content-Type
@@ -487,55 +1056,6 @@ impl RequestWrapper {
     None
   }
 
-  /// 保存上传的文件到本地并返回文件信息
-  fn save_uploaded_file(&self, headers: &str, content: &str) -> Option<FileInfo> {
-    let original_filename = self.extract_filename(headers)?;
-    let content_type = self.extract_content_type(headers);
-    let file_size = content.len();
-
-    // 确保 static 目录存在
-    let static_dir = Path::new("static");
-    if !static_dir.exists() {
-      if let Err(e) = fs::create_dir_all(static_dir) {
-        eprintln!("创建 static 目录失败: {}", e);
-        return None;
-      }
-    }
-
-    // 生成唯一文件名，保留原始扩展名
-    let file_extension = Path::new(&original_filename)
-      .extension()
-      .and_then(|ext| ext.to_str())
-      .unwrap_or("");
-
-    let unique_filename = if file_extension.is_empty() {
-      format!("{}", Uuid::new_v4())
-    } else {
-      format!("{}.{}", Uuid::new_v4(), file_extension)
-    };
-
-    let file_path = static_dir.join(&unique_filename);
-    let relative_path = format!("static/{}", unique_filename);
-
-    // 保存文件
-    if let Err(e) = fs::write(&file_path, content.as_bytes()) {
-      eprintln!("保存文件失败: {}", e);
-      return None;
-    }
-
-    // 返回文件信息对象
-    let file_info = FileInfo {
-      r#type: "file".to_string(),
-      original_name: original_filename,
-      filename: unique_filename,
-      path: relative_path,
-      content_type,
-      size: file_size as u32,
-    };
-
-    Some(file_info)
-  }
-
   #[napi]
   /// 获取表单数据中指定键的值 - 零拷贝优化：使用缓存的表单数据
   pub fn get_form_value(&self, key: String) -> Option<serde_json::Value> {
@@ -621,6 +1141,15 @@ impl RequestWrapper {
     }
   }
 
+  #[napi]
+  /// 在 Rust 侧用 serde_json 序列化并发送 JSON 响应，与 sendObject 的结果相同
+  /// 名称沿用最初的 simd-json 实现；实测 simd-json 序列化 serde_json::Value 约慢一倍，因此实际不使用 SIMD
+  pub fn send_json_simd(&mut self, value: serde_json::Value) -> Result<()> {
+    let json_string = serialize_json_value(&value)
+      .map_err(|e| napi::Error::from_reason(format!("JSON序列化失败: {}", e)))?;
+    self.send_response(InnerResp::Json(json_string))
+  }
+
   #[napi]
   /// 发送空响应
   pub fn send_empty(&mut self) -> Result<()> {
@@ -637,13 +1166,33 @@ impl RequestWrapper {
   }
 
   #[napi]
-  /// 设置响应状态码
+  /// 以指定的 4xx / 5xx 状态码发送统一格式的 JSON 错误响应 {"error": message, "code": code, "details": details}
+  /// 没有 details 时省略该字段；状态码不是 4xx / 5xx 时抛出错误
+  pub fn send_error_json(
+    &mut self,
+    status: u16,
+    code: String,
+    message: String,
+    details: Option<serde_json::Value>,
+  ) -> Result<()> {
+    let status = parse_error_status_code(status)
+      .ok_or_else(|| napi::Error::from_reason(format!("无效的错误状态码 {}", status)))?;
+    if self.sent {
+      return Err(napi::Error::from_reason("响应已经发送"));
+    }
+
+    self.status_code = Some(status.as_u16());
+    self.send_response(InnerResp::Json(error_json_body(code, message, details)))
+  }
+
+  #[napi]
+  /// 设置响应状态码；超出 100-599 的状态码（例如 600）不会被设置，返回 false
   pub fn set_status_code(&mut self, status: u16) -> bool {
     if self.sent {
       return false;
     }
 
-    if !(100..1000).contains(&status) {
+    if parse_status_code(status).is_none() {
       return false;
     }
 
@@ -652,14 +1201,59 @@ impl RequestWrapper {
   }
 
   #[napi]
-  /// 添加响应头
-  pub fn add_header(&mut self, key: String, value: String) {
+  /// 添加响应头；名称或值不合法（包含空格、换行等字符）时抛出错误
+  pub fn add_header(&mut self, key: String, value: String) -> Result<()> {
+    validate_response_header(&key, &value)?;
     if !self.sent {
       self.headers.push((key, value));
     }
+    Ok(())
   }
 }
 
+#[napi(object)]
+/// 请求元数据快照，一次 FFI 调用取回处理函数常用的全部信息
+pub struct RequestInfo {
+  pub path: String,
+  pub method: String,
+  pub query_string: String,
+  pub query_params: HashMap<String, String>,
+  pub headers: HashMap<String, String>,
+  pub path_params: HashMap<String, String>,
+  pub body_size: u32,
+  pub content_type: Option<String>,
+}
+
+#[napi(object)]
+/// getBodyJsonResult 的结果：value 和 error 都为 null 表示没有请求体
+pub struct JsonBodyResult {
+  /// 解析得到的 JSON 值
+  #[napi(ts_type = "any")]
+  pub value: Option<serde_json::Value>,
+  /// 解析失败的原因，例如 "expected value at line 3 column 8"
+  pub error: Option<String>,
+}
+
+#[napi(object)]
+/// getBodyJson 的解析选项
+pub struct JsonBodyOptions {
+  /// 为 true 时超出 ±(2^53 - 1) 的整数以十进制字符串返回，保证 64 位 ID 不丢失精度
+  /// 此模式使用 serde_json 标准解析器而不是 SIMD 解析器，且每次调用都会重新解析；
+  /// 超出 64 位范围的整数和高精度浮点数仍会丢失精度
+  pub big_int_as_string: Option<bool>,
+}
+
+#[napi(object)]
+/// 字节区间，start 和 end 都包含在内
+pub struct ByteRange {
+  pub start: i64,
+  pub end: i64,
+}
+
+/// 响应发送器，由包装器和分发线程共同持有：
+/// 处理函数同步抛出异常时，包装器仍被 JavaScript 持有（直到被垃圾回收），分发线程通过它立即返回 500
+pub type SharedResponseSender = Arc<parking_lot::Mutex<Option<oneshot::Sender<JsResponse>>>>;
+
 #[napi]
 #[derive(Serialize)]
 pub struct DetachedRequestWrapper {
@@ -673,26 +1267,64 @@ pub struct DetachedRequestWrapper {
   #[serde(skip)]
   uri: Cow<'static, str>,
   #[serde(skip)]
+  version: Version,
+  #[serde(skip)]
   headers: HashMap<String, String>,
+  // 所有请求头（名称, 值），保留重复的请求头
+  #[serde(skip)]
+  header_pairs: Vec<(String, String)>,
+  // 请求被提取为包装器的时刻，单调时钟，不受系统时间调整影响
+  #[serde(skip)]
+  detached_at: Instant,
   #[serde(skip)]
   body: Option<Bytes>,
   #[serde(skip)]
   path_params: HashMap<String, String>,
   #[serde(skip)]
-  response_sender: Option<oneshot::Sender<JsResponse>>,
+  remote_addr: Option<IpAddr>,
+  #[serde(skip)]
+  max_response_body_size: Option<usize>,
+  // 来自 ServerOptions.requestTimeoutMs，为 None 时不限制
+  #[serde(skip)]
+  request_timeout: Option<Duration>,
+  #[serde(skip)]
+  csrf_secret: Option<Arc<[u8]>>,
+  // 匹配到的路由中通配参数（`*name`）的名称
+  #[serde(skip)]
+  wildcard_param: Option<String>,
+  #[serde(skip)]
+  dispatch_timing: Option<Arc<DispatchTiming>>,
+  #[serde(skip)]
+  in_flight: Option<InFlightCallback>,
+  #[serde(skip)]
+  response_sender: Option<SharedResponseSender>,
   #[serde(skip)]
   sent: bool,
   #[serde(skip)]
   status_code: Option<u16>,
   #[serde(skip)]
   response_headers: Vec<(String, String)>,
-  // 预计算缓存字段 - 零拷贝优化：在创建时就解析好，避免运行时原子操作
+  // before 钩子调用 next() 时通知分发线程继续执行处理函数，仅在 before 钩子的包装器上存在
+  #[serde(skip)]
+  next_sender: Option<oneshot::Sender<()>>,
+  // 最终响应状态码，仅在 after 钩子的包装器上存在
+  #[serde(skip)]
+  response_status: Option<u16>,
+  // 响应就绪时冻结的耗时，仅在 after 钩子的包装器上存在
+  #[serde(skip)]
+  response_elapsed: Option<Duration>,
+  // 延迟解析缓存字段：第一次调用对应的 getter 时才解析，只用到路径的处理函数不需要付出解析开销
+  #[serde(skip)]
+  cached_query_params: OnceCell<Option<HashMap<String, String>>>,
+  #[serde(skip)]
+  cached_json: OnceCell<Option<serde_json::Value>>,
   #[serde(skip)]
-  cached_query_params: Option<HashMap<String, String>>,
+  cached_form_data: OnceCell<Option<serde_json::Value>>,
   #[serde(skip)]
-  cached_json: Option<serde_json::Value>,
+  cached_cookies: OnceCell<HashMap<String, String>>,
+  // 流式 multipart 状态，仅在流式路由上存在
   #[serde(skip)]
-  cached_form_data: Option<serde_json::Value>,
+  multipart: Option<MultipartStream>,
 }
 
 impl DetachedRequestWrapper {
@@ -711,10 +1343,10 @@ impl DetachedRequestWrapper {
         params.insert(key, value);
       }
     } else {
-      // 如果 serde_qs 解析失败，回退到简单解析
+      // 如果 serde_qs 解析失败（例如 a[]=1 这类嵌套写法），回退到逐对解析，解码方式与 serde_qs 一致
       for pair in query_string.split('&') {
         if let Some((key, value)) = pair.split_once('=') {
-          params.insert(key.to_string(), value.to_string());
+          params.insert(decode_query_component(key), decode_query_component(value));
         }
       }
     }
@@ -728,16 +1360,6 @@ impl DetachedRequestWrapper {
       .unwrap_or(false)
   }
 
-  fn parse_json_static(body: &Bytes) -> Option<serde_json::Value> {
-    match parse_json_from_bytes(body) {
-      Ok(simd_value) => Some(simd_to_serde_value(simd_value)),
-      Err(_) => {
-        // SIMD 解析失败，回退到标准解析
-        serde_json::from_slice(body).ok()
-      }
-    }
-  }
-
   fn is_form_content_type(headers: &HashMap<String, String>) -> bool {
     headers
       .get("content-type")
@@ -749,6 +1371,31 @@ impl DetachedRequestWrapper {
       .unwrap_or(false)
   }
 
+  /// 根据 Content-Type 判断非空请求体的类型，只比较字符串，不解析请求体
+  fn body_type_from_content_type(content_type: Option<&str>) -> &'static str {
+    let Some(content_type) = content_type else {
+      return "binary";
+    };
+    let mime = content_type
+      .split(';')
+      .next()
+      .unwrap_or_default()
+      .trim()
+      .to_ascii_lowercase();
+
+    if mime == "application/json" || mime.ends_with("+json") {
+      "json"
+    } else if mime == "application/x-www-form-urlencoded" {
+      "urlencoded"
+    } else if mime.starts_with("multipart/") {
+      "multipart"
+    } else if mime.starts_with("text/") {
+      "text"
+    } else {
+      "binary"
+    }
+  }
+
   fn parse_form_data_static(
     body: &Bytes,
     headers: &HashMap<String, String>,
@@ -797,58 +1444,21 @@ impl DetachedRequestWrapper {
     }
 
     let mut form_data = serde_json::Map::new();
-    let body_str = String::from_utf8_lossy(body);
-
-    // 查找实际的 boundary
-    let actual_boundary = if body_str.starts_with("--") {
-      if let Some(first_line_end) = body_str.find("\r\n").or_else(|| body_str.find("\n")) {
-        &body_str[2..first_line_end]
-      } else {
-        boundary
-      }
-    } else {
-      boundary
-    };
-
-    let boundary_delimiter = format!("--{}", actual_boundary);
-    let parts: Vec<&str> = body_str.split(&boundary_delimiter).collect();
-
-    for part in parts.iter().skip(1) {
-      if part.trim().is_empty() || part.starts_with("--") {
-        continue;
-      }
-
-      let header_end = part.find("\r\n\r\n").or_else(|| part.find("\n\n"));
-
-      if let Some(header_end) = header_end {
-        let headers = &part[..header_end];
-        let content_start = if part[header_end..].starts_with("\r\n\r\n") {
-          header_end + 4
-        } else {
-          header_end + 2
-        };
-        let content = &part[content_start..]
-          .trim_end_matches("\r\n")
-          .trim_end_matches("\n");
-
-        if let Some(name) = Self::extract_form_field_name_static(headers) {
-          if headers.contains("filename=") {
-            // 对于文件字段，在静态模式下我们只记录基本信息，不实际保存文件
-            if let Some(filename) = Self::extract_filename_static(headers) {
-              let file_info = serde_json::json!({
-                "type": "file",
-                "originalName": filename,
-                "filename": format!("static_mode_{}", filename),
-                "path": format!("static/static_mode_{}", filename),
-                "size": content.len(),
-                "contentType": Self::extract_content_type_static(headers)
-              });
-              form_data.insert(name, file_info);
-            }
-          } else {
-            // 处理文本字段
-            form_data.insert(name, serde_json::Value::String(content.to_string()));
+    for section in split_multipart(body, boundary) {
+      let headers = &section.headers;
+      if let Some(name) = Self::extract_form_field_name_static(headers) {
+        // 文件字段（filename= 或 filename*=）：保存到上传目录，内存模式下以 base64 返回内容
+        if let Some(filename) = Self::extract_filename_static(headers) {
+          let content_type = Self::extract_content_type_static(headers);
+          if let Some(file_value) = file_field_value(filename, content_type, section.content) {
+            form_data.insert(name, file_value);
           }
+        } else {
+          // 处理文本字段
+          form_data.insert(
+            name,
+            serde_json::Value::String(String::from_utf8_lossy(section.content).into_owned()),
+          );
         }
       }
     }
@@ -871,17 +1481,7 @@ impl DetachedRequestWrapper {
   }
 
   fn extract_filename_static(headers: &str) -> Option<String> {
-    for line in headers.lines() {
-      if line.to_lowercase().starts_with("content-disposition:") {
-        if let Some(filename_start) = line.find("filename=\"") {
-          let filename_part = &line[filename_start + 10..];
-          if let Some(filename_end) = filename_part.find('"') {
-            return Some(filename_part[..filename_end].to_string());
-          }
-        }
-      }
-    }
-    None
+    extract_disposition_filename(headers)
   }
 
   fn extract_content_type_static(headers: &str) -> Option<String> {
@@ -899,6 +1499,7 @@ impl DetachedRequestWrapper {
     req: HttpRequest,
     body: Option<Bytes>,
     path_params: HashMap<String, String>,
+    remote_addr: Option<IpAddr>,
   ) -> Self {
     // 🚀 优化：使用字符串内部化减少内存分配
     let path = intern_string(req.path().to_string());
@@ -909,9 +1510,14 @@ impl DetachedRequestWrapper {
     // 🚀 字符串池优化：智能预分配请求头容器
     let header_count = req.headers().len();
     let mut headers = HashMap::with_capacity(header_count.max(16));
+    let mut header_pairs = Vec::with_capacity(header_count);
 
     // 提前解析所有请求头，使用常量池优化常见请求头名称
     for (name, value) in req.headers() {
+      header_pairs.push((
+        name.as_str().to_string(),
+        String::from_utf8_lossy(value.as_bytes()).into_owned(),
+      ));
       if let Ok(value_str) = value.to_str() {
         let header_name_lower = name.as_str().to_lowercase();
         let header_name = COMMON_HEADERS
@@ -922,70 +1528,267 @@ impl DetachedRequestWrapper {
       }
     }
 
-    // 🚀 预计算缓存 - 零拷贝优化：在创建时解析，避免运行时原子操作开销
-    let cached_query_params = if query_string.is_empty() {
-      None
-    } else {
-      // 智能预分配：根据查询字符串中 '&' 的数量估算参数数量
-      let estimated_param_count = query_string.matches('&').count() + 1;
-      Some(Self::parse_query_params_static_with_capacity(
-        &query_string,
-        estimated_param_count,
-      ))
-    };
-
-    let cached_json = if let Some(ref body_bytes) = body {
-      if Self::is_json_content_type(&headers) {
-        Self::parse_json_static(body_bytes)
-      } else {
-        None
-      }
-    } else {
-      None
-    };
-
-    let cached_form_data = if let Some(ref body_bytes) = body {
-      if Self::is_form_content_type(&headers) {
-        Self::parse_form_data_static(body_bytes, &headers)
-      } else {
-        None
-      }
-    } else {
-      None
-    };
-
     Self {
       path,
       method,
       query_string,
       uri,
+      version: req.version(),
       headers,
+      header_pairs,
+      detached_at: Instant::now(),
       body,
       path_params,
+      remote_addr,
+      max_response_body_size: None,
+      request_timeout: None,
+      csrf_secret: None,
+      wildcard_param: None,
+      dispatch_timing: None,
+      in_flight: None,
+      response_sender: None,
+      sent: false,
+      status_code: None,
+      response_headers: Vec::new(),
+      next_sender: None,
+      response_status: None,
+      response_elapsed: None,
+      cached_query_params: OnceCell::new(),
+      cached_json: OnceCell::new(),
+      cached_form_data: OnceCell::new(),
+      cached_cookies: OnceCell::new(),
+      multipart: None,
+    }
+  }
+
+  /// 复制请求数据，得到一个尚未发送响应的新包装器，交给请求钩子使用
+  /// 请求体是共享引用计数的 Bytes，不会复制内容；流式 multipart 状态不会复制
+  pub fn hook_copy(&self) -> Self {
+    Self {
+      path: self.path.clone(),
+      method: self.method.clone(),
+      query_string: self.query_string.clone(),
+      uri: self.uri.clone(),
+      version: self.version,
+      headers: self.headers.clone(),
+      header_pairs: self.header_pairs.clone(),
+      detached_at: self.detached_at,
+      body: self.body.clone(),
+      path_params: self.path_params.clone(),
+      remote_addr: self.remote_addr,
+      max_response_body_size: self.max_response_body_size,
+      request_timeout: self.request_timeout,
+      csrf_secret: self.csrf_secret.clone(),
+      wildcard_param: self.wildcard_param.clone(),
+      dispatch_timing: None,
+      in_flight: None,
       response_sender: None,
       sent: false,
       status_code: None,
       response_headers: Vec::new(),
-      cached_query_params,
-      cached_json,
-      cached_form_data,
+      next_sender: None,
+      response_status: None,
+      response_elapsed: None,
+      cached_query_params: OnceCell::new(),
+      cached_json: OnceCell::new(),
+      cached_form_data: OnceCell::new(),
+      cached_cookies: OnceCell::new(),
+      multipart: None,
     }
   }
 
+  /// 查询参数，第一次访问时解析
+  fn query_params(&self) -> Option<&HashMap<String, String>> {
+    self
+      .cached_query_params
+      .get_or_init(|| {
+        if self.query_string.is_empty() {
+          return None;
+        }
+        // 智能预分配：根据查询字符串中 '&' 的数量估算参数数量
+        let estimated_param_count = self.query_string.matches('&').count() + 1;
+        Some(Self::parse_query_params_static_with_capacity(
+          &self.query_string,
+          estimated_param_count,
+        ))
+      })
+      .as_ref()
+  }
+
+  /// Cookie，第一次访问时解析
+  fn cookies(&self) -> &HashMap<String, String> {
+    self.cached_cookies.get_or_init(|| {
+      self
+        .headers
+        .get("cookie")
+        .map(|header| parse_cookie_header(header))
+        .unwrap_or_default()
+    })
+  }
+
+  /// JSON 请求体，第一次访问时解析；Content-Type 不是 JSON 时为 None
+  fn json_body(&self) -> Option<&serde_json::Value> {
+    self
+      .cached_json
+      .get_or_init(|| match &self.body {
+        Some(body_bytes) if Self::is_json_content_type(&self.headers) => {
+          parse_json_body(body_bytes)
+        }
+        _ => None,
+      })
+      .as_ref()
+  }
+
+  /// 表单数据，第一次访问时解析（multipart 文件在此时才写入临时目录）
+  fn form_data(&self) -> Option<&serde_json::Value> {
+    self
+      .cached_form_data
+      .get_or_init(|| match &self.body {
+        Some(body_bytes) if Self::is_form_content_type(&self.headers) => {
+          Self::parse_form_data_static(body_bytes, &self.headers)
+        }
+        _ => None,
+      })
+      .as_ref()
+  }
+
+  /// 按字段规格校验已解析的 JSON 请求体，spec 为 字段名 -> 类型名
+  /// 校验失败时返回缺失或类型不匹配的字段列表，便于处理函数直接返回 422
+  pub fn require_fields(
+    &self,
+    spec: HashMap<String, String>,
+  ) -> std::result::Result<(), Vec<String>> {
+    let invalid = find_invalid_fields(self.json_body(), &spec);
+    if invalid.is_empty() {
+      Ok(())
+    } else {
+      Err(invalid)
+    }
+  }
+
+  /// 根据 Range 请求头和资源总大小计算请求的字节区间 [start, end]
+  /// 没有 Range 头时返回 Ok(None)，区间超出资源范围时返回 RangeNotSatisfiable
+  pub fn get_range(
+    &self,
+    total_size: u64,
+  ) -> std::result::Result<Option<(u64, u64)>, RangeNotSatisfiable> {
+    match self.headers.get("range") {
+      Some(header) => resolve_byte_range(header, total_size),
+      None => Ok(None),
+    }
+  }
+
+  /// 设置流式 multipart 解析器
+  pub fn set_multipart_stream(&mut self, multipart: MultipartStream) {
+    self.multipart = Some(multipart);
+  }
+
+  /// 设置响应体大小上限
+  pub fn set_max_response_body_size(&mut self, limit: Option<usize>) {
+    self.max_response_body_size = limit;
+  }
+
+  /// 设置请求处理超时，转发到上游时据此计算等待响应头的超时时间
+  pub fn set_request_timeout(&mut self, timeout: Option<Duration>) {
+    self.request_timeout = timeout;
+  }
+
+  /// 设置 CSRF 令牌签名密钥，来自 ServerOptions.csrfSecret
+  pub fn set_csrf_secret(&mut self, secret: Option<Arc<[u8]>>) {
+    self.csrf_secret = secret;
+  }
+
+  /// 设置请求方法，用于方法覆盖（methodOverride）
+  pub fn set_method(&mut self, method: &str) {
+    self.method = intern_string(method.to_string());
+  }
+
+  /// 设置匹配到的路由中通配参数的名称，来自注册时的路由模式
+  pub fn set_wildcard_param(&mut self, name: Option<&str>) {
+    self.wildcard_param = name.map(str::to_string);
+  }
+
+  /// 设置分发耗时记录，发送响应时如果处理函数尚未被记录为已开始，则以此时为准
+  pub fn set_dispatch_timing(&mut self, timing: Arc<DispatchTiming>) {
+    self.dispatch_timing = Some(timing);
+  }
+
+  /// 设置进行中的回调计数，发送响应或包装器被释放时计数减一
+  pub fn set_in_flight(&mut self, in_flight: InFlightCallback) {
+    self.in_flight = Some(in_flight);
+  }
+
+  fn require_csrf_secret(&self) -> Result<&[u8]> {
+    self
+      .csrf_secret
+      .as_deref()
+      .ok_or_else(|| napi::Error::from_reason("未配置 csrfSecret，无法生成或校验 CSRF 令牌"))
+  }
+
   /// 设置响应发送器，用于异步响应
-  pub fn set_response_sender(&mut self, sender: oneshot::Sender<JsResponse>) {
+  pub fn set_response_sender(&mut self, sender: SharedResponseSender) {
     self.response_sender = Some(sender);
   }
 
+  /// 设置 before 钩子的继续通知，钩子调用 next() 时触发
+  pub fn set_next_sender(&mut self, sender: oneshot::Sender<()>) {
+    self.next_sender = Some(sender);
+  }
+
+  /// 设置最终响应状态码，供 after 钩子读取
+  pub fn set_response_status(&mut self, status: u16) {
+    self.response_status = Some(status);
+  }
+
+  /// 冻结从分发到响应就绪的耗时，after 钩子中的 getElapsedMs 返回该值，不包含钩子在回调队列中的等待时间
+  pub fn freeze_elapsed(&mut self) {
+    self.response_elapsed = Some(self.detached_at.elapsed());
+  }
+
   /// 发送响应
   fn send_response(&mut self, inner: InnerResp) -> Result<()> {
+    self.send_response_with(inner, None, Vec::new())
+  }
+
+  /// 发送响应，同时设置状态码并追加响应头；只有在确定可以发送之后才修改包装器，
+  /// 已经发送或响应体超出上限而失败时状态码和响应头保持不变
+  fn send_response_with(
+    &mut self,
+    inner: InnerResp,
+    status: Option<u16>,
+    headers: Vec<(String, String)>,
+  ) -> Result<()> {
     if self.sent {
       return Err(napi::Error::from_reason("响应已经发送"));
     }
 
-    self.sent = true;
+    // 超出响应体上限时拒绝发送，响应仍未发送，处理函数可以改为发送其他内容
+    if let Some(limit) = self.max_response_body_size {
+      let size = inner.body_len();
+      if size > limit {
+        return Err(napi::Error::from_reason(format!(
+          "响应体大小 {} 字节超过上限 {} 字节，请考虑分页或使用流式响应",
+          size, limit
+        )));
+      }
+    }
 
-    if let Some(sender) = self.response_sender.take() {
+    if status.is_some() {
+      self.status_code = status;
+    }
+    self.response_headers.extend(headers);
+    self.sent = true;
+    if let Some(timing) = &self.dispatch_timing {
+      timing.mark_handler_entered();
+    }
+    self.in_flight = None;
+
+    // 发送器已被取走时（处理函数抛出异常后已返回 500）不再发送
+    if let Some(sender) = self
+      .response_sender
+      .take()
+      .and_then(|sender| sender.lock().take())
+    {
       let response = JsResponse {
         inner,
         status_code: self.status_code,
@@ -1031,10 +1834,73 @@ impl DetachedRequestWrapper {
     self.uri.to_string()
   }
 
+  #[napi]
+  /// 获取请求的 HTTP 版本，例如 HTTP/1.0、HTTP/1.1、HTTP/2.0
+  pub fn get_http_version(&self) -> String {
+    format!("{:?}", self.version)
+  }
+
+  #[napi]
+  /// 一次取回路径、方法、查询参数、请求头、路径参数、请求体大小和 Content-Type，
+  /// 处理函数需要其中多项时可以减少 FFI 调用次数
+  pub fn get_request_info(&self) -> RequestInfo {
+    RequestInfo {
+      path: self.path.to_string(),
+      method: self.method.to_string(),
+      query_string: self.query_string.to_string(),
+      query_params: self.query_params().cloned().unwrap_or_default(),
+      headers: self.headers.clone(),
+      path_params: self.path_params.clone(),
+      body_size: self.body.as_ref().map(|b| b.len() as u32).unwrap_or(0),
+      content_type: self.headers.get("content-type").cloned(),
+    }
+  }
+
   #[napi(ts_return_type = "{[key: string]: string}")]
-  /// 获取查询参数作为对象 - 零拷贝优化：使用预计算缓存，无运行时开销
+  /// 获取查询参数作为对象 - 第一次调用时解析并缓存
   pub fn get_query_params(&self) -> HashMap<String, String> {
-    self.cached_query_params.clone().unwrap_or_default()
+    self.query_params().cloned().unwrap_or_default()
+  }
+
+  #[napi]
+  /// 获取查询参数，保留重复的键，例如 ?tag=a&tag=b 返回 { tag: ["a", "b"] }
+  pub fn get_query_params_multi(&self) -> HashMap<String, Vec<String>> {
+    parse_query_multi(&self.query_string)
+  }
+
+  #[napi]
+  /// 获取查询参数 name 的所有值，按出现顺序排列，不存在时返回空数组
+  pub fn get_query_values(&self, name: String) -> Vec<String> {
+    query_pairs(&self.query_string)
+      .filter(|(key, _)| *key == name)
+      .map(|(_, value)| value)
+      .collect()
+  }
+
+  #[napi]
+  /// 按规格读取并转换查询参数，例如 ?active=true 转为布尔值、?ids=1,2,3 转为数组
+  /// 缺失的参数使用默认值；转换失败的参数同样使用默认值，并在 errors 中列出
+  pub fn get_query_typed(&self, spec: HashMap<String, QueryParamSpec>) -> Result<TypedQuery> {
+    if let Some((name, param)) = spec
+      .iter()
+      .find(|(_, param)| !QUERY_PARAM_TYPES.contains(&param.r#type.as_str()))
+    {
+      return Err(napi::Error::from_reason(format!(
+        "查询参数 {} 的类型 {} 无效，可选值为 {}",
+        name,
+        param.r#type,
+        QUERY_PARAM_TYPES.join(" / ")
+      )));
+    }
+
+    let empty = HashMap::new();
+    let params = self.query_params().unwrap_or(&empty);
+    let (values, errors) = typed_query_values(params, &spec);
+
+    Ok(TypedQuery {
+      values: serde_json::Value::Object(values),
+      errors,
+    })
   }
 
   #[napi]
@@ -1046,6 +1912,50 @@ impl DetachedRequestWrapper {
     }
   }
 
+  #[napi]
+  /// 获取收到的原始请求体字节，未做任何解码或转换，没有请求体时返回 null
+  /// 唯一的例外是 Content-Encoding（gzip / deflate / br / zstd）压缩的请求体，这里得到的是解压后的字节
+  /// 校验 Webhook 签名（如 Stripe、GitHub 的 HMAC）时必须使用此方法，getBodyString 会替换非 UTF-8 字节导致签名不匹配
+  pub fn get_raw_body(&self) -> Option<Buffer> {
+    self.body.as_ref().map(|bytes| bytes.to_vec().into())
+  }
+
+  #[napi]
+  /// 与 getRawBody 相同，只是没有请求体时返回空 Buffer 而不是 null，省去调用方的空值判断
+  pub fn get_body_buffer(&self) -> Buffer {
+    self.get_raw_body().unwrap_or_else(|| Vec::new().into())
+  }
+
+  #[napi]
+  /// 校验 Webhook 签名：对原始请求体计算 HMAC-SHA256，与 headerName 请求头中的十六进制签名做常量时间比较
+  /// prefix 用于带前缀的格式，例如 GitHub 的 X-Hub-Signature-256 传入 "sha256="；请求头缺失或格式不符时返回 false
+  pub fn verify_hmac_sha256(
+    &self,
+    header_name: String,
+    secret: String,
+    prefix: Option<String>,
+  ) -> bool {
+    let Some(header) = self.headers.get(&header_name.to_ascii_lowercase()) else {
+      return false;
+    };
+    let body = self.body.as_deref().unwrap_or_default();
+    webhook::verify_hmac_sha256(secret.as_bytes(), body, header, prefix.as_deref())
+  }
+
+  #[napi]
+  /// 校验 Stripe Webhook 的 Stripe-Signature 请求头（t=时间戳,v1=签名），签名内容为 "时间戳.原始请求体"
+  /// 时间戳与当前时间相差超过 toleranceSeconds（默认 300 秒）时视为重放，返回 false
+  pub fn verify_stripe_signature(&self, secret: String, tolerance_seconds: Option<u32>) -> bool {
+    let Some(header) = self.headers.get("stripe-signature") else {
+      return false;
+    };
+    let body = self.body.as_deref().unwrap_or_default();
+    let tolerance = tolerance_seconds
+      .map(u64::from)
+      .unwrap_or(webhook::DEFAULT_STRIPE_TOLERANCE_SECS);
+    webhook::verify_stripe_signature(secret.as_bytes(), body, header, unix_now(), tolerance)
+  }
+
   #[napi]
   /// 检查请求体是否为空
   pub fn has_body(&self) -> bool {
@@ -1058,10 +1968,138 @@ impl DetachedRequestWrapper {
     self.body.as_ref().map(|b| b.len() as u32).unwrap_or(0)
   }
 
+  #[napi]
+  /// 获取请求声明的 Content-Length，请求头缺失或无法解析时返回 null
+  /// 与 getBodySize 不同，它不依赖请求体是否已读取；分块传输的请求没有 Content-Length，两者可能不一致
+  pub fn get_content_length(&self) -> Option<i64> {
+    parse_content_length(self.headers.get("content-length").map(String::as_str))
+  }
+
   #[napi(ts_return_type = "{[key: string]: any}")]
-  /// 尝试将请求体解析为JSON对象 - 零拷贝优化：使用预计算缓存，无运行时开销
-  pub fn get_body_json(&self) -> Option<serde_json::Value> {
-    self.cached_json.clone()
+  /// 尝试将请求体解析为JSON对象 - 第一次调用时解析并缓存
+  /// 默认使用 SIMD 解析器，超出 2^53 的整数转换为 JavaScript number 时会丢失精度，
+  /// 需要精确的 64 位整数时传入 { bigIntAsString: true }
+  pub fn get_body_json(&self, options: Option<JsonBodyOptions>) -> Option<serde_json::Value> {
+    if options.and_then(|options| options.big_int_as_string) == Some(true) {
+      return match &self.body {
+        Some(body) if Self::is_json_content_type(&self.headers) => parse_json_lossless(body).ok(),
+        _ => None,
+      };
+    }
+    self.json_body().cloned()
+  }
+
+  #[napi]
+  /// 解析 JSON 请求体并返回结果或失败原因，用于区分"没有请求体"和"请求体不是合法的 JSON"
+  /// 没有请求体时 value 和 error 都为 null；Content-Type 不是 application/json 或解析失败时 error 为原因
+  pub fn get_body_json_result(&self) -> JsonBodyResult {
+    let body = match &self.body {
+      Some(body) if !body.is_empty() => body,
+      _ => {
+        return JsonBodyResult {
+          value: None,
+          error: None,
+        }
+      }
+    };
+    if !Self::is_json_content_type(&self.headers) {
+      return JsonBodyResult {
+        value: None,
+        error: Some("Content-Type 不是 application/json".to_string()),
+      };
+    }
+    if let Some(value) = self.cached_json.get().and_then(Option::as_ref) {
+      return JsonBodyResult {
+        value: Some(value.clone()),
+        error: None,
+      };
+    }
+
+    match try_parse_json_body(body) {
+      Ok(value) => {
+        let _ = self.cached_json.set(Some(value.clone()));
+        JsonBodyResult {
+          value: Some(value),
+          error: None,
+        }
+      }
+      Err(e) => JsonBodyResult {
+        value: None,
+        error: Some(e.to_string()),
+      },
+    }
+  }
+
+  #[napi(ts_args_type = "callback: (element: any) => void")]
+  /// 🚀 流式遍历 JSON 数组请求体：逐个解析顶层元素并交给回调，返回元素个数
+  /// 解析失败时抛出错误，错误信息中包含出错元素的下标
+  /// 请求体不会被整体解析为 JSON，同一时间只保留一个元素，内存占用与单个元素大小相关而与数组长度无关
+  pub fn for_each_json_element(&self, callback: Function<serde_json::Value, ()>) -> Result<u32> {
+    let Some(body) = &self.body else {
+      return Ok(0);
+    };
+
+    let mut count = 0;
+    for element in JsonArrayElements::new(body) {
+      let element = element.map_err(|e| {
+        napi::Error::from_reason(format!(
+          "JSON 数组第 {} 个元素（下标 {}）解析失败: {}",
+          e.index + 1,
+          e.index,
+          e.message
+        ))
+      })?;
+      callback.call(element)?;
+      count += 1;
+    }
+
+    Ok(count)
+  }
+
+  #[napi]
+  /// 检查是否为 JSON 请求（Content-Type 包含 application/json），只检查请求头，不解析请求体
+  pub fn is_json_request(&self) -> bool {
+    Self::is_json_content_type(&self.headers)
+  }
+
+  #[napi]
+  /// 检查是否为表单请求（application/x-www-form-urlencoded 或 multipart/form-data），只检查请求头，不解析请求体
+  pub fn is_form_request(&self) -> bool {
+    Self::is_form_content_type(&self.headers)
+  }
+
+  #[napi(ts_return_type = "'json' | 'urlencoded' | 'multipart' | 'text' | 'binary' | 'empty'")]
+  /// 获取请求体类型，只根据 Content-Type 和请求体是否存在判断，不会触发解析
+  pub fn get_body_type(&self) -> String {
+    if self.multipart.is_some() {
+      return "multipart".to_string();
+    }
+    if self.body.as_ref().is_none_or(|body| body.is_empty()) {
+      return "empty".to_string();
+    }
+
+    Self::body_type_from_content_type(self.headers.get("content-type").map(String::as_str))
+      .to_string()
+  }
+
+  #[napi(
+    ts_args_type = "spec: Record<string, 'string' | 'number' | 'boolean' | 'object' | 'array'>"
+  )]
+  /// 按字段规格校验 JSON 请求体，返回缺失或类型不匹配的字段名，全部通过时返回空数组
+  pub fn check_required_fields(&self, spec: HashMap<String, String>) -> Result<Vec<String>> {
+    if let Some((field, expected)) = spec
+      .iter()
+      .find(|(_, expected)| !JSON_FIELD_TYPES.contains(&expected.as_str()))
+    {
+      return Err(napi::Error::from_reason(format!(
+        "字段 {} 的类型 {} 无效，可选值为 {}",
+        field,
+        expected,
+        JSON_FIELD_TYPES.join(" / ")
+      )));
+    }
+
+    Ok(self.require_fields(spec).err().unwrap_or_default())
   }
 
   #[napi]
@@ -1076,6 +2114,87 @@ impl DetachedRequestWrapper {
     self.headers.clone()
   }
 
+  #[napi(ts_return_type = "Array<[string, string]>")]
+  /// 获取所有请求头的 [名称, 值] 列表，同名请求头的每一次出现都会保留，并按收到的顺序排列；名称为小写
+  /// getHeaders 中同名请求头只保留一个值；不同名称之间的顺序由 actix 的 HeaderMap 决定，与线上顺序不一定一致
+  /// 值不是合法的 UTF-8 时其中的无效字节被替换为 U+FFFD
+  pub fn get_header_pairs(&self) -> Vec<(String, String)> {
+    self.header_pairs.clone()
+  }
+
+  #[napi(ts_return_type = "{[key: string]: string}")]
+  /// 获取 Cookie 请求头解析后的所有 Cookie，第一次调用时解析；同名 Cookie 以第一个为准
+  pub fn get_cookies(&self) -> HashMap<String, String> {
+    self.cookies().clone()
+  }
+
+  #[napi]
+  /// 获取指定名称的 Cookie，不存在时返回 null
+  pub fn get_cookie(&self, name: String) -> Option<String> {
+    self.cookies().get(&name).cloned()
+  }
+
+  #[napi]
+  /// 根据 Range 请求头和调用方提供的资源总大小计算字节区间，没有 Range 头时返回 null
+  /// 区间超出资源范围时抛出错误，处理函数应返回 416；否则由处理函数自行返回 206 和 Content-Range
+  pub fn get_byte_range(&self, total_size: i64) -> Result<Option<ByteRange>> {
+    let total_size =
+      u64::try_from(total_size).map_err(|_| napi::Error::from_reason("totalSize 不能为负数"))?;
+
+    match self.get_range(total_size) {
+      Ok(range) => Ok(range.map(|(start, end)| ByteRange {
+        start: start as i64,
+        end: end as i64,
+      })),
+      Err(RangeNotSatisfiable) => Err(napi::Error::from_reason("Range Not Satisfiable")),
+    }
+  }
+
+  #[napi]
+  /// 乐观锁检查：没有 If-Match 头或其中的 ETag 与当前版本匹配时返回 true
+  /// 返回 false 时处理函数应调用 sendPreconditionFailedAsync 返回 412
+  pub fn check_if_match(&self, current_etag: String) -> bool {
+    match self.headers.get("if-match") {
+      Some(header) => if_match_satisfied(header, &current_etag),
+      None => true,
+    }
+  }
+
+  #[napi]
+  /// 乐观锁检查：没有 If-Unmodified-Since 头或资源在该时间之后未被修改时返回 true
+  /// lastModified 为资源最后修改时间的毫秒时间戳；请求同时带有 If-Match 时应以 checkIfMatch 为准
+  pub fn check_if_unmodified_since(&self, last_modified: i64) -> bool {
+    match self.headers.get("if-unmodified-since") {
+      Some(header) => {
+        let last_modified = UNIX_EPOCH + Duration::from_millis(last_modified.max(0) as u64);
+        if_unmodified_since_satisfied(header, last_modified)
+      }
+      None => true,
+    }
+  }
+
+  #[napi]
+  /// 为会话生成 CSRF 令牌（会话 ID + 过期时间的 HMAC），默认 3600 秒后过期
+  /// 双重提交时把令牌同时放在 Cookie 和表单字段中，提交后用 verifyCsrfToken 校验
+  pub fn generate_csrf_token(
+    &self,
+    session_id: String,
+    ttl_seconds: Option<u32>,
+  ) -> Result<String> {
+    let secret = self.require_csrf_secret()?;
+    let ttl = ttl_seconds
+      .map(u64::from)
+      .unwrap_or(csrf::DEFAULT_CSRF_TTL_SECS);
+    Ok(csrf::generate_token(secret, &session_id, unix_now(), ttl))
+  }
+
+  #[napi]
+  /// 校验 CSRF 令牌的签名和有效期，签名比较是常量时间的
+  pub fn verify_csrf_token(&self, session_id: String, token: String) -> Result<bool> {
+    let secret = self.require_csrf_secret()?;
+    Ok(csrf::verify_token(secret, &session_id, &token, unix_now()))
+  }
+
   #[napi(ts_return_type = "{[key: string]: string}")]
   /// 获取路径参数作为对象
   pub fn get_path_params(&self) -> HashMap<String, String> {
@@ -1088,6 +2207,66 @@ impl DetachedRequestWrapper {
     self.path_params.get(&name).cloned()
   }
 
+  #[napi]
+  /// 获取通配参数的值，例如路由 /static/*path 匹配请求 /static/a/b/c.png 时返回 "a/b/c.png"
+  /// 匹配到的路由没有通配参数时返回 null
+  pub fn get_wildcard(&self) -> Option<String> {
+    self
+      .wildcard_param
+      .as_ref()
+      .and_then(|name| self.path_params.get(name))
+      .cloned()
+  }
+
+  #[napi(js_name = "next")]
+  /// 在 before 钩子中调用：不发送响应，立即继续执行路由处理函数，不必等钩子执行完毕
+  /// 不在 before 钩子中、已经调用过或已经发送响应时抛出错误
+  pub fn call_next(&mut self) -> Result<()> {
+    if self.sent {
+      return Err(napi::Error::from_reason("响应已经发送"));
+    }
+    let sender = self
+      .next_sender
+      .take()
+      .ok_or_else(|| napi::Error::from_reason("next() 只能在 before 钩子中调用一次"))?;
+    // 放弃响应发送器，之后的 send 系列调用不会再发出响应
+    self.sent = true;
+    self.response_sender = None;
+    let _ = sender.send(());
+    Ok(())
+  }
+
+  #[napi(ts_return_type = "any")]
+  /// 获取 setAppState 设置的共享应用状态，未设置时返回 null
+  /// 返回的是副本，对每个请求只读，修改它不会影响其他请求
+  pub fn get_app_state(&self) -> Option<serde_json::Value> {
+    app_state().map(|state| state.as_ref().clone())
+  }
+
+  #[napi]
+  /// 在 after 钩子中获取最终响应状态码，其他情况下返回 null
+  pub fn get_response_status(&self) -> Option<u16> {
+    self.response_status
+  }
+
+  #[napi]
+  /// 获取从请求被分发（请求体读取完成、包装器创建）到现在经过的毫秒数，带小数
+  /// 使用单调时钟，不受系统时间调整影响；在 after 钩子中返回响应就绪时记录的固定值，不包含钩子自身的排队时间
+  pub fn get_elapsed_ms(&self) -> f64 {
+    self
+      .response_elapsed
+      .unwrap_or_else(|| self.detached_at.elapsed())
+      .as_secs_f64()
+      * 1000.0
+  }
+
+  #[napi]
+  /// 获取客户端 IP；配置了可信代理（trustedProxies）时会沿 Forwarded / X-Forwarded-For / X-Real-IP 解析真实地址
+  /// 在分发时解析一次；Unix 域套接字连接没有对端地址，返回 null
+  pub fn get_remote_addr(&self) -> Option<String> {
+    self.remote_addr.map(|ip| ip.to_string())
+  }
+
   // 异步响应方法 - 这些方法返回Promise，支持JavaScript的await语法
 
   #[napi]
@@ -1130,6 +2309,61 @@ impl DetachedRequestWrapper {
     }
   }
 
+  #[napi]
+  /// 在 Rust 侧用 serde_json 序列化并发送 JSON 响应，与 sendObjectAsync 的结果相同 - 返回Promise，支持await
+  /// 名称沿用最初的 simd-json 实现；实测 simd-json 序列化 serde_json::Value 约慢一倍，因此实际不使用 SIMD
+  ///
+  /// # Safety
+  /// 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+  /// 函数内部只进行JSON序列化和响应发送操作，不涉及内存安全问题。
+  pub async unsafe fn send_json_simd_async(&mut self, value: serde_json::Value) -> Result<()> {
+    let json_string = serialize_json_value(&value)
+      .map_err(|e| napi::Error::from_reason(format!("JSON序列化失败: {}", e)))?;
+    self.send_response(InnerResp::Json(json_string))
+  }
+
+  #[napi]
+  /// 一次调用完成状态码、Content-Type 和响应体的发送，减少 FFI 往返次数
+  /// contentType 为 JSON 类型（application/json、application/*+json）时按 JSON 响应发送（会应用 jsonEnvelope），
+  /// 其他类型按文本发送；未指定时为 text/plain; charset=utf-8。状态码或 contentType 无效时抛出错误
+  ///
+  /// # Safety
+  /// 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+  /// 函数内部只进行响应发送操作，不涉及内存安全问题。
+  pub async unsafe fn send_async(
+    &mut self,
+    status: u16,
+    body: String,
+    content_type: Option<String>,
+  ) -> Result<()> {
+    let status = parse_status_code(status)
+      .ok_or_else(|| napi::Error::from_reason(format!("无效的状态码 {}", status)))?;
+    if self.sent {
+      return Err(napi::Error::from_reason("响应已经发送"));
+    }
+
+    let inner = match content_type {
+      Some(content_type) => {
+        validate_response_header("content-type", &content_type)?;
+        let is_json = Self::body_type_from_content_type(Some(&content_type)) == "json";
+        self
+          .response_headers
+          .retain(|(name, _)| !name.eq_ignore_ascii_case("content-type"));
+        self
+          .response_headers
+          .push(("content-type".to_string(), content_type));
+        if is_json {
+          InnerResp::Json(body)
+        } else {
+          InnerResp::Text(body)
+        }
+      }
+      None => InnerResp::Text(body),
+    };
+    self.status_code = Some(status.as_u16());
+    self.send_response(inner)
+  }
+
   #[napi]
   /// 异步发送空响应 - 返回Promise，支持await
   ///
@@ -1140,6 +2374,270 @@ impl DetachedRequestWrapper {
     self.send_response(InnerResp::EmptyString)
   }
 
+  #[napi]
+  /// 异步发送重定向：设置 Location 响应头并发送空响应体
+  /// 默认 302；permanent 为 true 时 GET/HEAD 请求使用 301，其他方法使用 308（保留请求方法和请求体）
+  /// url 含有控制字符等不能作为响应头值的字符时抛出错误
+  ///
+  /// # Safety
+  /// 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+  /// 函数内部只进行响应发送操作，不涉及内存安全问题。
+  pub async unsafe fn send_redirect_async(
+    &mut self,
+    url: String,
+    permanent: Option<bool>,
+  ) -> Result<()> {
+    if self.sent {
+      return Err(napi::Error::from_reason("响应已经发送"));
+    }
+    if HeaderValue::from_str(&url).is_err() {
+      return Err(napi::Error::from_reason(format!(
+        "重定向地址不是合法的响应头值: {:?}",
+        url
+      )));
+    }
+
+    self.status_code = Some(match (permanent.unwrap_or(false), self.method.as_ref()) {
+      (false, _) => 302,
+      (true, "GET" | "HEAD") => 301,
+      (true, _) => 308,
+    });
+    self.response_headers.push(("location".to_string(), url));
+    self.send_response(InnerResp::EmptyString)
+  }
+
+  #[napi]
+  /// 异步发送二进制响应：未通过 addHeaderAsync 设置 Content-Type 时使用 application/octet-stream，
+  /// 开启 ServerOptions.sniffContentType 时根据内容推断
+  /// GET / HEAD 请求带单个区间的 Range 头时自动返回 206 和对应的片段，区间超出范围时返回 416
+  ///
+  /// # Safety
+  /// 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+  /// 函数内部只进行响应发送操作，不涉及内存安全问题。
+  pub async unsafe fn send_buffer_async(&mut self, body: Buffer) -> Result<()> {
+    self.send_response(InnerResp::Raw(Bytes::from(body.to_vec())))
+  }
+
+  #[napi]
+  /// 异步发送磁盘上的文件：分块流式读取，不会把整个文件读入内存；相对路径相对于进程的工作目录
+  /// Content-Type 根据扩展名确定（通过 addHeaderAsync 设置的优先），Content-Length 取自文件大小；
+  /// 文件不存在时返回 404；GET / HEAD 请求带单个区间的 Range 头时返回 206 和对应的片段
+  /// 路径原样使用，不做目录限制，不要直接拼接客户端传入的路径
+  ///
+  /// # Safety
+  /// 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+  /// 函数内部只进行响应发送操作，不涉及内存安全问题。
+  pub async unsafe fn send_file_async(&mut self, path: String) -> Result<()> {
+    self.send_response(InnerResp::File(PathBuf::from(path)))
+  }
+
+  #[napi]
+  /// 异步发送缓存的响应 - 命中 cachePut 写入的缓存时直接发送并返回 true，未命中返回 false
+  ///
+  /// # Safety
+  /// 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+  /// 函数内部只进行缓存读取和响应发送操作，不涉及内存安全问题。
+  pub async unsafe fn send_cached_async(&mut self, key: String) -> Result<bool> {
+    if self.sent {
+      return Ok(false);
+    }
+
+    match get_cached_response(&key) {
+      Some(entry) => {
+        self.status_code = Some(entry.status);
+        self.response_headers.extend(entry.headers);
+        self.send_response(InnerResp::Raw(entry.body))?;
+        Ok(true)
+      }
+      None => Ok(false),
+    }
+  }
+
+  #[napi]
+  /// 把当前请求（方法、路径、查询字符串、请求头、请求体）转发到上游服务，并把上游响应以流的方式返回给客户端
+  /// 逐跳头部不会转发，同名请求头全部保留；等待上游响应头超过 requestTimeoutMs 的 80% 时返回 504，连接失败等其他错误返回 502
+  ///
+  /// # Safety
+  /// 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+  /// 函数内部只进行上游请求和响应发送操作，不涉及内存安全问题。
+  pub async unsafe fn forward_async(&mut self, upstream_base_url: String) -> Result<()> {
+    if self.sent {
+      return Err(napi::Error::from_reason("响应已经发送"));
+    }
+
+    let request = ForwardRequest {
+      method: &self.method,
+      uri: &self.uri,
+      headers: &self.header_pairs,
+      body: self.body.clone(),
+      remote_addr: self.remote_addr,
+      response_timeout: upstream_response_timeout(self.request_timeout),
+    };
+
+    match forward(&upstream_base_url, request).await {
+      Ok(upstream) => {
+        self.status_code = Some(upstream.status);
+        self.response_headers.extend(upstream.headers);
+        self.send_response(InnerResp::Stream(upstream.body))
+      }
+      Err(ProxyError::InvalidUrl(message)) => Err(napi::Error::from_reason(format!(
+        "无效的上游地址 {}",
+        message
+      ))),
+      Err(ProxyError::Timeout) => {
+        eprintln!("警告：上游 {} 响应超时", upstream_base_url);
+        self.status_code = Some(504);
+        self.send_response(InnerResp::Text("Gateway Timeout".to_string()))
+      }
+      Err(ProxyError::Upstream(e)) => {
+        eprintln!("警告：转发到上游 {} 失败: {}", upstream_base_url, e);
+        self.status_code = Some(502);
+        self.send_response(InnerResp::Text("Bad Gateway".to_string()))
+      }
+    }
+  }
+
+  #[napi]
+  /// 以 text/event-stream 响应当前请求，并把连接加入 Rust 侧 SSE 广播，之后通过 broadcastSseEvent 推送事件
+  /// 连接会一直保持到客户端断开、服务器停止，或因积压过多事件被断开
+  ///
+  /// # Safety
+  /// 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+  /// 函数内部只进行响应发送操作，不涉及内存安全问题。
+  pub async unsafe fn send_sse_stream_async(&mut self) -> Result<()> {
+    if self.sent {
+      return Err(napi::Error::from_reason("响应已经发送"));
+    }
+
+    self.response_headers.extend(sse::sse_headers());
+    self.send_response(InnerResp::Stream(sse::subscribe()))
+  }
+
+  #[napi]
+  /// 以 text/event-stream 响应当前请求，返回只属于这个连接的 SseWriter，通过 write / writeEvent 推送事件、close 结束
+  /// 每个事件到达后立即写出，不会等到关闭；客户端断开后 write 返回 false
+  ///
+  /// # Safety
+  /// 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+  /// 函数内部只进行响应发送操作，不涉及内存安全问题。
+  pub async unsafe fn start_sse_async(&mut self) -> Result<sse::SseWriter> {
+    if self.sent {
+      return Err(napi::Error::from_reason("响应已经发送"));
+    }
+
+    let (writer, body) = sse::channel();
+    self.response_headers.extend(sse::sse_headers());
+    self.send_response(InnerResp::Stream(body))?;
+    Ok(writer)
+  }
+
+  #[napi]
+  /// 低层发送接口：使用给定的状态码、仅使用给定的头部并原样发送请求体
+  /// 不会自动补充 Content-Type，之前通过 addHeaderAsync 添加的头部也会被忽略；Content-Length、Date 等仍由 HTTP 层生成
+  ///
+  /// # Safety
+  /// 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+  /// 函数内部只进行响应发送操作，不涉及内存安全问题。
+  pub async unsafe fn send_raw_async(
+    &mut self,
+    status: u16,
+    headers: Option<HashMap<String, String>>,
+    body: Buffer,
+  ) -> Result<()> {
+    let status = parse_status_code(status)
+      .ok_or_else(|| napi::Error::from_reason(format!("无效的状态码 {}", status)))?;
+    let headers: Vec<(String, String)> = headers.unwrap_or_default().into_iter().collect();
+    for (name, value) in &headers {
+      validate_response_header(name, value)?;
+    }
+
+    if self.sent {
+      return Err(napi::Error::from_reason("响应已经发送"));
+    }
+    self.status_code = Some(status.as_u16());
+    self.response_headers = headers;
+    self.send_response(InnerResp::Exact(Bytes::from(body.to_vec())))
+  }
+
+  #[napi(ts_args_type = "template: string, values: Record<string, any>")]
+  /// 异步发送模板响应：{{key}} 替换为 HTML 转义后的值，{{{key}}} 原样输出，缺失的键替换为空字符串
+  /// 未设置 Content-Type 时以 text/html; charset=utf-8 发送
+  ///
+  /// # Safety
+  /// 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+  /// 函数内部只进行字符串替换和响应发送操作，不涉及内存安全问题。
+  pub async unsafe fn send_template_async(
+    &mut self,
+    template: String,
+    values: HashMap<String, serde_json::Value>,
+  ) -> Result<()> {
+    let mut headers = Vec::new();
+    if !self
+      .response_headers
+      .iter()
+      .any(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+    {
+      headers.push((
+        "content-type".to_string(),
+        "text/html; charset=utf-8".to_string(),
+      ));
+    }
+    self.send_response_with(
+      InnerResp::Text(render_template(&template, &values)),
+      None,
+      headers,
+    )
+  }
+
+  #[napi]
+  /// 发送 gRPC Trailers-Only 响应：只有头部、没有响应体，grpc-status / grpc-message 随头部一起发送
+  /// HTTP/2 上这是一个带 END_STREAM 的 HEADERS 帧，gRPC 规范将其视为只包含 trailers 的错误响应；
+  /// actix 不支持在响应体之后单独发送 trailers，因此不能先发送数据再补发状态
+  /// 仅支持 HTTP/2 连接，HTTP/1.x 请求上调用时不发送任何内容并抛出错误
+  ///
+  /// # Safety
+  /// 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+  /// 函数内部只进行响应发送操作，不涉及内存安全问题。
+  pub async unsafe fn send_grpc_trailers_only_async(
+    &mut self,
+    grpc_status: u32,
+    grpc_message: Option<String>,
+  ) -> Result<()> {
+    if self.version != Version::HTTP_2 {
+      return Err(napi::Error::from_reason(format!(
+        "gRPC Trailers-Only 响应需要 HTTP/2，当前请求为 {:?}",
+        self.version
+      )));
+    }
+
+    // gRPC 的错误通过 grpc-status 表达，HTTP 状态码始终为 200
+    self.status_code = Some(200);
+    self.response_headers.extend([
+      ("content-type".to_string(), "application/grpc".to_string()),
+      ("grpc-status".to_string(), grpc_status.to_string()),
+    ]);
+    if let Some(message) = grpc_message.filter(|message| !message.is_empty()) {
+      self
+        .response_headers
+        .push(("grpc-message".to_string(), encode_grpc_message(&message)));
+    }
+    self.send_response(InnerResp::Exact(Bytes::new()))
+  }
+
+  #[napi]
+  /// 异步发送 412 Precondition Failed 响应，用于 checkIfMatch / checkIfUnmodifiedSince 不成立时
+  ///
+  /// # Safety
+  /// 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+  /// 函数内部只进行响应发送操作，不涉及内存安全问题。
+  pub async unsafe fn send_precondition_failed_async(&mut self) -> Result<()> {
+    self.send_response_with(
+      InnerResp::Text("Precondition Failed".to_string()),
+      Some(412),
+      Vec::new(),
+    )
+  }
+
   #[napi]
   /// 异步发送服务器错误响应 - 返回Promise，支持await
   ///
@@ -1154,7 +2652,31 @@ impl DetachedRequestWrapper {
   }
 
   #[napi]
-  /// 异步设置响应状态码 - 返回Promise，支持await
+  /// 异步以指定的 4xx / 5xx 状态码发送统一格式的 JSON 错误响应 {"error": message, "code": code, "details": details}
+  /// 没有 details 时省略该字段；状态码不是 4xx / 5xx 时抛出错误
+  ///
+  /// # Safety
+  /// 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+  /// 函数内部只进行响应发送操作，不涉及内存安全问题。
+  pub async unsafe fn send_error_json_async(
+    &mut self,
+    status: u16,
+    code: String,
+    message: String,
+    details: Option<serde_json::Value>,
+  ) -> Result<()> {
+    let status = parse_error_status_code(status)
+      .ok_or_else(|| napi::Error::from_reason(format!("无效的错误状态码 {}", status)))?;
+    if self.sent {
+      return Err(napi::Error::from_reason("响应已经发送"));
+    }
+
+    self.status_code = Some(status.as_u16());
+    self.send_response(InnerResp::Json(error_json_body(code, message, details)))
+  }
+
+  #[napi]
+  /// 异步设置响应状态码 - 返回Promise，支持await；超出 100-599 的状态码（例如 600）不会被设置，返回 false
   ///
   /// # Safety
   /// 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
@@ -1164,7 +2686,7 @@ impl DetachedRequestWrapper {
       return Ok(false);
     }
 
-    if !(100..1000).contains(&status) {
+    if parse_status_code(status).is_none() {
       return Ok(false);
     }
 
@@ -1173,21 +2695,109 @@ impl DetachedRequestWrapper {
   }
 
   #[napi]
-  /// 异步添加响应头 - 返回Promise，支持await
+  /// 异步添加响应头 - 返回Promise，支持await；名称或值不合法（包含空格、换行等字符）时抛出错误
   ///
   /// # Safety
   /// 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
   /// 函数内部只进行响应头添加操作，不涉及内存安全问题。
   pub async unsafe fn add_header_async(&mut self, key: String, value: String) -> Result<()> {
+    validate_response_header(&key, &value)?;
     if !self.sent {
       self.response_headers.push((key, value));
     }
     Ok(())
   }
 
+  #[napi]
+  /// 设置 Cookie：按 options 中的属性生成一个 Set-Cookie 响应头，多次调用会产生多个 Set-Cookie 头
+  /// 值需要由调用方自行编码（例如 encodeURIComponent）；名称、值或属性不合法时抛出错误
+  ///
+  /// # Safety
+  /// 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+  /// 函数内部只进行响应头添加操作，不涉及内存安全问题。
+  pub async unsafe fn set_cookie_async(
+    &mut self,
+    name: String,
+    value: String,
+    options: Option<CookieOptions>,
+  ) -> Result<()> {
+    if self.sent {
+      return Err(napi::Error::from_reason("响应已经发送"));
+    }
+
+    let options = options.unwrap_or_default();
+    let cookie = format_set_cookie(&name, &value, &options).map_err(napi::Error::from_reason)?;
+    self
+      .response_headers
+      .push(("set-cookie".to_string(), cookie));
+    Ok(())
+  }
+
+  #[napi]
+  /// 在最终响应上添加预加载 Link 头部，例如 "</style.css>; rel=preload; as=style"，只给出 URL 时按 rel=preload 处理
+  /// 不会发送 103 Early Hints 中间响应（actix-web 不支持），浏览器在收到最终响应后才开始预加载；
+  /// Cloudflare 等 CDN/代理可以根据这些 Link 头部自行向浏览器发送 103
+  ///
+  /// # Safety
+  /// 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+  /// 函数内部只进行响应头添加操作，不涉及内存安全问题。
+  pub async unsafe fn add_preload_links_async(&mut self, links: Vec<String>) -> Result<()> {
+    if self.sent {
+      return Err(napi::Error::from_reason("响应已经发送"));
+    }
+
+    for link in links {
+      let link = if link.trim_start().starts_with('<') {
+        link
+      } else {
+        format!("<{}>; rel=preload", link.trim())
+      };
+      self.response_headers.push(("link".to_string(), link));
+    }
+    Ok(())
+  }
+
+  #[napi]
+  /// 设置响应优先级提示（RFC 9218）：urgency 为 0-7，数值越小越优先，默认 3；incremental 表示客户端可以边接收边处理
+  /// 只对 HTTP/2 请求生效，以 Priority 响应头的形式发送，供支持 RFC 9218 的 CDN/代理调度；HTTP/1.x 请求忽略并返回 false
+  /// actix-web 使用的 h2 不提供设置流权重/依赖（PRIORITY 帧）的接口，RFC 9113 也已弃用该方案，本连接自身的帧调度不受影响
+  ///
+  /// # Safety
+  /// 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+  /// 函数内部只进行响应头添加操作，不涉及内存安全问题。
+  pub async unsafe fn set_priority_async(
+    &mut self,
+    urgency: u32,
+    incremental: Option<bool>,
+  ) -> Result<bool> {
+    if self.sent {
+      return Err(napi::Error::from_reason("响应已经发送"));
+    }
+    if urgency > 7 {
+      return Err(napi::Error::from_reason(format!(
+        "urgency 必须在 0-7 之间，收到 {}",
+        urgency
+      )));
+    }
+    if self.version != Version::HTTP_2 {
+      return Ok(false);
+    }
+
+    let value = if incremental.unwrap_or(false) {
+      format!("u={}, i", urgency)
+    } else {
+      format!("u={}", urgency)
+    };
+    self
+      .response_headers
+      .retain(|(name, _)| !name.eq_ignore_ascii_case("priority"));
+    self.response_headers.push(("priority".to_string(), value));
+    Ok(true)
+  }
+
   #[napi]
   /// 异步获取表单数据参数，支持 application/x-www-form-urlencoded 和 multipart/form-data 格式
-  /// 对于文件字段，直接返回文件信息对象 - 零拷贝优化：使用预计算缓存，无运行时开销
+  /// 对于文件字段，直接返回文件信息对象 - 第一次调用时解析并缓存
   ///
   /// # Safety
   /// 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
@@ -1195,14 +2805,53 @@ impl DetachedRequestWrapper {
   pub async unsafe fn get_form_data_async(&self) -> Result<serde_json::Value> {
     Ok(
       self
-        .cached_form_data
-        .clone()
+        .form_data()
+        .cloned()
         .unwrap_or_else(|| serde_json::Value::Object(serde_json::Map::new())),
     )
   }
 
   #[napi]
-  /// 异步获取表单数据中指定键的值 - 零拷贝优化：使用预计算缓存，无运行时开销
+  /// 流式 multipart：前进到下一个 part 并返回其元信息，没有更多 part 时返回 null
+  /// 仅对 newRouteMultipartStreamAsync 注册的路由可用，上一个 part 未读完的数据会被跳过
+  ///
+  /// # Safety
+  /// 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+  /// 函数内部只进行请求体流的读取操作，不涉及内存安全问题。
+  pub async unsafe fn next_part_async(&mut self) -> Result<Option<MultipartPartInfo>> {
+    let multipart = self
+      .multipart
+      .as_mut()
+      .ok_or_else(|| napi::Error::from_reason("当前路由未启用流式 multipart"))?;
+
+    multipart
+      .next_part()
+      .await
+      .map_err(|e| napi::Error::from_reason(format!("读取 multipart 数据失败: {}", e)))
+  }
+
+  #[napi]
+  /// 流式 multipart：读取当前 part 的下一个数据块，当前 part 读完时返回 null
+  ///
+  /// # Safety
+  /// 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
+  /// 函数内部只进行请求体流的读取操作，不涉及内存安全问题。
+  pub async unsafe fn read_part_chunk_async(&mut self) -> Result<Option<Buffer>> {
+    let Some(multipart) = self.multipart.as_mut() else {
+      return Ok(None);
+    };
+
+    match multipart.next_chunk().await {
+      Ok(chunk) => Ok(chunk.map(|chunk| chunk.to_vec().into())),
+      Err(e) => Err(napi::Error::from_reason(format!(
+        "读取 multipart 数据失败: {}",
+        e
+      ))),
+    }
+  }
+
+  #[napi]
+  /// 异步获取表单数据中指定键的值 - 第一次调用时解析并缓存
   ///
   /// # Safety
   /// 此函数被标记为unsafe是为了与NAPI绑定兼容，但实际操作是安全的。
@@ -1211,10 +2860,348 @@ impl DetachedRequestWrapper {
     &self,
     key: String,
   ) -> Result<Option<serde_json::Value>> {
-    if let Some(serde_json::Value::Object(map)) = &self.cached_form_data {
+    if let Some(serde_json::Value::Object(map)) = self.form_data() {
       Ok(map.get(&key).cloned())
     } else {
       Ok(None)
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_format_set_cookie() {
+    let options = CookieOptions {
+      max_age: Some(3600),
+      path: Some("/".to_string()),
+      domain: None,
+      http_only: Some(true),
+      secure: Some(true),
+      same_site: Some("lax".to_string()),
+    };
+    assert_eq!(
+      format_set_cookie("session", "abc123", &options).unwrap(),
+      "session=abc123; Max-Age=3600; Path=/; Secure; HttpOnly; SameSite=Lax"
+    );
+
+    let insecure_none = CookieOptions {
+      secure: None,
+      same_site: Some("None".to_string()),
+      ..options
+    };
+    assert!(format_set_cookie("session", "abc123", &insecure_none).is_err());
+    assert!(format_set_cookie("bad name", "x", &insecure_none).is_err());
+    assert!(format_set_cookie("session", "a;b", &insecure_none).is_err());
+  }
+
+  #[test]
+  fn test_validate_response_header() {
+    assert!(validate_response_header("x-request-id", "abc\t123").is_ok());
+    assert!(validate_response_header("bad name", "v").is_err());
+    assert!(validate_response_header("x-evil\r\nset-cookie", "v").is_err());
+    assert!(validate_response_header("x-ok", "v\r\nset-cookie: a=b").is_err());
+    assert!(validate_response_header("x-ok", "v\u{7f}").is_err());
+  }
+
+  #[test]
+  fn test_extract_disposition_filename() {
+    let plain = "Content-Disposition: form-data; name=\"file\"; filename=\"resume.pdf\"";
+    assert_eq!(
+      extract_disposition_filename(plain).as_deref(),
+      Some("resume.pdf")
+    );
+
+    // 同时出现时优先使用 filename*
+    let extended = "Content-Disposition: form-data; name=\"file\"; filename=\"r_sum_.pdf\"; \
+                    filename*=UTF-8''r%C3%A9sum%C3%A9.pdf\r\nContent-Type: application/pdf";
+    assert_eq!(
+      extract_disposition_filename(extended).as_deref(),
+      Some("résumé.pdf")
+    );
+
+    let latin1 = "content-disposition: form-data; name=\"f\"; filename*=iso-8859-1'fr'caf%E9.txt";
+    assert_eq!(
+      extract_disposition_filename(latin1).as_deref(),
+      Some("café.txt")
+    );
+
+    // 无法解码的扩展形式回退到 filename
+    let broken = "Content-Disposition: form-data; filename=\"a.txt\"; filename*=UTF-8''%ZZ";
+    assert_eq!(
+      extract_disposition_filename(broken).as_deref(),
+      Some("a.txt")
+    );
+    assert_eq!(
+      extract_disposition_filename("Content-Disposition: form-data; name=\"x\""),
+      None
+    );
+  }
+
+  #[test]
+  fn test_parse_content_length() {
+    assert_eq!(parse_content_length(Some("1024")), Some(1024));
+    assert_eq!(parse_content_length(Some(" 0 ")), Some(0));
+    assert_eq!(parse_content_length(Some("-1")), None);
+    assert_eq!(parse_content_length(Some("abc")), None);
+    assert_eq!(parse_content_length(None), None);
+  }
+
+  #[test]
+  fn test_parse_cookie_header() {
+    let cookies =
+      parse_cookie_header(r#"session=abc123; theme="dark mode";lang=zh; session=other; =x; flag"#);
+    assert_eq!(cookies.len(), 3);
+    assert_eq!(cookies["session"], "abc123");
+    assert_eq!(cookies["theme"], "dark mode");
+    assert_eq!(cookies["lang"], "zh");
+  }
+
+  #[test]
+  fn test_query_params_are_decoded() {
+    let params = DetachedRequestWrapper::parse_query_params_static_with_capacity(
+      "name=John%20Doe&q=a%2Bb&city=%E5%8C%97%E4%BA%AC&greeting=hello+world",
+      4,
+    );
+    assert_eq!(params["name"], "John Doe");
+    assert_eq!(params["q"], "a+b");
+    assert_eq!(params["city"], "北京");
+    assert_eq!(params["greeting"], "hello world");
+
+    // serde_qs 无法解析时的逐对解析也要解码
+    let params = DetachedRequestWrapper::parse_query_params_static_with_capacity(
+      "tags[]=x&my%20key=a%2Bb+c",
+      2,
+    );
+    assert_eq!(params["my key"], "a+b c");
+    assert_eq!(decode_query_component("100%"), "100%");
+    assert_eq!(decode_query_component("%E2%9C%93%zz"), "✓%zz");
+
+    let params = parse_query_multi("tag=a&tag=b+c&flag&&name=%E2%9C%93");
+    assert_eq!(params["tag"], vec!["a", "b c"]);
+    assert_eq!(params["flag"], vec![""]);
+    assert_eq!(params["name"], vec!["✓"]);
+    assert_eq!(params.len(), 3);
+  }
+
+  #[test]
+  fn test_split_multipart_keeps_binary_content() {
+    let file: &[u8] = &[0x89, b'P', b'N', b'G', 0xff, 0xfe, 0x00, b'\r', b'\n'];
+    let body = [
+      b"--AbC123\r\nContent-Disposition: form-data; name=\"title\"\r\n\r\nhello\r\n".as_slice(),
+      b"--AbC123\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.png\"\r\n",
+      b"Content-Type: image/png\r\n\r\n",
+      file,
+      b"\r\n--AbC123--\r\n",
+    ]
+    .concat();
+
+    // Content-Type 中的 boundary 已被转为小写，以请求体第一行为准
+    let sections = split_multipart(&body, "abc123");
+    assert_eq!(sections.len(), 2);
+    assert_eq!(sections[0].content, b"hello");
+    assert!(sections[1].headers.contains("filename=\"a.png\""));
+    assert_eq!(sections[1].content, file);
+  }
+
+  #[test]
+  fn test_parse_multipart_with_files_accepts_extended_filename() {
+    // 只带 filename*= 的 part 也是文件字段，不能被当成文本字段
+    let body = Bytes::from_static(
+      b"--b\r\nContent-Disposition: form-data; name=\"title\"\r\n\r\nhello\r\n\
+        --b\r\nContent-Disposition: form-data; name=\"file\"; filename*=UTF-8''%E6%8A%A5%E5%91%8A.txt\r\n\
+        Content-Type: text/plain\r\n\r\nreport\r\n--b--\r\n",
+    );
+    let content_type = "multipart/form-data; boundary=b";
+    crate::uploads::set_multipart_in_memory(true);
+
+    let request = RequestWrapper::new(
+      actix_web::test::TestRequest::default().to_http_request(),
+      None,
+    );
+    let form = request.parse_multipart_with_files(&body, content_type);
+    assert_eq!(form["title"], "hello");
+    assert_eq!(form["file"]["type"], "file");
+    assert_eq!(form["file"]["originalName"], "报告.txt");
+    assert_eq!(form["file"]["data"], encode_base64(b"report"));
+
+    let form = DetachedRequestWrapper::parse_multipart_static(&body, content_type).unwrap();
+    assert_eq!(form["file"]["type"], "file");
+    assert_eq!(form["file"]["originalName"], "报告.txt");
+
+    crate::uploads::set_multipart_in_memory(false);
+  }
+
+  #[test]
+  fn test_save_uploaded_file() {
+    let dir = std::env::temp_dir().join(format!("actix-js-uploads-{}", Uuid::new_v4()));
+    let info = save_uploaded_file(
+      &dir,
+      "报告.txt".to_string(),
+      Some("text/plain".to_string()),
+      b"report",
+    )
+    .unwrap();
+    assert_eq!(info.original_name, "报告.txt");
+    assert!(info.filename.ends_with(".txt"));
+    // 返回的路径必须指向真实写入的文件
+    assert_eq!(fs::read(info.path.unwrap()).unwrap(), b"report");
+
+    // 上传目录无法创建（父路径是普通文件）时返回错误而不是虚构的路径
+    let blocked = dir.join(&info.filename).join("nested");
+    let error = save_uploaded_file(&blocked, "a.txt".to_string(), None, b"a").unwrap_err();
+    assert!(error.contains("创建上传目录"));
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[actix_web::test]
+  async fn test_failed_send_leaves_wrapper_unchanged() {
+    let request = actix_web::test::TestRequest::default().to_http_request();
+    let mut wrapper = DetachedRequestWrapper::new_detached(request, None, HashMap::new(), None);
+    wrapper.send_response(InnerResp::EmptyString).unwrap();
+
+    // 已经发送之后再次发送会失败，状态码和响应头都不应被修改
+    unsafe {
+      assert!(wrapper.send_precondition_failed_async().await.is_err());
+      assert!(wrapper
+        .send_template_async("<p>{{x}}</p>".to_string(), HashMap::new())
+        .await
+        .is_err());
+    }
+    assert_eq!(wrapper.status_code, None);
+    assert!(wrapper.response_headers.is_empty());
+  }
+
+  #[test]
+  fn test_find_invalid_fields() {
+    let spec: HashMap<String, String> = [
+      ("name", "string"),
+      ("age", "number"),
+      ("tags", "array"),
+      ("active", "boolean"),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k.to_string(), v.to_string()))
+    .collect();
+
+    let json = serde_json::json!({ "name": "actix", "age": "18", "tags": [] });
+    assert_eq!(
+      find_invalid_fields(Some(&json), &spec),
+      vec!["active".to_string(), "age".to_string()]
+    );
+
+    // 非对象请求体：所有字段都视为缺失
+    assert_eq!(find_invalid_fields(None, &spec).len(), 4);
+  }
+
+  #[test]
+  fn test_conditional_preconditions() {
+    assert!(if_match_satisfied("\"v2\"", "v2"));
+    assert!(if_match_satisfied("\"v1\", \"v2\"", "\"v2\""));
+    assert!(if_match_satisfied("*", "v9"));
+    assert!(!if_match_satisfied("\"v1\"", "v2"));
+    assert!(!if_match_satisfied("W/\"v2\"", "v2"));
+
+    let since = "Sun, 06 Nov 1994 08:49:37 GMT";
+    let at_since = UNIX_EPOCH + Duration::from_secs(784_111_777);
+    assert!(if_unmodified_since_satisfied(since, at_since));
+    assert!(!if_unmodified_since_satisfied(
+      since,
+      at_since + Duration::from_secs(1)
+    ));
+    assert!(if_unmodified_since_satisfied(
+      "not a date",
+      SystemTime::now()
+    ));
+  }
+
+  #[test]
+  fn test_typed_query_values() {
+    let params: HashMap<String, String> = [
+      ("page", "2"),
+      ("active", "true"),
+      ("ids", "1, 2,3"),
+      ("ratio", "abc"),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k.to_string(), v.to_string()))
+    .collect();
+    let spec: HashMap<String, QueryParamSpec> = [
+      ("page", "int", None),
+      ("active", "bool", None),
+      ("ids", "csv", None),
+      ("ratio", "float", Some(serde_json::json!(0.5))),
+      ("q", "string", Some(serde_json::json!(""))),
+    ]
+    .into_iter()
+    .map(|(name, kind, default)| {
+      (
+        name.to_string(),
+        QueryParamSpec {
+          r#type: kind.to_string(),
+          default,
+        },
+      )
+    })
+    .collect();
+
+    let (values, errors) = typed_query_values(&params, &spec);
+    assert_eq!(values["page"], 2);
+    assert_eq!(values["active"], true);
+    assert_eq!(values["ids"], serde_json::json!(["1", "2", "3"]));
+    assert_eq!(values["ratio"], 0.5);
+    assert_eq!(values["q"], "");
+    assert_eq!(errors, vec!["ratio".to_string()]);
+  }
+
+  #[test]
+  fn test_body_type_from_content_type() {
+    let body_type = DetachedRequestWrapper::body_type_from_content_type;
+    assert_eq!(body_type(Some("application/json; charset=utf-8")), "json");
+    assert_eq!(body_type(Some("application/vnd.api+json")), "json");
+    assert_eq!(
+      body_type(Some("application/x-www-form-urlencoded")),
+      "urlencoded"
+    );
+    assert_eq!(
+      body_type(Some("multipart/form-data; boundary=x")),
+      "multipart"
+    );
+    assert_eq!(body_type(Some("text/plain")), "text");
+    assert_eq!(body_type(Some("application/octet-stream")), "binary");
+    assert_eq!(body_type(None), "binary");
+  }
+
+  #[test]
+  fn test_encode_grpc_message() {
+    assert_eq!(encode_grpc_message("not found"), "not found");
+    assert_eq!(
+      encode_grpc_message("100% 失败"),
+      "100%25 %E5%A4%B1%E8%B4%A5"
+    );
+  }
+
+  #[test]
+  fn test_render_template() {
+    let values: HashMap<String, serde_json::Value> = [
+      ("name", serde_json::json!("<b>Tom & Jerry</b>")),
+      ("count", serde_json::json!(3)),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k.to_string(), v))
+    .collect();
+
+    assert_eq!(
+      render_template("<p>{{ name }}</p><p>{{{name}}}</p>", &values),
+      "<p>&lt;b&gt;Tom &amp; Jerry&lt;/b&gt;</p><p><b>Tom & Jerry</b></p>"
+    );
+    assert_eq!(
+      render_template("{{count}} items{{missing}}", &values),
+      "3 items"
+    );
+    assert_eq!(render_template("open {{name", &values), "open {{name");
+  }
+}
@@ -1,8 +1,125 @@
 use actix_web::{
-  http::{header::HeaderValue, StatusCode},
-  HttpResponse,
+  body::{BodySize, BoxBody, MessageBody, SizedStream},
+  http::{
+    header::{HeaderValue, HttpDate, CONNECTION, CONTENT_ENCODING, DATE},
+    ConnectionType, StatusCode, Version,
+  },
+  HttpRequest, HttpResponse,
 };
 use bytes::Bytes;
+use futures::stream::BoxStream;
+use futures::{Stream, StreamExt};
+use parking_lot::RwLock;
+use std::io::{Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::AsyncReadExt;
+use uuid::Uuid;
+
+use crate::sniff;
+
+/// 文件响应每次从磁盘读取的块大小
+const FILE_CHUNK_SIZE: u64 = 64 * 1024;
+
+/// 缓存的 Date 头部值（秒级时间戳, 格式化结果），每秒最多格式化一次
+static DATE_HEADER: LazyLock<RwLock<(u64, HeaderValue)>> =
+  LazyLock::new(|| RwLock::new((0, HeaderValue::from_static(""))));
+
+/// 获取当前时间的 RFC 7231 格式 Date 头部值
+fn cached_date_header() -> HeaderValue {
+  let now = SystemTime::now();
+  let secs = now
+    .duration_since(UNIX_EPOCH)
+    .map(|elapsed| elapsed.as_secs())
+    .unwrap_or_default();
+
+  {
+    let cached = DATE_HEADER.read();
+    if cached.0 == secs {
+      return cached.1.clone();
+    }
+  }
+
+  let value = HeaderValue::from_str(&HttpDate::from(now).to_string())
+    .unwrap_or_else(|_| HeaderValue::from_static(""));
+  *DATE_HEADER.write() = (secs, value.clone());
+  value
+}
+
+/// 校验响应状态码：接受 100..=599 的任意值，包括没有标准原因短语的自定义状态码（例如 299）
+/// StatusCode::from_u16 接受 100..=999，600 及以上不属于任何状态码类别，这里一并拒绝
+pub fn parse_status_code(status: u16) -> Option<StatusCode> {
+  if !(100..=599).contains(&status) {
+    return None;
+  }
+  StatusCode::from_u16(status).ok()
+}
+
+/// 统一的错误响应体 {"error": message, "code": code, "details": details}，没有 details 时省略该字段
+pub fn error_json_body(
+  code: String,
+  message: String,
+  details: Option<serde_json::Value>,
+) -> String {
+  let mut body = serde_json::json!({ "error": message, "code": code });
+  if let Some(details) = details.filter(|details| !details.is_null()) {
+    body["details"] = details;
+  }
+  body.to_string()
+}
+
+/// 错误响应使用的状态码：只接受 4xx 和 5xx
+pub fn parse_error_status_code(status: u16) -> Option<StatusCode> {
+  parse_status_code(status).filter(|status| status.is_client_error() || status.is_server_error())
+}
+
+/// Range 请求超出资源范围（应返回 416 Range Not Satisfiable）
+#[derive(Debug, PartialEq)]
+pub struct RangeNotSatisfiable;
+
+/// 解析单个 `bytes=` 区间并按资源总大小换算为闭区间 [start, end]
+/// 格式无法识别或包含多个区间时按 RFC 7233 忽略该头部，返回 Ok(None)
+pub fn resolve_byte_range(
+  header: &str,
+  total_size: u64,
+) -> std::result::Result<Option<(u64, u64)>, RangeNotSatisfiable> {
+  let Some(spec) = header.trim().strip_prefix("bytes=") else {
+    return Ok(None);
+  };
+  if spec.contains(',') {
+    return Ok(None);
+  }
+  let Some((start, end)) = spec.trim().split_once('-') else {
+    return Ok(None);
+  };
+
+  let range = match (start.trim(), end.trim()) {
+    // bytes=-500：最后 500 字节
+    ("", suffix) => match suffix.parse::<u64>() {
+      Ok(0) => return Err(RangeNotSatisfiable),
+      Ok(suffix) => (
+        total_size.saturating_sub(suffix),
+        total_size.saturating_sub(1),
+      ),
+      Err(_) => return Ok(None),
+    },
+    // bytes=500-：从 500 到末尾
+    (start, "") => match start.parse::<u64>() {
+      Ok(start) => (start, total_size.saturating_sub(1)),
+      Err(_) => return Ok(None),
+    },
+    (start, end) => match (start.parse::<u64>(), end.parse::<u64>()) {
+      (Ok(start), Ok(end)) if start <= end => (start, end.min(total_size.saturating_sub(1))),
+      _ => return Ok(None),
+    },
+  };
+
+  if total_size == 0 || range.0 >= total_size {
+    return Err(RangeNotSatisfiable);
+  }
+  Ok(Some(range))
+}
 
 /// 响应的内部表示，类似参考项目的InnerResp
 #[derive(Debug)]
@@ -13,6 +130,123 @@ pub enum InnerResp {
   EmptyString,
   ServerError,
   ServerErrorWithMessage(String),
+  /// 流式响应体（如反向代理的上游响应），不在内存中缓冲
+  Stream(BodyStream),
+  /// 原样发送的字节，只使用调用方给出的头部，不补充 Content-Type
+  Exact(Bytes),
+  /// 磁盘上的文件，生成响应时才打开并流式读取；文件不存在时返回 404
+  File(PathBuf),
+}
+
+/// 流式响应体，已知长度时以 Content-Length 发送，否则使用分块传输
+pub struct BodyStream {
+  length: Option<u64>,
+  stream: BoxStream<'static, std::io::Result<Bytes>>,
+}
+
+impl BodyStream {
+  pub fn new(
+    length: Option<u64>,
+    stream: impl Stream<Item = std::io::Result<Bytes>> + Send + 'static,
+  ) -> Self {
+    Self {
+      length,
+      stream: Box::pin(stream),
+    }
+  }
+
+  /// 逐块转换响应体，转换在 actix 拉取下一个块时才执行，不会预读上游，背压保持不变
+  /// 转换后的长度无法预知，改为分块传输
+  pub fn map_chunks(self, mut transform: impl FnMut(Bytes) -> Bytes + Send + 'static) -> Self {
+    Self {
+      length: None,
+      stream: Box::pin(self.stream.map(move |chunk| chunk.map(&mut transform))),
+    }
+  }
+}
+
+impl BodyStream {
+  /// 从 start 开始流式读取文件中的 length 个字节，每次只在内存中保留一个块
+  fn from_file(mut file: std::fs::File, start: u64, length: u64) -> std::io::Result<Self> {
+    file.seek(SeekFrom::Start(start))?;
+    let file = tokio::fs::File::from_std(file);
+    let stream = futures::stream::try_unfold((file, length), |(mut file, remaining)| async move {
+      if remaining == 0 {
+        return Ok(None);
+      }
+      let mut chunk = vec![0; remaining.min(FILE_CHUNK_SIZE) as usize];
+      let read = file.read(&mut chunk).await?;
+      if read == 0 {
+        // 文件在发送过程中被截断，已经写出的 Content-Length 无法满足，只能中断连接
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof));
+      }
+      chunk.truncate(read);
+      Ok(Some((Bytes::from(chunk), (file, remaining - read as u64))))
+    });
+    Ok(Self::new(Some(length), stream))
+  }
+}
+
+/// 打开要发送的文件，返回文件句柄和大小；目录等非普通文件按不存在处理
+fn open_file(path: &Path) -> std::io::Result<(std::fs::File, u64)> {
+  let file = std::fs::File::open(path)?;
+  let metadata = file.metadata()?;
+  if !metadata.is_file() {
+    return Err(std::io::Error::from(std::io::ErrorKind::NotFound));
+  }
+  Ok((file, metadata.len()))
+}
+
+impl std::fmt::Debug for BodyStream {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("BodyStream")
+      .field("length", &self.length)
+      .finish_non_exhaustive()
+  }
+}
+
+impl InnerResp {
+  /// 响应体字节数，长度未知的流式响应体和文件响应按 0 计算
+  pub fn body_len(&self) -> usize {
+    match self {
+      InnerResp::Text(text) | InnerResp::Json(text) | InnerResp::ServerErrorWithMessage(text) => {
+        text.len()
+      }
+      InnerResp::Raw(bytes) | InnerResp::Exact(bytes) => bytes.len(),
+      InnerResp::Stream(stream) => stream.length.unwrap_or_default() as usize,
+      InnerResp::EmptyString | InnerResp::ServerError | InnerResp::File(_) => 0,
+    }
+  }
+
+  /// 内存中的响应体字节，流式响应体返回 None
+  pub fn body_bytes(&self) -> Option<&[u8]> {
+    match self {
+      InnerResp::Text(text) | InnerResp::Json(text) | InnerResp::ServerErrorWithMessage(text) => {
+        Some(text.as_bytes())
+      }
+      InnerResp::Raw(bytes) | InnerResp::Exact(bytes) => Some(bytes),
+      InnerResp::Stream(_) | InnerResp::File(_) => None,
+      InnerResp::EmptyString => Some(b""),
+      InnerResp::ServerError => Some(b"Internal Server Error"),
+    }
+  }
+
+  /// 复制响应体，流式响应体只能消费一次，返回 None
+  pub fn try_clone(&self) -> Option<Self> {
+    Some(match self {
+      InnerResp::Text(text) => InnerResp::Text(text.clone()),
+      InnerResp::Json(json) => InnerResp::Json(json.clone()),
+      InnerResp::Raw(bytes) => InnerResp::Raw(bytes.clone()),
+      InnerResp::EmptyString => InnerResp::EmptyString,
+      InnerResp::ServerError => InnerResp::ServerError,
+      InnerResp::ServerErrorWithMessage(message) => {
+        InnerResp::ServerErrorWithMessage(message.clone())
+      }
+      InnerResp::Stream(_) => return None,
+      InnerResp::Exact(bytes) => InnerResp::Exact(bytes.clone()),
+      InnerResp::File(path) => InnerResp::File(path.clone()),
+    })
+  }
 }
 
 /// JavaScript响应对象，包含响应数据、状态码和头部信息
@@ -24,23 +258,93 @@ pub struct JsResponse {
 }
 
 impl JsResponse {
+  /// 复制响应，流式响应返回 None
+  pub fn try_clone(&self) -> Option<Self> {
+    Some(JsResponse {
+      inner: self.inner.try_clone()?,
+      status_code: self.status_code,
+      headers: self.headers.clone(),
+    })
+  }
+
+  /// 传入信封时，把成功的 JSON 响应包装为 {data: ..., meta: {...}}，错误响应保持原样
+  /// 需要在后处理器之前调用，签名等后处理器才能看到最终发送的响应体
+  pub fn apply_envelope(&mut self, envelope: Option<&RequestEnvelope>) {
+    let Some(envelope) = envelope else {
+      return;
+    };
+    let status = self.get_status_code();
+    if status.is_client_error() || status.is_server_error() {
+      return;
+    }
+    if let InnerResp::Json(json) = &mut self.inner {
+      *json = envelope.wrap(std::mem::take(json));
+    }
+  }
+
   /// 将JsResponse转换为actix-web的HttpResponse
   pub fn into_http_response(self) -> HttpResponse {
+    let mut response = self.build_http_response();
+
+    // 所有响应类型都带上 Date，处理函数自己设置的 Date 优先
+    if !response.headers().contains_key(DATE) {
+      response.headers_mut().insert(DATE, cached_date_header());
+    }
+
+    response
+  }
+
+  /// 转换为 HEAD 请求的响应：头部与 GET 响应相同，去掉响应体但保留 Content-Length
+  /// 用于 HEAD 请求回退到 GET 处理函数的情况
+  pub fn into_head_response(self) -> HttpResponse {
+    let response = self.into_http_response();
+    let length = match response.body().size() {
+      BodySize::Sized(length) => Some(length),
+      BodySize::None | BodySize::Stream => None,
+    };
+
+    response.map_body(|_, _| match length {
+      // 长度相同的空流：actix 按长度写出 Content-Length，HEAD 请求不会读取响应体
+      Some(length) => BoxBody::new(SizedStream::new(
+        length,
+        futures::stream::empty::<std::io::Result<Bytes>>(),
+      )),
+      None => BoxBody::new(()),
+    })
+  }
+
+  fn build_http_response(mut self) -> HttpResponse {
     let status = self.get_status_code();
 
+    // GET / HEAD 的文件响应已在 apply_byte_range 中转为流式响应体，其余情况在这里打开，之后按已知长度的流式响应发送
+    if let InnerResp::File(path) = &self.inner {
+      let opened =
+        open_file(path).and_then(|(file, length)| BodyStream::from_file(file, 0, length));
+      match opened {
+        Ok(stream) => self.stream_file(stream),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+          return HttpResponse::NotFound()
+            .content_type("application/json")
+            .body(r#"{"error": "File not found"}"#);
+        }
+        Err(error) => {
+          return HttpResponse::InternalServerError()
+            .content_type("text/plain")
+            .body(format!("读取文件失败: {}", error));
+        }
+      }
+    }
+
     let mut builder = HttpResponse::build(status);
 
-    // 设置内容类型和应用自定义头部
-    match &self.inner {
-      InnerResp::Text(_) | InnerResp::EmptyString => {
-        builder.content_type("text/plain; charset=utf-8");
-      }
-      InnerResp::Json(_) => {
-        builder.content_type("application/json; charset=utf-8");
-      }
-      InnerResp::Raw(_) => {
-        builder.content_type("application/octet-stream");
+    // 按响应类型确定默认的内容类型
+    let default_content_type = match &self.inner {
+      InnerResp::Text(_) | InnerResp::EmptyString => Some("text/plain; charset=utf-8"),
+      InnerResp::Json(_) => Some("application/json; charset=utf-8"),
+      InnerResp::Raw(_) | InnerResp::Stream(_) | InnerResp::File(_) => {
+        Some("application/octet-stream")
       }
+      InnerResp::Exact(_) => None,
       InnerResp::ServerError | InnerResp::ServerErrorWithMessage(_) => {
         return HttpResponse::InternalServerError()
           .content_type("text/plain")
@@ -50,6 +354,12 @@ impl JsResponse {
             _ => unreachable!(),
           });
       }
+    };
+    // 处理函数自己设置的 Content-Type 优先（如 sendText 配合 text/csv、代理响应），不再设置默认值，避免出现两个 Content-Type
+    if let Some(content_type) = default_content_type {
+      if !self.has_custom_content_type() {
+        builder.content_type(content_type);
+      }
     }
 
     // 应用自定义头部
@@ -65,12 +375,198 @@ impl JsResponse {
     match self.inner {
       InnerResp::Text(text) => builder.body(text),
       InnerResp::Json(json) => builder.body(json),
-      InnerResp::Raw(bytes) => builder.body(bytes),
+      InnerResp::Raw(bytes) | InnerResp::Exact(bytes) => builder.body(bytes),
+      InnerResp::Stream(BodyStream { length, stream }) => match length {
+        Some(length) => builder.body(SizedStream::new(length, stream)),
+        None => builder.streaming(stream),
+      },
       InnerResp::EmptyString => builder.body(""),
       _ => unreachable!(), // 这些情况在上面已经处理过了
     }
   }
 
+  /// 原始字节响应没有设置 Content-Type 时，根据响应体内容推断内容类型
+  /// 推断成功时同时设置 X-Content-Type-Options: nosniff，避免浏览器再按自己的规则重新嗅探
+  pub fn sniff_content_type(&mut self) {
+    let InnerResp::Raw(bytes) = &self.inner else {
+      return;
+    };
+    if self.has_custom_content_type() {
+      return;
+    }
+    if let Some(mime) = sniff::sniff_content_type(bytes) {
+      self.headers.get_or_insert_with(Vec::new).extend([
+        ("content-type".to_string(), mime.to_string()),
+        ("x-content-type-options".to_string(), "nosniff".to_string()),
+      ]);
+    }
+  }
+
+  /// 用文件的流式响应体替换文件响应，内容类型根据扩展名确定（处理函数自己设置的 Content-Type 优先）
+  fn stream_file(&mut self, stream: BodyStream) {
+    let InnerResp::File(path) = std::mem::replace(&mut self.inner, InnerResp::Stream(stream))
+    else {
+      return;
+    };
+    if !self.has_custom_content_type() {
+      self.headers.get_or_insert_with(Vec::new).push((
+        "content-type".to_string(),
+        sniff::content_type_for_path(&path).to_string(),
+      ));
+    }
+  }
+
+  /// 按请求的 Range 头截取原始字节响应（sendBuffer）和文件响应（sendFile），只支持单个区间：
+  /// 区间有效时返回 206 和 Content-Range，超出范围时返回 416；其他响应类型和非 200 响应保持原样
+  /// 多区间或无法识别的 Range 头按 RFC 7233 忽略，返回完整响应体
+  /// 文件在这里打开后直接转为流式响应体，生成响应时不再重复打开
+  pub fn apply_byte_range(&mut self, range: Option<&str>) {
+    if !matches!(self.status_code, None | Some(200)) {
+      return;
+    }
+    let (file, total_size) = match &self.inner {
+      InnerResp::Raw(bytes) => (None, bytes.len() as u64),
+      InnerResp::File(path) => match open_file(path) {
+        Ok((file, length)) => (Some(file), length),
+        // 文件不存在时在生成响应时返回 404
+        Err(_) => return,
+      },
+      _ => return,
+    };
+    let headers = self.headers.get_or_insert_with(Vec::new);
+    if !headers
+      .iter()
+      .any(|(key, _)| key.eq_ignore_ascii_case("accept-ranges"))
+    {
+      headers.push(("accept-ranges".to_string(), "bytes".to_string()));
+    }
+
+    let range = match range {
+      Some(range) => resolve_byte_range(range, total_size),
+      None => Ok(None),
+    };
+    match range {
+      Ok(None) => {
+        // 完整发送文件，复用已经打开的句柄；转换失败时留给生成响应时重新打开并报告错误
+        if let Some(file) = file {
+          if let Ok(stream) = BodyStream::from_file(file, 0, total_size) {
+            self.stream_file(stream);
+          }
+        }
+      }
+      Ok(Some((start, end))) => {
+        match (&self.inner, file) {
+          (InnerResp::Raw(bytes), _) => {
+            self.inner = InnerResp::Raw(bytes.slice(start as usize..=end as usize))
+          }
+          (_, Some(file)) => match BodyStream::from_file(file, start, end - start + 1) {
+            Ok(stream) => self.stream_file(stream),
+            Err(_) => return,
+          },
+          _ => return,
+        }
+        self.headers.get_or_insert_with(Vec::new).push((
+          "content-range".to_string(),
+          format!("bytes {}-{}/{}", start, end, total_size),
+        ));
+        self.status_code = Some(206);
+      }
+      Err(_) => {
+        headers.push((
+          "content-range".to_string(),
+          format!("bytes */{}", total_size),
+        ));
+        self.inner = InnerResp::Raw(Bytes::new());
+        self.status_code = Some(416);
+      }
+    }
+  }
+
+  /// 检查自定义头部中是否包含 Content-Type
+  fn has_custom_content_type(&self) -> bool {
+    self.headers.as_ref().is_some_and(|headers| {
+      headers
+        .iter()
+        .any(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+    })
+  }
+
+  /// 把函数式处理函数的返回值转换为响应：字符串作为文本响应；
+  /// {status, headers, body} 对象中字符串 body 作为文本、其他 body 序列化为 JSON、缺省时为空响应
+  pub fn from_return_value(value: serde_json::Value) -> Self {
+    let mut object = match value {
+      serde_json::Value::String(text) => {
+        return JsResponse {
+          inner: InnerResp::Text(text),
+          status_code: None,
+          headers: None,
+        }
+      }
+      serde_json::Value::Object(object)
+        if ["status", "headers", "body"]
+          .iter()
+          .any(|key| object.contains_key(*key)) =>
+      {
+        object
+      }
+      // 返回 Promise 时得到的是空对象
+      _ => {
+        return JsResponse {
+          inner: InnerResp::ServerErrorWithMessage(
+            "处理函数必须同步返回字符串或 {status, headers, body} 对象".to_string(),
+          ),
+          status_code: None,
+          headers: None,
+        }
+      }
+    };
+
+    let status_code = match object.get("status") {
+      None | Some(serde_json::Value::Null) => None,
+      Some(status) => match status
+        .as_u64()
+        .and_then(|status| u16::try_from(status).ok())
+        .and_then(parse_status_code)
+      {
+        Some(status) => Some(status.as_u16()),
+        // 无效的状态码在这里报错，而不是在生成响应时被替换为 200
+        None => {
+          return JsResponse {
+            inner: InnerResp::ServerErrorWithMessage(format!(
+              "处理函数返回了无效的状态码 {}",
+              status
+            )),
+            status_code: None,
+            headers: None,
+          }
+        }
+      },
+    };
+    let headers = match object.remove("headers") {
+      Some(serde_json::Value::Object(headers)) => Some(
+        headers
+          .into_iter()
+          .map(|(name, value)| match value {
+            serde_json::Value::String(value) => (name, value),
+            value => (name, value.to_string()),
+          })
+          .collect(),
+      ),
+      _ => None,
+    };
+    let inner = match object.remove("body") {
+      None | Some(serde_json::Value::Null) => InnerResp::EmptyString,
+      Some(serde_json::Value::String(text)) => InnerResp::Text(text),
+      Some(body) => InnerResp::Json(body.to_string()),
+    };
+
+    JsResponse {
+      inner,
+      status_code,
+      headers,
+    }
+  }
+
   /// 获取状态码
   fn get_status_code(&self) -> StatusCode {
     match self.status_code {
@@ -79,3 +575,388 @@ impl JsResponse {
     }
   }
 }
+
+/// JSON 响应信封配置：把处理函数返回的 JSON 放到 data 字段下，并附加服务端生成的 meta
+pub struct JsonEnvelope {
+  pub data_key: String,
+  pub meta_key: String,
+}
+
+/// 单个请求使用的信封，携带请求 ID
+pub struct RequestEnvelope<'a> {
+  envelope: &'a JsonEnvelope,
+  request_id: String,
+}
+
+impl JsonEnvelope {
+  /// 为请求创建信封：优先沿用客户端传入的 X-Request-Id，否则生成新的 UUID
+  pub fn for_request(&self, req: &HttpRequest) -> RequestEnvelope<'_> {
+    let request_id = req
+      .headers()
+      .get("x-request-id")
+      .and_then(|value| value.to_str().ok())
+      .map(str::to_string)
+      .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    RequestEnvelope {
+      envelope: self,
+      request_id,
+    }
+  }
+}
+
+impl RequestEnvelope<'_> {
+  /// 🚀 直接拼接字符串包装 JSON 响应体，避免重新解析处理函数已经序列化好的 JSON
+  pub fn wrap(&self, json: String) -> String {
+    if json.trim().is_empty() {
+      return json;
+    }
+
+    let timestamp = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|elapsed| elapsed.as_millis() as u64)
+      .unwrap_or_default();
+    let meta = serde_json::json!({
+      "requestId": self.request_id,
+      "timestamp": timestamp,
+    });
+
+    format!(
+      "{{{}:{},{}:{}}}",
+      serde_json::Value::from(self.envelope.data_key.as_str()),
+      json,
+      serde_json::Value::from(self.envelope.meta_key.as_str()),
+      meta
+    )
+  }
+}
+
+/// HTTP/1.0 默认不复用连接，只有客户端显式发送 Connection: keep-alive 时才保持连接
+pub fn wants_connection_close(req: &HttpRequest) -> bool {
+  req.version() == Version::HTTP_10
+    && !req
+      .headers()
+      .get(CONNECTION)
+      .and_then(|value| value.to_str().ok())
+      .is_some_and(|value| {
+        value
+          .split(',')
+          .any(|token| token.trim().eq_ignore_ascii_case("keep-alive"))
+      })
+}
+
+/// 按 HTTP/1.0 语义收尾响应：响应体都是定长的，actix 会写出 Content-Length 而不是分块编码，
+/// 这里只需要在需要时声明 Connection: close，让连接在响应后关闭
+pub fn apply_connection_semantics(response: &mut HttpResponse, close: bool) {
+  if close {
+    response
+      .head_mut()
+      .set_connection_type(ConnectionType::Close);
+  }
+}
+
+/// 获取响应使用的内容编码（gzip / br 等），未设置 Content-Encoding 时为 identity
+/// 需要在所有中间件执行之后的最终响应上读取，才能反映压缩等中间件的结果
+pub fn response_content_encoding<B>(response: &HttpResponse<B>) -> &str {
+  response
+    .headers()
+    .get(CONTENT_ENCODING)
+    .and_then(|value| value.to_str().ok())
+    .unwrap_or("identity")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use actix_web::http::header::CONTENT_TYPE;
+
+  #[test]
+  fn test_envelope_wrap() {
+    let config = JsonEnvelope {
+      data_key: "data".to_string(),
+      meta_key: "meta".to_string(),
+    };
+    let envelope = RequestEnvelope {
+      envelope: &config,
+      request_id: "req-1".to_string(),
+    };
+
+    let wrapped: serde_json::Value =
+      serde_json::from_str(&envelope.wrap(r#"{"id":42}"#.to_string())).unwrap();
+    assert_eq!(wrapped["data"], serde_json::json!({ "id": 42 }));
+    assert_eq!(wrapped["meta"]["requestId"], "req-1");
+    assert!(wrapped["meta"]["timestamp"].is_u64());
+
+    // 在后处理器之前完成包装，错误响应保持原样
+    let mut success = JsResponse {
+      inner: InnerResp::Json(r#"{"id":42}"#.to_string()),
+      status_code: None,
+      headers: None,
+    };
+    success.apply_envelope(Some(&envelope));
+    assert!(
+      matches!(&success.inner, InnerResp::Json(json) if json.starts_with(r#"{"data":{"id":42}"#))
+    );
+
+    let mut failure = JsResponse {
+      inner: InnerResp::Json(r#"{"error":"gone"}"#.to_string()),
+      status_code: Some(410),
+      headers: None,
+    };
+    failure.apply_envelope(Some(&envelope));
+    assert!(matches!(&failure.inner, InnerResp::Json(json) if json == r#"{"error":"gone"}"#));
+  }
+
+  #[test]
+  fn test_exact_response_has_no_inferred_content_type() {
+    let response = JsResponse {
+      inner: InnerResp::Exact(Bytes::from_static(b"\x00\x01")),
+      status_code: Some(299),
+      headers: Some(vec![("x-protocol".to_string(), "v1".to_string())]),
+    }
+    .into_http_response();
+
+    assert_eq!(response.status().as_u16(), 299);
+    assert!(response.headers().get("content-type").is_none());
+    assert_eq!(response.headers().get("x-protocol").unwrap(), "v1");
+  }
+
+  #[test]
+  fn test_custom_content_type_overrides_default() {
+    let response = JsResponse {
+      inner: InnerResp::Text("a,b\n1,2\n".to_string()),
+      status_code: None,
+      headers: Some(vec![("Content-Type".to_string(), "text/csv".to_string())]),
+    }
+    .into_http_response();
+
+    let content_types: Vec<_> = response.headers().get_all(CONTENT_TYPE).collect();
+    assert_eq!(content_types, vec!["text/csv"]);
+  }
+
+  #[test]
+  fn test_apply_byte_range() {
+    let raw = || JsResponse {
+      inner: InnerResp::Raw(Bytes::from_static(b"0123456789")),
+      status_code: None,
+      headers: None,
+    };
+
+    let mut partial = raw();
+    partial.apply_byte_range(Some("bytes=2-5"));
+    let response = partial.into_http_response();
+    assert_eq!(response.status().as_u16(), 206);
+    assert_eq!(
+      response.headers().get("content-range").unwrap(),
+      "bytes 2-5/10"
+    );
+    assert_eq!(response.body().size(), BodySize::Sized(4));
+
+    let mut unsatisfiable = raw();
+    unsatisfiable.apply_byte_range(Some("bytes=20-"));
+    assert_eq!(unsatisfiable.status_code, Some(416));
+    assert_eq!(unsatisfiable.inner.body_len(), 0);
+
+    let mut full = raw();
+    full.apply_byte_range(Some("bytes=0-1,4-5"));
+    assert_eq!(full.status_code, None);
+    assert_eq!(full.inner.body_len(), 10);
+  }
+
+  #[test]
+  fn test_file_response() {
+    let path = std::env::temp_dir().join(format!("actix-js-send-file-{}.txt", Uuid::new_v4()));
+    std::fs::write(&path, "0123456789").unwrap();
+
+    let response = JsResponse {
+      inner: InnerResp::File(path.clone()),
+      status_code: None,
+      headers: None,
+    }
+    .into_http_response();
+    assert_eq!(response.body().size(), BodySize::Sized(10));
+    assert_eq!(
+      response.headers().get(CONTENT_TYPE).unwrap(),
+      "text/plain; charset=utf-8"
+    );
+
+    let mut partial = JsResponse {
+      inner: InnerResp::File(path.clone()),
+      status_code: None,
+      headers: None,
+    };
+    partial.apply_byte_range(Some("bytes=-4"));
+    let response = partial.into_http_response();
+    assert_eq!(response.status().as_u16(), 206);
+    assert_eq!(
+      response.headers().get("content-range").unwrap(),
+      "bytes 6-9/10"
+    );
+    assert_eq!(response.body().size(), BodySize::Sized(4));
+
+    // 没有 Range 头时文件在 apply_byte_range 中打开一次，直接转为完整的流式响应体
+    let mut whole = JsResponse {
+      inner: InnerResp::File(path.clone()),
+      status_code: None,
+      headers: None,
+    };
+    whole.apply_byte_range(None);
+    assert!(matches!(whole.inner, InnerResp::Stream(_)));
+    let response = whole.into_http_response();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.body().size(), BodySize::Sized(10));
+    assert_eq!(response.headers().get("accept-ranges").unwrap(), "bytes");
+
+    std::fs::remove_file(&path).unwrap();
+    let missing = JsResponse {
+      inner: InnerResp::File(path),
+      status_code: None,
+      headers: None,
+    }
+    .into_http_response();
+    assert_eq!(missing.status().as_u16(), 404);
+  }
+
+  #[test]
+  fn test_error_json_body() {
+    let body: serde_json::Value = serde_json::from_str(&error_json_body(
+      "USER_EXISTS".to_string(),
+      "用户已存在".to_string(),
+      Some(serde_json::json!({ "field": "email" })),
+    ))
+    .unwrap();
+    assert_eq!(
+      body,
+      serde_json::json!({ "error": "用户已存在", "code": "USER_EXISTS", "details": { "field": "email" } })
+    );
+
+    let without_details: serde_json::Value = serde_json::from_str(&error_json_body(
+      "NOT_FOUND".to_string(),
+      "\"quoted\"".to_string(),
+      None,
+    ))
+    .unwrap();
+    assert_eq!(
+      without_details,
+      serde_json::json!({ "error": "\"quoted\"", "code": "NOT_FOUND" })
+    );
+
+    assert!(parse_error_status_code(409).is_some());
+    assert!(parse_error_status_code(200).is_none());
+  }
+
+  #[test]
+  fn test_head_response_keeps_content_length() {
+    let response = JsResponse {
+      inner: InnerResp::Text("hello".to_string()),
+      status_code: None,
+      headers: None,
+    }
+    .into_head_response();
+
+    assert_eq!(response.body().size(), BodySize::Sized(5));
+    assert!(response.headers().contains_key(DATE));
+  }
+
+  #[test]
+  fn test_from_return_value() {
+    let response = JsResponse::from_return_value(serde_json::json!({
+      "status": 201,
+      "headers": { "x-id": "42" },
+      "body": { "id": 42 },
+    }));
+    assert_eq!(response.status_code, Some(201));
+    assert_eq!(
+      response.headers,
+      Some(vec![("x-id".to_string(), "42".to_string())])
+    );
+    assert!(matches!(response.inner, InnerResp::Json(ref json) if json == r#"{"id":42}"#));
+
+    let text = JsResponse::from_return_value(serde_json::json!("hello"));
+    assert!(matches!(text.inner, InnerResp::Text(ref body) if body == "hello"));
+
+    // 异步处理函数返回的 Promise 会被转换为空对象
+    let invalid = JsResponse::from_return_value(serde_json::json!({}));
+    assert!(matches!(
+      invalid.inner,
+      InnerResp::ServerErrorWithMessage(_)
+    ));
+
+    let invalid_status = JsResponse::from_return_value(serde_json::json!({ "status": 600 }));
+    assert!(matches!(
+      invalid_status.inner,
+      InnerResp::ServerErrorWithMessage(_)
+    ));
+  }
+
+  #[test]
+  fn test_resolve_byte_range() {
+    assert_eq!(resolve_byte_range("bytes=0-99", 1000), Ok(Some((0, 99))));
+    assert_eq!(resolve_byte_range("bytes=900-", 1000), Ok(Some((900, 999))));
+    assert_eq!(resolve_byte_range("bytes=-100", 1000), Ok(Some((900, 999))));
+    assert_eq!(
+      resolve_byte_range("bytes=500-5000", 1000),
+      Ok(Some((500, 999)))
+    );
+    assert_eq!(
+      resolve_byte_range("bytes=1000-", 1000),
+      Err(RangeNotSatisfiable)
+    );
+    assert_eq!(resolve_byte_range("bytes=0-1,5-6", 1000), Ok(None));
+    assert_eq!(resolve_byte_range("items=0-1", 1000), Ok(None));
+  }
+
+  #[test]
+  fn test_parse_status_code() {
+    assert_eq!(parse_status_code(201), Some(StatusCode::CREATED));
+    assert_eq!(parse_status_code(418), Some(StatusCode::IM_A_TEAPOT));
+    // 没有标准原因短语的状态码同样有效
+    assert_eq!(
+      parse_status_code(299).map(|status| status.as_u16()),
+      Some(299)
+    );
+    assert!(parse_status_code(599).is_some());
+    assert!(parse_status_code(600).is_none());
+    assert!(parse_status_code(99).is_none());
+  }
+
+  #[test]
+  fn test_date_header_on_all_variants() {
+    let path = std::env::temp_dir().join(format!("actix-js-date-{}.txt", Uuid::new_v4()));
+    std::fs::write(&path, "file").unwrap();
+    let chunks = || futures::stream::iter([Ok(Bytes::from_static(b"chunk"))]);
+
+    let variants = vec![
+      InnerResp::Text("ok".to_string()),
+      InnerResp::Json("{}".to_string()),
+      InnerResp::Raw(Bytes::from_static(b"raw")),
+      InnerResp::Exact(Bytes::from_static(b"exact")),
+      InnerResp::EmptyString,
+      InnerResp::ServerError,
+      InnerResp::ServerErrorWithMessage("boom".to_string()),
+      // 流式响应体分别走 SizedStream 和分块传输两条路径
+      InnerResp::Stream(BodyStream::new(Some(5), chunks())),
+      InnerResp::Stream(BodyStream::new(None, chunks())),
+      // 文件响应在生成时打开，文件不存在时返回 404
+      InnerResp::File(path.clone()),
+      InnerResp::File(path.with_extension("missing")),
+    ];
+
+    for inner in variants {
+      let response = JsResponse {
+        inner,
+        status_code: None,
+        headers: None,
+      }
+      .into_http_response();
+
+      let date = response.headers().get(DATE).unwrap().to_str().unwrap();
+      // RFC 7231 IMF-fixdate，例如 "Sun, 06 Nov 1994 08:49:37 GMT"
+      assert_eq!(date.len(), 29);
+      assert!(date.ends_with(" GMT"));
+      assert!(date.parse::<HttpDate>().is_ok());
+    }
+
+    std::fs::remove_file(&path).unwrap();
+  }
+}
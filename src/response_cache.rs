@@ -0,0 +1,111 @@
+use bytes::Bytes;
+use lru::LruCache;
+use napi::bindgen_prelude::*;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+use crate::response::parse_status_code;
+
+// 响应缓存：LRU 淘汰 + 每个条目独立的 TTL
+const RESPONSE_CACHE_CAPACITY: usize = 1024;
+
+#[derive(Clone)]
+pub struct CacheEntry {
+  pub status: u16,
+  pub headers: Vec<(String, String)>,
+  pub body: Bytes,
+  expires_at: Instant,
+}
+
+static RESPONSE_CACHE: LazyLock<Mutex<LruCache<String, CacheEntry>>> = LazyLock::new(|| {
+  Mutex::new(LruCache::new(
+    NonZeroUsize::new(RESPONSE_CACHE_CAPACITY).unwrap(),
+  ))
+});
+
+/// 读取缓存条目，过期条目会被顺带移除
+pub fn get_cached_response(key: &str) -> Option<CacheEntry> {
+  let mut cache = RESPONSE_CACHE.lock();
+  match cache.get(key) {
+    Some(entry) if entry.expires_at > Instant::now() => Some(entry.clone()),
+    Some(_) => {
+      cache.pop(key);
+      None
+    }
+    None => None,
+  }
+}
+
+/// 写入缓存条目，超出容量时淘汰最久未使用的条目
+pub fn put_cached_response(
+  key: String,
+  status: u16,
+  headers: Vec<(String, String)>,
+  body: Bytes,
+  ttl: Duration,
+) {
+  let entry = CacheEntry {
+    status,
+    headers,
+    body,
+    expires_at: Instant::now() + ttl,
+  };
+  RESPONSE_CACHE.lock().put(key, entry);
+}
+
+#[napi(object)]
+/// 缓存的响应
+pub struct CachedResponse {
+  pub status: u16,
+  pub headers: HashMap<String, String>,
+  pub body: Buffer,
+}
+
+#[napi]
+/// 读取响应缓存，未命中或已过期时返回 null
+pub fn cache_get(key: String) -> Option<CachedResponse> {
+  get_cached_response(&key).map(|entry| CachedResponse {
+    status: entry.status,
+    headers: entry.headers.into_iter().collect(),
+    body: entry.body.to_vec().into(),
+  })
+}
+
+#[napi]
+/// 写入响应缓存，ttlSecs 为 0 时不缓存
+pub fn cache_put(
+  key: String,
+  status: u16,
+  headers: HashMap<String, String>,
+  body: Buffer,
+  ttl_secs: u32,
+) -> Result<()> {
+  if parse_status_code(status).is_none() {
+    return Err(napi::Error::from_reason(format!(
+      "无效的状态码: {}",
+      status
+    )));
+  }
+
+  if ttl_secs == 0 {
+    return Ok(());
+  }
+
+  put_cached_response(
+    key,
+    status,
+    headers.into_iter().collect(),
+    Bytes::copy_from_slice(&body),
+    Duration::from_secs(ttl_secs as u64),
+  );
+  Ok(())
+}
+
+#[napi]
+/// 清空响应缓存
+pub fn cache_clear() {
+  RESPONSE_CACHE.lock().clear();
+}
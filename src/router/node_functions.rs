@@ -3,9 +3,18 @@ use napi::bindgen_prelude::*;
 use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
 use serde::Serialize;
 
-use crate::request::{DetachedRequestWrapper, RequestWrapper};
-use crate::router::read_only::clear_route_cache;
-use crate::router::store::{add_new_route, cleanup_route};
+use crate::in_flight::{in_flight_count, InFlightCallback};
+use crate::request::{DetachedRequestWrapper, RequestWrapper, SharedResponseSender};
+use crate::response::{InnerResp, JsResponse};
+use crate::router::read_only::{clear_route_cache, try_set_route_cache_capacity};
+use crate::router::store::{
+  add_new_route, add_route_name, cleanup_route, join_route_prefix, list_route_patterns,
+  url_for_route,
+};
+use crate::timing::DispatchTiming;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::oneshot;
 
 // 定义请求数据结构
 #[derive(Serialize)]
@@ -20,6 +29,45 @@ pub struct RequestData {
 use std::sync::Arc;
 pub type CallBackFunction = Arc<ThreadsafeFunction<DetachedRequestWrapper>>;
 pub type LegacyCallBackFunction = Arc<ThreadsafeFunction<RequestWrapper>>;
+/// 函数式处理函数：直接返回响应对象或字符串，而不是调用 send 系列方法
+pub type FunctionalCallBackFunction =
+  Arc<ThreadsafeFunction<DetachedRequestWrapper, serde_json::Value>>;
+/// before 钩子的返回值：异步钩子返回 Promise，同步钩子返回普通值（通常是 undefined）
+pub type HookReturn = Either<Promise<serde_json::Value>, serde_json::Value>;
+/// before 钩子：需要读取返回值，判断钩子何时执行完毕
+pub type HookCallBackFunction = Arc<ThreadsafeFunction<DetachedRequestWrapper, HookReturn>>;
+
+/// 路由对应的 JavaScript 处理函数
+#[derive(Clone)]
+pub enum RouteCallback {
+  /// 通过 send 系列方法发送响应
+  Handler(CallBackFunction),
+  /// 以返回值作为响应
+  Functional(FunctionalCallBackFunction),
+}
+
+/// 路由表中的条目：回调函数以及注册时使用的路由模式
+#[derive(Clone)]
+pub struct RouteEntry {
+  pub callback: RouteCallback,
+  pub pattern: Arc<str>,
+  /// 是否以流式方式处理 multipart 请求体（不缓冲、不落盘）
+  pub multipart_stream: bool,
+  /// 是否合并相同的并发请求
+  pub coalesce: bool,
+}
+
+#[napi(object)]
+/// 路由注册选项
+pub struct RouteOptions {
+  /// 路由名称，用于通过 urlFor 反向生成 URL，例如 "user.show"
+  pub name: Option<String>,
+  /// 合并相同的并发请求（方法、路径和查询字符串都相同），只调用一次处理函数并把结果共享给其余请求，默认 false
+  /// 适用于开销大、结果可缓存的 GET 路由，避免缓存失效时大量请求同时打到后端；流式响应无法共享，其余请求会各自调用处理函数
+  /// 只能用于 GET / HEAD 路由，其他方法设置为 true 时注册失败
+  /// 状态码和响应头（包括 Set-Cookie）会原样共享，不要用于按用户返回不同内容的路由
+  pub coalesce: Option<bool>,
+}
 
 #[napi]
 /// HTTP方法枚举
@@ -29,9 +77,22 @@ pub enum Methods {
   PUT,
   PATCH,
   DELETE,
+  OPTIONS,
+  HEAD,
 }
 
 impl Methods {
+  /// 支持的全部请求方法
+  pub const ALL: [Methods; 7] = [
+    Methods::GET,
+    Methods::POST,
+    Methods::PUT,
+    Methods::PATCH,
+    Methods::DELETE,
+    Methods::OPTIONS,
+    Methods::HEAD,
+  ];
+
   #[inline(always)]
   pub fn convert_from_str(method: &str) -> Option<Self> {
     match method {
@@ -40,31 +101,136 @@ impl Methods {
       "PUT" => Some(Methods::PUT),
       "PATCH" => Some(Methods::PATCH),
       "DELETE" => Some(Methods::DELETE),
+      "OPTIONS" => Some(Methods::OPTIONS),
+      "HEAD" => Some(Methods::HEAD),
       _ => None,
     }
   }
 
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Methods::GET => "GET",
+      Methods::POST => "POST",
+      Methods::PUT => "PUT",
+      Methods::PATCH => "PATCH",
+      Methods::DELETE => "DELETE",
+      Methods::OPTIONS => "OPTIONS",
+      Methods::HEAD => "HEAD",
+    }
+  }
+
   #[inline(always)]
+  /// 与 convert_from_str 使用同一个方法集合，CONNECT / TRACE 等方法返回 None
   pub fn convert_from_actix(method: Method) -> Option<Self> {
-    match method {
-      Method::GET => Some(Methods::GET),
-      Method::POST => Some(Methods::POST),
-      Method::PUT => Some(Methods::PUT),
-      Method::PATCH => Some(Methods::PATCH),
-      Method::DELETE => Some(Methods::DELETE),
-      _ => None,
-    }
+    Self::convert_from_str(method.as_str())
   }
 }
 
+/// 读取路由的合并选项：只有 GET / HEAD 这类安全方法可以合并，
+/// 其他方法合并后第二个请求的请求体和副作用会被悄悄丢弃，注册时直接拒绝
+fn route_coalesce(method: &Methods, options: Option<&RouteOptions>) -> Result<bool> {
+  let coalesce = options
+    .and_then(|options| options.coalesce)
+    .unwrap_or(false);
+  if coalesce && !matches!(method, Methods::GET | Methods::HEAD) {
+    return Err(napi::Error::from_reason(format!(
+      "coalesce 只能用于 GET / HEAD 路由，{} 请求不能合并",
+      method.as_str()
+    )));
+  }
+  Ok(coalesce)
+}
+
 #[napi]
 /// 注册新路由（异步版本）
 pub fn new_route_async(
   route: String,
   method: Methods,
   callback: ThreadsafeFunction<DetachedRequestWrapper>,
+  options: Option<RouteOptions>,
+) -> Result<()> {
+  let entry = RouteEntry {
+    callback: RouteCallback::Handler(Arc::new(callback)),
+    pattern: Arc::from(route.as_str()),
+    multipart_stream: false,
+    coalesce: route_coalesce(&method, options.as_ref())?,
+  };
+  add_new_route(&route, method, entry)?;
+
+  if let Some(name) = options.and_then(|options| options.name) {
+    add_route_name(name, &route)?;
+  }
+
+  Ok(())
+}
+
+#[napi(object, object_to_js = false)]
+/// 路由分组中的一条路由
+pub struct GroupRoute {
+  pub method: Methods,
+  /// 相对于分组前缀的路由模式，例如 "/users/:id"
+  pub pattern: String,
+  pub callback: ThreadsafeFunction<DetachedRequestWrapper>,
+  pub options: Option<RouteOptions>,
+}
+
+#[napi]
+/// 以同一个前缀批量注册路由，例如 addRouteGroup("/api/v1", [{ method: Methods.GET, pattern: "/users", callback }])
+/// 前缀与模式之间多余的斜杠会被合并；路由模式按 newRouteAsync 的规则校验，
+/// 某条路由注册失败时返回错误，排在它之前的路由保持已注册
+pub fn add_route_group(prefix: String, routes: Vec<GroupRoute>) -> Result<()> {
+  for route in routes {
+    new_route_async(
+      join_route_prefix(&prefix, &route.pattern),
+      route.method,
+      route.callback,
+      route.options,
+    )?;
+  }
+  Ok(())
+}
+
+#[napi]
+/// 注册流式 multipart 路由（异步版本）
+/// 请求体不会被缓冲或写入磁盘，处理函数通过 nextPartAsync / readPartChunkAsync 逐个读取 part
+pub fn new_route_multipart_stream_async(
+  route: String,
+  method: Methods,
+  callback: ThreadsafeFunction<DetachedRequestWrapper>,
 ) -> Result<()> {
-  add_new_route(&route, method, Arc::new(callback))
+  let entry = RouteEntry {
+    callback: RouteCallback::Handler(Arc::new(callback)),
+    pattern: Arc::from(route.as_str()),
+    multipart_stream: true,
+    coalesce: false,
+  };
+  add_new_route(&route, method, entry)
+}
+
+#[napi(
+  ts_args_type = "route: string, method: Methods, callback: (err: Error | null, arg: DetachedRequestWrapper) => FunctionalResponse | string, options?: RouteOptions | undefined | null"
+)]
+/// 注册函数式路由：处理函数同步返回 {status, headers, body} 对象或字符串，由 Rust 转换为响应，不需要调用 send 系列方法
+/// 返回值不是字符串或响应对象（包括返回 Promise）、或处理函数抛出异常时返回 500
+pub fn new_route_functional(
+  route: String,
+  method: Methods,
+  callback: ThreadsafeFunction<DetachedRequestWrapper, serde_json::Value>,
+  options: Option<RouteOptions>,
+) -> Result<()> {
+  let entry = RouteEntry {
+    callback: RouteCallback::Functional(Arc::new(callback)),
+    pattern: Arc::from(route.as_str()),
+    multipart_stream: false,
+    coalesce: route_coalesce(&method, options.as_ref())?,
+  };
+  add_new_route(&route, method, entry)?;
+
+  if let Some(name) = options.and_then(|options| options.name) {
+    add_route_name(name, &route)?;
+  }
+
+  Ok(())
 }
 
 #[napi]
@@ -81,9 +247,10 @@ pub fn new_route(
 }
 
 #[napi]
-/// 清理所有路由
+/// 清理所有路由，同时移除 setBeforeHook / setAfterHook 设置的全局钩子
 pub fn cleanup_router() -> Result<()> {
   cleanup_route();
+  crate::hooks::clear_hooks();
   Ok(())
 }
 
@@ -92,8 +259,9 @@ pub fn cleanup_router() -> Result<()> {
 pub fn get_async(
   route: String,
   callback: ThreadsafeFunction<DetachedRequestWrapper>,
+  options: Option<RouteOptions>,
 ) -> Result<()> {
-  new_route_async(route, Methods::GET, callback)
+  new_route_async(route, Methods::GET, callback, options)
 }
 
 #[napi]
@@ -101,8 +269,9 @@ pub fn get_async(
 pub fn post_async(
   route: String,
   callback: ThreadsafeFunction<DetachedRequestWrapper>,
+  options: Option<RouteOptions>,
 ) -> Result<()> {
-  new_route_async(route, Methods::POST, callback)
+  new_route_async(route, Methods::POST, callback, options)
 }
 
 #[napi]
@@ -110,8 +279,9 @@ pub fn post_async(
 pub fn put_async(
   route: String,
   callback: ThreadsafeFunction<DetachedRequestWrapper>,
+  options: Option<RouteOptions>,
 ) -> Result<()> {
-  new_route_async(route, Methods::PUT, callback)
+  new_route_async(route, Methods::PUT, callback, options)
 }
 
 #[napi]
@@ -119,8 +289,9 @@ pub fn put_async(
 pub fn patch_async(
   route: String,
   callback: ThreadsafeFunction<DetachedRequestWrapper>,
+  options: Option<RouteOptions>,
 ) -> Result<()> {
-  new_route_async(route, Methods::PATCH, callback)
+  new_route_async(route, Methods::PATCH, callback, options)
 }
 
 #[napi]
@@ -128,8 +299,30 @@ pub fn patch_async(
 pub fn del_async(
   route: String,
   callback: ThreadsafeFunction<DetachedRequestWrapper>,
+  options: Option<RouteOptions>,
+) -> Result<()> {
+  new_route_async(route, Methods::DELETE, callback, options)
+}
+
+#[napi]
+/// 注册OPTIONS路由（异步版本），例如应答 CORS 预检请求
+pub fn options_async(
+  route: String,
+  callback: ThreadsafeFunction<DetachedRequestWrapper>,
+  options: Option<RouteOptions>,
+) -> Result<()> {
+  new_route_async(route, Methods::OPTIONS, callback, options)
+}
+
+#[napi]
+/// 注册HEAD路由（异步版本）
+/// 没有注册 HEAD 路由时，HEAD 请求由同一路径的 GET 处理函数处理，发送时去掉响应体
+pub fn head_async(
+  route: String,
+  callback: ThreadsafeFunction<DetachedRequestWrapper>,
+  options: Option<RouteOptions>,
 ) -> Result<()> {
-  new_route_async(route, Methods::DELETE, callback)
+  new_route_async(route, Methods::HEAD, callback, options)
 }
 
 #[napi]
@@ -162,22 +355,144 @@ pub fn del(route: String, callback: ThreadsafeFunction<RequestWrapper>) -> Resul
   new_route(route, Methods::DELETE, callback)
 }
 
-/// 执行JavaScript回调函数（带DetachedRequestWrapper - 异步版本）
-pub fn execute_callback_with_detached_request(
-  callback: &CallBackFunction,
-  request_wrapper: DetachedRequestWrapper,
-) {
-  // 使用正确的API调用ThreadsafeFunction
-  match callback.call(Ok(request_wrapper), ThreadsafeFunctionCallMode::NonBlocking) {
+/// 成功加入回调队列的请求数
+static CALLBACKS_QUEUED: AtomicU64 = AtomicU64::new(0);
+/// 加入回调队列失败（队列已满或正在关闭）而被丢弃的请求数
+static CALLBACKS_DROPPED: AtomicU64 = AtomicU64::new(0);
+
+/// 把回调调用结果计入回调队列统计，返回是否成功入队
+fn track_call_status(status: napi::Status) -> bool {
+  match status {
     napi::Status::Ok => {
-      // 回调调用成功
+      CALLBACKS_QUEUED.fetch_add(1, Ordering::Relaxed);
+      true
     }
     status => {
+      CALLBACKS_DROPPED.fetch_add(1, Ordering::Relaxed);
       eprintln!("JavaScript回调调用失败，状态: {:?}", status);
+      false
     }
   }
 }
 
+fn call_mode(blocking: bool) -> ThreadsafeFunctionCallMode {
+  if blocking {
+    ThreadsafeFunctionCallMode::Blocking
+  } else {
+    ThreadsafeFunctionCallMode::NonBlocking
+  }
+}
+
+/// 执行JavaScript回调函数（带DetachedRequestWrapper - 异步版本）
+/// 返回请求是否成功加入回调队列；blocking 为 true 时队列已满会阻塞等待
+/// 处理函数被调用并返回（异步处理函数在第一个 await 处返回）时在 timing 中记录排队结束的时间
+/// 处理函数同步抛出异常且尚未发送响应时，立即通过 response_sender 返回 500，而不是等到超时
+pub fn execute_callback_with_detached_request(
+  callback: &CallBackFunction,
+  request_wrapper: DetachedRequestWrapper,
+  response_sender: SharedResponseSender,
+  blocking: bool,
+  timing: Arc<DispatchTiming>,
+) -> bool {
+  // 使用正确的API调用ThreadsafeFunction
+  let status = callback.call_with_return_value(
+    Ok(request_wrapper),
+    call_mode(blocking),
+    move |result, _| {
+      timing.mark_handler_entered();
+      if let Err(e) = result {
+        // 异常信息只写入服务端日志，客户端收到通用的 500，避免泄露内部细节
+        eprintln!("❌ 处理函数抛出异常: {}", e.reason);
+        if let Some(sender) = response_sender.lock().take() {
+          let _ = sender.send(JsResponse {
+            inner: InnerResp::ServerError,
+            status_code: None,
+            headers: None,
+          });
+        }
+      }
+      Ok(())
+    },
+  );
+  track_call_status(status)
+}
+
+/// 执行 before 钩子：钩子的返回值（或同步抛出的异常）通过返回的通道交给分发线程
+/// 没能进入回调队列时返回 None，与 execute_callback_with_detached_request 共用队列统计
+pub fn execute_hook_callback(
+  callback: &HookCallBackFunction,
+  request_wrapper: DetachedRequestWrapper,
+  blocking: bool,
+  timing: Arc<DispatchTiming>,
+) -> Option<oneshot::Receiver<Result<HookReturn>>> {
+  let (returned_tx, returned_rx) = oneshot::channel();
+  let status = callback.call_with_return_value(
+    Ok(request_wrapper),
+    call_mode(blocking),
+    move |result, _| {
+      timing.mark_handler_entered();
+      let _ = returned_tx.send(result);
+      Ok(())
+    },
+  );
+  track_call_status(status).then_some(returned_rx)
+}
+
+/// 执行函数式处理函数：返回值转换为响应后通过 response_sender 交给分发线程
+/// 返回请求是否成功加入回调队列，与 execute_callback_with_detached_request 共用队列统计
+pub fn execute_functional_callback(
+  callback: &FunctionalCallBackFunction,
+  request_wrapper: DetachedRequestWrapper,
+  response_sender: oneshot::Sender<JsResponse>,
+  blocking: bool,
+  timing: Arc<DispatchTiming>,
+  in_flight: InFlightCallback,
+) -> bool {
+  let status = callback.call_with_return_value(
+    Ok(request_wrapper),
+    call_mode(blocking),
+    move |result, _| {
+      timing.mark_handler_entered();
+      let response = match result {
+        Ok(value) => JsResponse::from_return_value(value),
+        Err(e) => {
+          eprintln!("❌ 处理函数抛出异常: {}", e.reason);
+          JsResponse {
+            inner: InnerResp::ServerError,
+            status_code: None,
+            headers: None,
+          }
+        }
+      };
+      let _ = response_sender.send(response);
+      drop(in_flight);
+      Ok(())
+    },
+  );
+  track_call_status(status)
+}
+
+#[napi(object)]
+/// 回调队列统计
+pub struct CallbackQueueStats {
+  /// 成功加入回调队列的请求数
+  pub queued: i64,
+  /// 加入回调队列失败、直接返回 503 的请求数
+  pub dropped: i64,
+  /// 已分发但尚未发送响应的回调数
+  pub in_flight: i64,
+}
+
+#[napi]
+/// 获取回调队列统计，用于观察高负载下是否有请求因队列已满被丢弃
+pub fn get_callback_queue_stats() -> CallbackQueueStats {
+  CallbackQueueStats {
+    queued: CALLBACKS_QUEUED.load(Ordering::Relaxed) as i64,
+    dropped: CALLBACKS_DROPPED.load(Ordering::Relaxed) as i64,
+    in_flight: in_flight_count() as i64,
+  }
+}
+
 /// 执行JavaScript回调函数（带RequestWrapper - 兼容旧版本）
 pub fn execute_callback_with_request(
   callback: &LegacyCallBackFunction,
@@ -206,6 +521,47 @@ pub fn execute_callback(
   // 这里我们不能创建假的RequestWrapper，所以暂时保持空实现
 }
 
+#[napi(object)]
+/// 已注册的路由
+pub struct RouteInfo {
+  /// 请求方法，例如 "GET"
+  pub method: String,
+  /// 注册时使用的路由模式，例如 "/users/:id"
+  pub pattern: String,
+}
+
+#[napi]
+/// 列出所有已注册的路由，可用于生成 OpenAPI 文档或调试；返回顺序不固定
+pub fn list_routes() -> Vec<RouteInfo> {
+  list_route_patterns()
+    .into_iter()
+    .map(|(method, pattern)| RouteInfo {
+      method: method.to_string(),
+      pattern,
+    })
+    .collect()
+}
+
+#[napi(ts_args_type = "name: string, params?: Record<string, string | number | boolean>")]
+/// 根据路由名称反向生成 URL，例如 urlFor("user.show", { id: 42 }) 返回 /users/42
+/// 名称未注册或缺少路径参数时抛出错误
+pub fn url_for(name: String, params: Option<HashMap<String, serde_json::Value>>) -> Result<String> {
+  let params = params
+    .unwrap_or_default()
+    .into_iter()
+    .map(|(key, value)| match value {
+      serde_json::Value::String(value) => Ok((key, value)),
+      serde_json::Value::Number(_) | serde_json::Value::Bool(_) => Ok((key, value.to_string())),
+      _ => Err(napi::Error::from_reason(format!(
+        "路径参数 {} 只能是字符串、数字或布尔值",
+        key
+      ))),
+    })
+    .collect::<Result<HashMap<String, String>>>()?;
+
+  url_for_route(&name, &params)
+}
+
 // 🚀 新增：LRU缓存管理接口
 
 #[napi]
@@ -214,3 +570,15 @@ pub fn clear_router_cache() -> Result<()> {
   clear_route_cache();
   Ok(())
 }
+
+#[napi]
+/// 设置每个请求方法的路由 LRU 缓存容量（默认 1000），0 表示禁用缓存，每次都直接进行路由匹配
+/// 必须在服务器启动前调用；缓存在第一次路由匹配时创建，创建后再调用会返回错误
+pub fn set_route_cache_capacity(capacity: u32) -> Result<()> {
+  if !try_set_route_cache_capacity(capacity as usize) {
+    return Err(napi::Error::from_reason(
+      "路由缓存已创建，setRouteCacheCapacity 必须在服务器启动前调用",
+    ));
+  }
+  Ok(())
+}
@@ -1,18 +1,20 @@
 use actix_web::http::Method;
 use halfbrown::HashMap;
 use lru::LruCache;
-use matchit::{Params, Router};
+use matchit::{MatchError, Params, Router};
 use parking_lot::Mutex;
 use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
 use std::{cell::UnsafeCell, mem::MaybeUninit};
 
-use crate::router::node_functions::CallBackFunction;
+use crate::router::node_functions::{Methods, RouteCallback, RouteEntry};
 
 struct RouteCell(UnsafeCell<MaybeUninit<ReadRoutes>>);
 
 unsafe impl Sync for RouteCell where ReadRoutes: Sync {}
 
-type ReaderLookup = Router<CallBackFunction>;
+type ReaderLookup = Router<RouteEntry>;
 static ROUTER: RouteCell = RouteCell(UnsafeCell::new(MaybeUninit::uninit()));
 
 pub struct ReadRoutes {
@@ -21,18 +23,21 @@ pub struct ReadRoutes {
   pub put: ReaderLookup,
   pub patch: ReaderLookup,
   pub delete: ReaderLookup,
+  pub options: ReaderLookup,
+  pub head: ReaderLookup,
 }
 
 impl ReadRoutes {
   #[inline(always)]
-  fn get_for_actix_method(&self, method: Method) -> Option<&ReaderLookup> {
+  fn get_for_method(&self, method: &Methods) -> &ReaderLookup {
     match method {
-      Method::GET => Some(&self.get),
-      Method::POST => Some(&self.post),
-      Method::PUT => Some(&self.put),
-      Method::PATCH => Some(&self.patch),
-      Method::DELETE => Some(&self.delete),
-      _ => None,
+      Methods::GET => &self.get,
+      Methods::POST => &self.post,
+      Methods::PUT => &self.put,
+      Methods::PATCH => &self.patch,
+      Methods::DELETE => &self.delete,
+      Methods::OPTIONS => &self.options,
+      Methods::HEAD => &self.head,
     }
   }
 }
@@ -47,9 +52,33 @@ fn get_routers() -> &'static ReadRoutes {
   unsafe { &*(*ROUTER.0.get()).as_ptr() }
 }
 
+/// 路由未命中的原因
+pub enum RouteMiss {
+  /// 增加或去掉末尾斜杠后可以匹配，携带修正后的路径
+  TrailingSlashRedirect(String),
+  NotFound,
+  /// 请求方法不在 Methods 支持的范围内，任何路由都无法注册该方法
+  MethodNotImplemented,
+}
+
+impl RouteMiss {
+  #[inline]
+  fn from_match_error(route: &str, error: MatchError) -> Self {
+    match error {
+      MatchError::MissingTrailingSlash => RouteMiss::TrailingSlashRedirect(format!("{}/", route)),
+      MatchError::ExtraTrailingSlash => {
+        RouteMiss::TrailingSlashRedirect(route[..route.len() - 1].to_string())
+      }
+      MatchError::NotFound => RouteMiss::NotFound,
+    }
+  }
+}
+
+// 缓存持有 RouteEntry 的克隆（内部是 Arc），而不是指向路由表的 &'static 引用，
+// 这样路由表被清理或替换后，缓存和正在处理的请求仍然持有各自的回调函数
 #[derive(Clone)]
 struct RouteCacheEntry {
-  callback: &'static CallBackFunction,
+  route: RouteEntry,
   params: std::collections::HashMap<String, String>,
 }
 
@@ -59,46 +88,41 @@ struct RouteCache {
   put_cache: Mutex<LruCache<String, RouteCacheEntry>>,
   patch_cache: Mutex<LruCache<String, RouteCacheEntry>>,
   delete_cache: Mutex<LruCache<String, RouteCacheEntry>>,
+  options_cache: Mutex<LruCache<String, RouteCacheEntry>>,
+  head_cache: Mutex<LruCache<String, RouteCacheEntry>>,
 }
 
 impl RouteCache {
-  fn new(capacity: usize) -> Self {
-    let cache_size = NonZeroUsize::new(capacity).unwrap();
+  fn new(cache_size: NonZeroUsize) -> Self {
     Self {
       get_cache: Mutex::new(LruCache::new(cache_size)),
       post_cache: Mutex::new(LruCache::new(cache_size)),
       put_cache: Mutex::new(LruCache::new(cache_size)),
       patch_cache: Mutex::new(LruCache::new(cache_size)),
       delete_cache: Mutex::new(LruCache::new(cache_size)),
+      options_cache: Mutex::new(LruCache::new(cache_size)),
+      head_cache: Mutex::new(LruCache::new(cache_size)),
     }
   }
 
-  fn get_cache_for_method(
-    &self,
-    method: &Method,
-  ) -> Option<&Mutex<LruCache<String, RouteCacheEntry>>> {
+  fn get_cache_for_method(&self, method: &Methods) -> &Mutex<LruCache<String, RouteCacheEntry>> {
     match method {
-      &Method::GET => Some(&self.get_cache),
-      &Method::POST => Some(&self.post_cache),
-      &Method::PUT => Some(&self.put_cache),
-      &Method::PATCH => Some(&self.patch_cache),
-      &Method::DELETE => Some(&self.delete_cache),
-      _ => None,
+      Methods::GET => &self.get_cache,
+      Methods::POST => &self.post_cache,
+      Methods::PUT => &self.put_cache,
+      Methods::PATCH => &self.patch_cache,
+      Methods::DELETE => &self.delete_cache,
+      Methods::OPTIONS => &self.options_cache,
+      Methods::HEAD => &self.head_cache,
     }
   }
 
-  fn get(&self, route: &str, method: &Method) -> Option<RouteCacheEntry> {
-    self
-      .get_cache_for_method(method)?
-      .lock()
-      .get(route)
-      .cloned()
+  fn get(&self, route: &str, method: &Methods) -> Option<RouteCacheEntry> {
+    self.get_cache_for_method(method).lock().get(route).cloned()
   }
 
-  fn put(&self, route: String, method: &Method, entry: RouteCacheEntry) {
-    if let Some(cache_mutex) = self.get_cache_for_method(method) {
-      cache_mutex.lock().put(route, entry);
-    }
+  fn put(&self, route: String, method: &Methods, entry: RouteCacheEntry) {
+    self.get_cache_for_method(method).lock().put(route, entry);
   }
 
   fn clear(&self) {
@@ -107,13 +131,33 @@ impl RouteCache {
     self.put_cache.lock().clear();
     self.patch_cache.lock().clear();
     self.delete_cache.lock().clear();
+    self.options_cache.lock().clear();
+    self.head_cache.lock().clear();
   }
 }
 
-static ROUTE_CACHE: std::sync::OnceLock<RouteCache> = std::sync::OnceLock::new();
+/// 每个请求方法的缓存容量默认值
+pub const DEFAULT_ROUTE_CACHE_CAPACITY: usize = 1000;
+
+static ROUTE_CACHE_CAPACITY: AtomicUsize = AtomicUsize::new(DEFAULT_ROUTE_CACHE_CAPACITY);
+/// 容量为 0 时为 None，所有查找直接交给 matchit
+static ROUTE_CACHE: OnceLock<Option<RouteCache>> = OnceLock::new();
 
-fn get_route_cache() -> &'static RouteCache {
-  ROUTE_CACHE.get_or_init(|| RouteCache::new(1000))
+/// 设置路由缓存容量，缓存在第一次路由匹配时按该容量创建；缓存已创建时返回 false
+pub fn try_set_route_cache_capacity(capacity: usize) -> bool {
+  if ROUTE_CACHE.get().is_some() {
+    return false;
+  }
+  ROUTE_CACHE_CAPACITY.store(capacity, Ordering::Relaxed);
+  true
+}
+
+fn get_route_cache() -> Option<&'static RouteCache> {
+  ROUTE_CACHE
+    .get_or_init(|| {
+      NonZeroUsize::new(ROUTE_CACHE_CAPACITY.load(Ordering::Relaxed)).map(RouteCache::new)
+    })
+    .as_ref()
 }
 
 // 🚀 LRU缓存优化的路由匹配函数 - 先查缓存，未命中再进行实际匹配
@@ -121,41 +165,60 @@ fn get_route_cache() -> &'static RouteCache {
 pub fn get_route_with_params_cached(
   route: &str,
   method: Method,
-) -> Option<(
-  &'static CallBackFunction,
-  std::collections::HashMap<String, String>,
-)> {
+) -> Result<(RouteEntry, std::collections::HashMap<String, String>), RouteMiss> {
+  // 与路由注册使用同一个方法集合，不支持的方法不会落到 404
+  let method = Methods::convert_from_actix(method).ok_or(RouteMiss::MethodNotImplemented)?;
   let cache = get_route_cache();
 
   // 🚀 第一步：尝试从缓存中获取
-  if let Some(cached_entry) = cache.get(route, &method) {
-    return Some((cached_entry.callback, cached_entry.params));
+  if let Some(cached_entry) = cache.and_then(|cache| cache.get(route, &method)) {
+    return Ok((cached_entry.route, cached_entry.params));
   }
 
   // 🚀 第二步：缓存未命中，进行实际路由匹配
-  let checking = get_routers().get_for_actix_method(method.clone())?;
-  let found = checking.at(route);
+  let found = get_routers().get_for_method(&method).at(route);
 
   match found {
     Ok(res) => {
       let std_params = params_to_std_map(&res.params);
 
       // 🚀 第三步：将匹配结果放入缓存（只缓存成功的匹配）
-      let cache_entry = RouteCacheEntry {
-        callback: res.value,
-        params: std_params.clone(),
-      };
-      cache.put(route.to_string(), &method, cache_entry);
-
-      Some((res.value, std_params))
+      if let Some(cache) = cache {
+        let cache_entry = RouteCacheEntry {
+          route: res.value.clone(),
+          params: std_params.clone(),
+        };
+        cache.put(route.to_string(), &method, cache_entry);
+      }
+
+      Ok((res.value.clone(), std_params))
     }
-    Err(_) => None, // 失败的匹配不缓存，避免缓存污染
+    // 失败的匹配不缓存，避免缓存污染；保留 matchit 的末尾斜杠建议供重定向策略使用
+    Err(error) => Err(RouteMiss::from_match_error(route, error)),
   }
 }
 
+/// 列出能够匹配该路径的请求方法，用于 405 响应的 Allow 头；auto_head 开启时 GET 路由同时应答 HEAD
+/// 只在路由未命中时调用，不经过缓存
+pub fn allowed_methods(route: &str, auto_head: bool) -> Vec<&'static str> {
+  let routers = get_routers();
+  let mut allowed: Vec<&'static str> = Methods::ALL
+    .iter()
+    .filter(|method| routers.get_for_method(method).at(route).is_ok())
+    .map(Methods::as_str)
+    .collect();
+  if auto_head && allowed.contains(&"GET") && !allowed.contains(&"HEAD") {
+    allowed.push("HEAD");
+  }
+  allowed
+}
+
 // 🚀 清理路由缓存的公共函数 - 在路由更新时调用
+// 缓存尚未创建时无需清理，也不在这里提前创建，以便注册路由后仍可调整容量
 pub fn clear_route_cache() {
-  get_route_cache().clear();
+  if let Some(Some(cache)) = ROUTE_CACHE.get() {
+    cache.clear();
+  }
 }
 
 // 🚀 修改现有函数使用缓存优化版本
@@ -163,21 +226,18 @@ pub fn clear_route_cache() {
 pub fn get_route_with_params(
   route: &str,
   method: Method,
-) -> Option<(
-  &'static CallBackFunction,
-  std::collections::HashMap<String, String>,
-)> {
+) -> Result<(RouteEntry, std::collections::HashMap<String, String>), RouteMiss> {
   // 使用缓存优化版本
   get_route_with_params_cached(route, method)
 }
 
 #[inline(always)]
-pub fn get_route(route: &str, method: Method) -> Option<&'static CallBackFunction> {
-  let checking = get_routers().get_for_actix_method(method)?;
-  let found = checking.at(route);
+pub fn get_route(route: &str, method: Method) -> Option<RouteCallback> {
+  let method = Methods::convert_from_actix(method)?;
+  let found = get_routers().get_for_method(&method).at(route);
 
   match found {
-    Ok(res) => Some(res.value),
+    Ok(res) => Some(res.value.callback.clone()),
     Err(_) => None,
   }
 }
@@ -206,8 +266,8 @@ fn params_to_std_map(params: &Params) -> std::collections::HashMap<String, Strin
 
 #[inline]
 pub fn get_params(route: &str, method: Method) -> Option<HashMap<String, String>> {
-  let checking = get_routers().get_for_actix_method(method)?;
-  let found = checking.at(route);
+  let method = Methods::convert_from_actix(method)?;
+  let found = get_routers().get_for_method(&method).at(route);
 
   match found {
     Ok(res) => Some(params_to_map(&res.params)),
@@ -2,12 +2,13 @@ use lazy_static::lazy_static;
 use matchit::Router;
 use napi::bindgen_prelude::*;
 use parking_lot::{Mutex, RwLock};
+use std::collections::HashMap;
 
 use super::read_only::{clear_route_cache, write_reader, ReadRoutes};
-use crate::router::node_functions::{CallBackFunction, Methods};
+use crate::router::node_functions::{Methods, RouteEntry};
 
-type ReaderLookup = Router<CallBackFunction>;
-type ThreadSafeLookup = RwLock<Router<CallBackFunction>>;
+type ReaderLookup = Router<RouteEntry>;
+type ThreadSafeLookup = RwLock<Router<RouteEntry>>;
 
 lazy_static! {
   static ref GLOBAL_DATA: Mutex<InternalRoutes> = {
@@ -27,6 +28,12 @@ struct InternalRoutes {
   put: ThreadSafeLookup,
   patch: ThreadSafeLookup,
   delete: ThreadSafeLookup,
+  options: ThreadSafeLookup,
+  head: ThreadSafeLookup,
+  // 路由名称 -> 路由模式，用于反向生成 URL
+  names: HashMap<String, String>,
+  // 请求方法 -> 已注册的路由模式；matchit::Router 不提供遍历接口，单独记录用于列出路由
+  patterns: HashMap<&'static str, Vec<String>>,
 }
 
 impl InternalRoutes {
@@ -37,6 +44,10 @@ impl InternalRoutes {
       put: RwLock::new(Router::new()),
       patch: RwLock::new(Router::new()),
       delete: RwLock::new(Router::new()),
+      options: RwLock::new(Router::new()),
+      head: RwLock::new(Router::new()),
+      names: HashMap::new(),
+      patterns: HashMap::new(),
     }
   }
 
@@ -47,6 +58,8 @@ impl InternalRoutes {
       Methods::PUT => &self.put,
       Methods::PATCH => &self.patch,
       Methods::DELETE => &self.delete,
+      Methods::OPTIONS => &self.options,
+      Methods::HEAD => &self.head,
     }
   }
 
@@ -57,6 +70,8 @@ impl InternalRoutes {
       put: thread_to_reader(&self.put),
       patch: thread_to_reader(&self.patch),
       delete: thread_to_reader(&self.delete),
+      options: thread_to_reader(&self.options),
+      head: thread_to_reader(&self.head),
     }
   }
 
@@ -66,6 +81,10 @@ impl InternalRoutes {
     self.put = RwLock::new(Router::new());
     self.patch = RwLock::new(Router::new());
     self.delete = RwLock::new(Router::new());
+    self.options = RwLock::new(Router::new());
+    self.head = RwLock::new(Router::new());
+    self.names.clear();
+    self.patterns.clear();
   }
 }
 
@@ -82,18 +101,167 @@ pub fn cleanup_route() {
   clear_route_cache();
 }
 
-pub fn add_new_route(route: &str, method: Methods, function: CallBackFunction) -> Result<()> {
-  let gd = GLOBAL_DATA.lock();
+pub fn add_new_route(route: &str, method: Methods, entry: RouteEntry) -> Result<()> {
+  let mut gd = GLOBAL_DATA.lock();
+  let method_name = method.as_str();
   let lock = gd.get_rw_from_method(method);
   let mut writing = lock.write();
 
   writing
-    .insert(route, function)
+    .insert(route, entry)
     .map_err(|_| Error::new(Status::GenericFailure, "Error inserting route".to_string()))?;
 
   drop(writing);
+  // matchit 拒绝重复的路由模式，插入成功的模式不会重复记录
+  gd.patterns
+    .entry(method_name)
+    .or_default()
+    .push(route.to_string());
   drop(gd);
   clear_route_cache();
 
   Ok(())
 }
+
+/// 登记路由名称；同一名称不能指向不同的路由模式
+pub fn add_route_name(name: String, route: &str) -> Result<()> {
+  let mut gd = GLOBAL_DATA.lock();
+
+  match gd.names.get(&name) {
+    Some(existing) if existing != route => Err(Error::new(
+      Status::InvalidArg,
+      format!("路由名称 {} 已被 {} 使用", name, existing),
+    )),
+    Some(_) => Ok(()),
+    None => {
+      gd.names.insert(name, route.to_string());
+      Ok(())
+    }
+  }
+}
+
+/// 列出所有已注册的 (请求方法, 路由模式)
+pub fn list_route_patterns() -> Vec<(&'static str, String)> {
+  GLOBAL_DATA
+    .lock()
+    .patterns
+    .iter()
+    .flat_map(|(method, patterns)| patterns.iter().map(|pattern| (*method, pattern.clone())))
+    .collect()
+}
+
+/// 根据路由名称和参数生成 URL
+pub fn url_for_route(name: &str, params: &HashMap<String, String>) -> Result<String> {
+  let pattern = GLOBAL_DATA
+    .lock()
+    .names
+    .get(name)
+    .cloned()
+    .ok_or_else(|| Error::new(Status::InvalidArg, format!("未找到名为 {} 的路由", name)))?;
+
+  build_url(&pattern, params).map_err(|e| Error::new(Status::InvalidArg, e))
+}
+
+/// 拼接路由分组前缀和路由模式，合并两者之间多余的斜杠："/api/v1/" + "/users" -> "/api/v1/users"
+/// 模式为空或只有 "/" 时返回前缀本身
+pub fn join_route_prefix(prefix: &str, pattern: &str) -> String {
+  let prefix = prefix.trim_end_matches('/');
+  let pattern = pattern.trim_start_matches('/');
+  match (prefix.is_empty(), pattern.is_empty()) {
+    (true, true) => "/".to_string(),
+    (false, true) => prefix.to_string(),
+    _ => format!("{}/{}", prefix, pattern),
+  }
+}
+
+/// 路由模式中通配参数（`*name`）的名称；matchit 只允许通配参数出现在模式末尾
+pub fn catch_all_param(pattern: &str) -> Option<&str> {
+  let start = pattern.rfind("/*")?;
+  Some(&pattern[start + 2..]).filter(|name| !name.is_empty())
+}
+
+/// 把参数代入 matchit 路由模式：`:name` 匹配单个路径段，`*name` 匹配剩余路径
+fn build_url(
+  pattern: &str,
+  params: &HashMap<String, String>,
+) -> std::result::Result<String, String> {
+  let mut url = String::with_capacity(pattern.len());
+  let mut rest = pattern;
+
+  while let Some(start) = rest.find([':', '*']) {
+    url.push_str(&rest[..start]);
+    let catch_all = rest[start..].starts_with('*');
+    let tail = &rest[start + 1..];
+    let end = tail.find('/').unwrap_or(tail.len());
+    let key = &tail[..end];
+
+    let value = params
+      .get(key)
+      .ok_or_else(|| format!("缺少路径参数 {}", key))?;
+    if catch_all {
+      // 通配参数本身可以包含 '/'，逐段编码
+      let segments: Vec<String> = value.split('/').map(encode_path_segment).collect();
+      url.push_str(&segments.join("/"));
+    } else {
+      url.push_str(&encode_path_segment(value));
+    }
+
+    rest = &tail[end..];
+  }
+
+  url.push_str(rest);
+  Ok(url)
+}
+
+/// 对路径段做百分号编码，保留 RFC 3986 中的非保留字符
+fn encode_path_segment(segment: &str) -> String {
+  let mut encoded = String::with_capacity(segment.len());
+  for byte in segment.bytes() {
+    match byte {
+      b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+        encoded.push(byte as char)
+      }
+      _ => encoded.push_str(&format!("%{:02X}", byte)),
+    }
+  }
+  encoded
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_build_url() {
+    let params: HashMap<String, String> = [("id", "42"), ("path", "docs/a b.md")]
+      .into_iter()
+      .map(|(k, v)| (k.to_string(), v.to_string()))
+      .collect();
+
+    assert_eq!(build_url("/users/:id", &params).unwrap(), "/users/42");
+    assert_eq!(
+      build_url("/users/:id/files/*path", &params).unwrap(),
+      "/users/42/files/docs/a%20b.md"
+    );
+    assert!(build_url("/posts/:slug", &params).is_err());
+  }
+
+  #[test]
+  fn test_catch_all_param() {
+    assert_eq!(catch_all_param("/static/*path"), Some("path"));
+    assert_eq!(catch_all_param("/users/:id/files/*rest"), Some("rest"));
+    assert_eq!(catch_all_param("/users/:id"), None);
+  }
+
+  #[test]
+  fn test_join_route_prefix() {
+    assert_eq!(join_route_prefix("/api/v1/", "/users"), "/api/v1/users");
+    assert_eq!(
+      join_route_prefix("/api/v1", "users/:id"),
+      "/api/v1/users/:id"
+    );
+    assert_eq!(join_route_prefix("/api/v1/", "/"), "/api/v1");
+    assert_eq!(join_route_prefix("/", "/users"), "/users");
+    assert_eq!(join_route_prefix("", ""), "/");
+  }
+}
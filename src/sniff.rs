@@ -0,0 +1,177 @@
+// 响应内容类型嗅探
+// 参照 WHATWG MIME Sniffing 标准中对未知类型的识别规则，只识别特征明确的格式，
+// 无法确定时保持 application/octet-stream，避免把二进制数据当作文本或 HTML 渲染
+
+use std::path::Path;
+
+/// 浏览器嗅探时检查的最大字节数
+const SNIFF_LEN: usize = 1445;
+
+/// 以固定字节序列开头的格式
+const MAGIC_SIGNATURES: [(&[u8], &str); 6] = [
+  (b"\x89PNG\r\n\x1a\n", "image/png"),
+  (b"\xff\xd8\xff", "image/jpeg"),
+  (b"GIF87a", "image/gif"),
+  (b"GIF89a", "image/gif"),
+  (b"%PDF-", "application/pdf"),
+  (b"\x00\x00\x01\x00", "image/x-icon"),
+];
+
+/// 识别为 HTML 的起始标签，后面必须紧跟空格或 >（不区分大小写）
+const HTML_TAGS: [&[u8]; 17] = [
+  b"<!DOCTYPE HTML",
+  b"<HTML",
+  b"<HEAD",
+  b"<SCRIPT",
+  b"<IFRAME",
+  b"<H1",
+  b"<DIV",
+  b"<FONT",
+  b"<TABLE",
+  b"<A",
+  b"<STYLE",
+  b"<TITLE",
+  b"<B",
+  b"<BODY",
+  b"<BR",
+  b"<P",
+  b"<!--",
+];
+
+/// 常见文件扩展名对应的内容类型，用于 sendFile
+const EXTENSION_TYPES: [(&str, &str); 26] = [
+  ("html", "text/html; charset=utf-8"),
+  ("htm", "text/html; charset=utf-8"),
+  ("css", "text/css; charset=utf-8"),
+  ("js", "text/javascript; charset=utf-8"),
+  ("mjs", "text/javascript; charset=utf-8"),
+  ("json", "application/json; charset=utf-8"),
+  ("txt", "text/plain; charset=utf-8"),
+  ("csv", "text/csv; charset=utf-8"),
+  ("xml", "application/xml"),
+  ("svg", "image/svg+xml"),
+  ("png", "image/png"),
+  ("jpg", "image/jpeg"),
+  ("jpeg", "image/jpeg"),
+  ("gif", "image/gif"),
+  ("webp", "image/webp"),
+  ("ico", "image/x-icon"),
+  ("pdf", "application/pdf"),
+  ("zip", "application/zip"),
+  ("gz", "application/gzip"),
+  ("wasm", "application/wasm"),
+  ("woff", "font/woff"),
+  ("woff2", "font/woff2"),
+  ("mp3", "audio/mpeg"),
+  ("mp4", "video/mp4"),
+  ("webm", "video/webm"),
+  ("wav", "audio/wav"),
+];
+
+/// 根据文件扩展名（不区分大小写）确定内容类型，无法识别时返回 application/octet-stream
+pub fn content_type_for_path(path: &Path) -> &'static str {
+  path
+    .extension()
+    .and_then(|extension| extension.to_str())
+    .and_then(|extension| {
+      EXTENSION_TYPES
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(extension))
+    })
+    .map(|(_, mime)| *mime)
+    .unwrap_or("application/octet-stream")
+}
+
+/// 推断响应体的内容类型，无法识别时返回 None
+pub fn sniff_content_type(body: &[u8]) -> Option<&'static str> {
+  let head = &body[..body.len().min(SNIFF_LEN)];
+
+  if let Some((_, mime)) = MAGIC_SIGNATURES
+    .iter()
+    .find(|(signature, _)| head.starts_with(signature))
+  {
+    return Some(mime);
+  }
+  if head.len() >= 12 && head.starts_with(b"RIFF") && &head[8..12] == b"WEBP" {
+    return Some("image/webp");
+  }
+
+  // 文本类格式要求整个响应体是合法的 UTF-8，且不含二进制控制字符
+  if std::str::from_utf8(body).is_err() || head.iter().copied().any(is_binary_byte) {
+    return None;
+  }
+
+  let trimmed = head.trim_ascii_start();
+  if is_html(trimmed) {
+    return Some("text/html; charset=utf-8");
+  }
+  if matches!(trimmed.first(), Some(b'{' | b'['))
+    && serde_json::from_slice::<serde::de::IgnoredAny>(body).is_ok()
+  {
+    return Some("application/json; charset=utf-8");
+  }
+  Some("text/plain; charset=utf-8")
+}
+
+fn is_html(trimmed: &[u8]) -> bool {
+  HTML_TAGS.iter().any(|tag| {
+    trimmed.len() > tag.len()
+      && trimmed[..tag.len()].eq_ignore_ascii_case(tag)
+      // 注释只需要匹配开头
+      && (tag == b"<!--" || matches!(trimmed[tag.len()], b' ' | b'>'))
+  })
+}
+
+/// WHATWG 定义的二进制数据字节
+fn is_binary_byte(byte: u8) -> bool {
+  matches!(byte, 0x00..=0x08 | 0x0B | 0x0E..=0x1A | 0x1C..=0x1F)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_sniff_content_type() {
+    assert_eq!(
+      sniff_content_type(b"\x89PNG\r\n\x1a\n\x00\x00"),
+      Some("image/png")
+    );
+    assert_eq!(sniff_content_type(b"%PDF-1.7\n"), Some("application/pdf"));
+    assert_eq!(
+      sniff_content_type(b"  <!doctype html><html></html>"),
+      Some("text/html; charset=utf-8")
+    );
+    assert_eq!(
+      sniff_content_type(br#"{"ok": true}"#),
+      Some("application/json; charset=utf-8")
+    );
+    assert_eq!(
+      sniff_content_type(b"hello world"),
+      Some("text/plain; charset=utf-8")
+    );
+    // 看起来像标签但不是 HTML 起始标签时按纯文本处理
+    assert_eq!(
+      sniff_content_type(b"<api-version>"),
+      Some("text/plain; charset=utf-8")
+    );
+    assert_eq!(sniff_content_type(b"\x00\x01\x02binary"), None);
+  }
+
+  #[test]
+  fn test_content_type_for_path() {
+    assert_eq!(content_type_for_path(Path::new("movie.MP4")), "video/mp4");
+    assert_eq!(
+      content_type_for_path(Path::new("/srv/www/index.html")),
+      "text/html; charset=utf-8"
+    );
+    assert_eq!(
+      content_type_for_path(Path::new("archive.tar.unknown")),
+      "application/octet-stream"
+    );
+    assert_eq!(
+      content_type_for_path(Path::new("README")),
+      "application/octet-stream"
+    );
+  }
+}
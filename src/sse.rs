@@ -0,0 +1,186 @@
+use bytes::Bytes;
+use futures::StreamExt;
+use std::sync::LazyLock;
+use tokio::sync::{broadcast, mpsc};
+
+use crate::response::BodyStream;
+
+// Rust 侧 SSE 广播模块
+// 所有通过 sendSseStreamAsync 建立的 SSE 连接都订阅同一个广播通道，
+// broadcastSseEvent 把事件扇出给每个连接；跟不上的连接会被断开，不会拖慢广播
+// startSseAsync 建立的连接则各自持有一个通道，由返回的 SseWriter 单独推送
+
+/// 每个连接最多积压的事件数，超过后该连接被视为慢消费者并断开
+const SSE_CHANNEL_CAPACITY: usize = 256;
+
+static SSE_BROADCAST: LazyLock<broadcast::Sender<Bytes>> =
+  LazyLock::new(|| broadcast::channel(SSE_CHANNEL_CAPACITY).0);
+
+/// 按 SSE 格式编码一个事件，多行数据拆分为多个 data 字段
+fn format_event(event: Option<&str>, data: &str) -> Bytes {
+  let mut frame = String::with_capacity(data.len() + 32);
+  if let Some(event) = event.filter(|event| !event.is_empty()) {
+    // 事件名不能包含换行，否则会破坏帧格式
+    frame.push_str("event: ");
+    frame.push_str(&event.replace(['\r', '\n'], ""));
+    frame.push('\n');
+  }
+  for line in data.lines() {
+    frame.push_str("data: ");
+    frame.push_str(line);
+    frame.push('\n');
+  }
+  if data.is_empty() {
+    frame.push_str("data: \n");
+  }
+  frame.push('\n');
+  Bytes::from(frame)
+}
+
+/// SSE 响应的头部
+pub fn sse_headers() -> [(String, String); 3] {
+  [
+    ("content-type".to_string(), "text/event-stream".to_string()),
+    ("cache-control".to_string(), "no-cache".to_string()),
+    // 禁止 nginx 等反向代理缓冲事件流
+    ("x-accel-buffering".to_string(), "no".to_string()),
+  ]
+}
+
+/// 订阅广播通道，返回 SSE 响应体流
+pub fn subscribe() -> BodyStream {
+  let receiver = SSE_BROADCAST.subscribe();
+  // 先发送一条注释，让客户端立即收到响应头
+  let connected = futures::stream::once(async { Ok(Bytes::from_static(b": connected\n\n")) });
+
+  let events = futures::stream::unfold(receiver, |mut receiver| async move {
+    match receiver.recv().await {
+      Ok(frame) => Some((Ok(frame), receiver)),
+      Err(broadcast::error::RecvError::Lagged(skipped)) => {
+        eprintln!("警告：SSE 连接积压了 {} 个事件，已断开", skipped);
+        None
+      }
+      Err(broadcast::error::RecvError::Closed) => None,
+    }
+  });
+
+  BodyStream::new(None, connected.chain(events))
+}
+
+/// 为单个连接创建事件通道，返回写入端和 SSE 响应体流
+pub fn channel() -> (SseWriter, BodyStream) {
+  let (sender, receiver) = mpsc::channel(SSE_CHANNEL_CAPACITY);
+  let connected = futures::stream::once(async { Ok(Bytes::from_static(b": connected\n\n")) });
+  // 每个事件到达后立即作为一个块写出，不等待关闭
+  let events = futures::stream::unfold(receiver, |mut receiver| async move {
+    receiver.recv().await.map(|frame| (Ok(frame), receiver))
+  });
+
+  (
+    SseWriter {
+      sender: Some(sender),
+    },
+    BodyStream::new(None, connected.chain(events)),
+  )
+}
+
+#[napi]
+/// 单个 SSE 连接的写入端，由 startSseAsync 返回
+pub struct SseWriter {
+  sender: Option<mpsc::Sender<Bytes>>,
+}
+
+#[napi]
+impl SseWriter {
+  #[napi]
+  /// 发送一条只有 data 的事件，多行数据会拆分为多个 data 字段
+  /// 连接已关闭（客户端断开或已调用 close）时返回 false；积压超过 256 个事件时连接被断开并返回 false
+  pub fn write(&mut self, data: String) -> bool {
+    self.send(format_event(None, &data))
+  }
+
+  #[napi]
+  /// 发送一条带事件名的事件，返回值与 write 相同
+  pub fn write_event(&mut self, event: String, data: String) -> bool {
+    self.send(format_event(Some(&event), &data))
+  }
+
+  #[napi]
+  /// 结束事件流，已发送的事件仍会写出；可以重复调用
+  pub fn close(&mut self) {
+    self.sender = None;
+  }
+
+  #[napi]
+  /// 连接是否仍然打开
+  pub fn is_open(&self) -> bool {
+    self
+      .sender
+      .as_ref()
+      .is_some_and(|sender| !sender.is_closed())
+  }
+
+  fn send(&mut self, frame: Bytes) -> bool {
+    let Some(sender) = &self.sender else {
+      return false;
+    };
+    match sender.try_send(frame) {
+      Ok(()) => true,
+      Err(mpsc::error::TrySendError::Full(_)) => {
+        eprintln!(
+          "警告：SSE 连接积压了 {} 个事件，已断开",
+          SSE_CHANNEL_CAPACITY
+        );
+        self.sender = None;
+        false
+      }
+      Err(mpsc::error::TrySendError::Closed(_)) => {
+        self.sender = None;
+        false
+      }
+    }
+  }
+}
+
+#[napi]
+/// 向所有通过 sendSseStreamAsync 建立的 SSE 连接广播一个事件，返回当前连接数
+/// event 为空字符串时只发送 data；积压超过 256 个事件的慢连接会被断开
+pub fn broadcast_sse_event(event: String, data: String) -> u32 {
+  // 没有订阅者时 send 返回错误，此时事件直接丢弃
+  SSE_BROADCAST
+    .send(format_event(Some(&event), &data))
+    .unwrap_or(0) as u32
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_sse_writer() {
+    let (mut writer, _body) = channel();
+    assert!(writer.is_open());
+    assert!(writer.write("hello".to_string()));
+
+    writer.close();
+    assert!(!writer.is_open());
+    assert!(!writer.write("after close".to_string()));
+
+    // 响应体被丢弃（客户端断开）后写入失败
+    let (mut writer, body) = channel();
+    drop(body);
+    assert!(!writer.write_event("ping".to_string(), String::new()));
+  }
+
+  #[test]
+  fn test_format_event() {
+    assert_eq!(
+      format_event(Some("health"), "{\"ok\":true}"),
+      Bytes::from_static(b"event: health\ndata: {\"ok\":true}\n\n")
+    );
+    assert_eq!(
+      format_event(None, "line1\nline2"),
+      Bytes::from_static(b"data: line1\ndata: line2\n\n")
+    );
+  }
+}
@@ -0,0 +1,154 @@
+use bytes::Bytes;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::response::{InnerResp, JsResponse};
+
+// 流式响应逐块转换
+// 流式响应（例如反向代理转发的上游响应）的每个块在写出之前依次经过路由注册的转换，
+// 转换在 Rust 侧执行，避免每个块都经过一次 JavaScript 调用
+
+/// 块转换，每个响应各自持有一个实例，可以在块之间保存状态（例如当前行号）
+pub type ChunkTransform = Box<dyn FnMut(Bytes) -> Bytes + Send>;
+/// 为每个流式响应创建一个新的块转换
+pub type ChunkTransformFactory = Arc<dyn Fn() -> ChunkTransform + Send + Sync>;
+
+/// 按注册时的路由模式（例如 "/logs/:id"）索引
+static CHUNK_TRANSFORMS: LazyLock<RwLock<HashMap<String, Vec<ChunkTransformFactory>>>> =
+  LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// 为路由注册块转换，多个转换按注册顺序依次执行
+pub fn register_chunk_transform(route: &str, factory: ChunkTransformFactory) {
+  CHUNK_TRANSFORMS
+    .write()
+    .entry(route.to_string())
+    .or_default()
+    .push(factory);
+}
+
+/// 对 route_pattern 的流式响应应用已注册的块转换，非流式响应不受影响
+/// text/event-stream 响应同样跳过：行前缀会破坏 SSE 的 `field: value` 帧格式
+pub fn apply_chunk_transforms(route_pattern: &str, response: &mut JsResponse) {
+  if !matches!(response.inner, InnerResp::Stream(_)) || is_event_stream(response) {
+    return;
+  }
+  let transforms = CHUNK_TRANSFORMS.read();
+  let Some(factories) = transforms.get(route_pattern) else {
+    return;
+  };
+
+  let InnerResp::Stream(mut body) = std::mem::replace(&mut response.inner, InnerResp::EmptyString)
+  else {
+    unreachable!()
+  };
+  for factory in factories {
+    body = body.map_chunks(factory());
+  }
+  response.inner = InnerResp::Stream(body);
+}
+
+fn is_event_stream(response: &JsResponse) -> bool {
+  response.headers.as_ref().is_some_and(|headers| {
+    headers.iter().any(|(name, value)| {
+      name.eq_ignore_ascii_case("content-type")
+        && value
+          .trim_start()
+          .to_ascii_lowercase()
+          .starts_with("text/event-stream")
+    })
+  })
+}
+
+/// 在每一行的开头插入 prefix 生成的前缀，行可以跨越多个块
+fn prefix_lines(mut prefix: impl FnMut() -> String + Send + 'static) -> ChunkTransform {
+  let mut at_line_start = true;
+  Box::new(move |chunk: Bytes| {
+    let mut transformed = Vec::with_capacity(chunk.len() + 16);
+    for line in chunk.split_inclusive(|byte| *byte == b'\n') {
+      if at_line_start {
+        transformed.extend_from_slice(prefix().as_bytes());
+      }
+      transformed.extend_from_slice(line);
+      at_line_start = line.ends_with(b"\n");
+    }
+    Bytes::from(transformed)
+  })
+}
+
+#[napi]
+/// 为路由的流式响应（例如 forwardAsync 转发的日志）逐行添加行号前缀，例如 "1 "；行号按响应从 1 开始计数
+/// route 为路由注册时的模式（例如 "/logs/:id"）；转换后的响应改为分块传输，text/event-stream 响应不做转换
+pub fn add_stream_line_numbers(route: String) {
+  register_chunk_transform(
+    &route,
+    Arc::new(|| {
+      let mut line_number = 0u64;
+      prefix_lines(move || {
+        line_number += 1;
+        format!("{} ", line_number)
+      })
+    }),
+  );
+}
+
+#[napi]
+/// 为路由的流式响应逐行添加时间戳前缀，例如 "[1700000000000] "，时间戳为该行开始写出时的 Unix 毫秒时间戳
+/// route 为路由注册时的模式（例如 "/logs/:id"）；转换后的响应改为分块传输，text/event-stream 响应不做转换
+pub fn add_stream_timestamps(route: String) {
+  register_chunk_transform(
+    &route,
+    Arc::new(|| {
+      prefix_lines(|| {
+        let now = SystemTime::now()
+          .duration_since(UNIX_EPOCH)
+          .map(|elapsed| elapsed.as_millis())
+          .unwrap_or_default();
+        format!("[{}] ", now)
+      })
+    }),
+  );
+}
+
+#[napi]
+/// 移除所有已注册的流式响应块转换
+pub fn clear_stream_transforms() {
+  CHUNK_TRANSFORMS.write().clear();
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_prefix_lines_across_chunks() {
+    let mut line_number = 0;
+    let mut transform = prefix_lines(move || {
+      line_number += 1;
+      format!("{} ", line_number)
+    });
+
+    assert_eq!(transform(Bytes::from("first\nsec")), "1 first\n2 sec");
+    // 上一块没有以换行结束，这一块的开头属于同一行
+    assert_eq!(transform(Bytes::from("ond\n")), "ond\n");
+    assert_eq!(transform(Bytes::new()), "");
+    assert_eq!(transform(Bytes::from("third\n")), "3 third\n");
+  }
+
+  #[test]
+  fn test_event_stream_is_not_transformed() {
+    let mut response = JsResponse {
+      inner: InnerResp::EmptyString,
+      status_code: None,
+      headers: Some(vec![(
+        "Content-Type".to_string(),
+        "text/event-stream; charset=utf-8".to_string(),
+      )]),
+    };
+    assert!(is_event_stream(&response));
+
+    response.headers = Some(vec![("content-type".to_string(), "text/plain".to_string())]);
+    assert!(!is_event_stream(&response));
+  }
+}
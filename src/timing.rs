@@ -0,0 +1,93 @@
+use actix_web::HttpResponse;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// 请求耗时拆分
+// 回调通过 NonBlocking 的 ThreadsafeFunction 排队等待 Node 事件循环执行，
+// 这里分别记录排队等待和处理函数执行的时间，便于判断延迟是在队列中还是在处理函数里
+
+/// 一次回调分发的时间点，分发线程和 JavaScript 线程共享
+pub struct DispatchTiming {
+  dispatched_at: Instant,
+  /// 处理函数被调用时距分发的纳秒数，0 表示尚未调用
+  handler_entered_nanos: AtomicU64,
+}
+
+impl DispatchTiming {
+  /// 在把回调放入队列之前创建
+  pub fn start() -> Arc<Self> {
+    Arc::new(Self {
+      dispatched_at: Instant::now(),
+      handler_entered_nanos: AtomicU64::new(0),
+    })
+  }
+
+  /// 记录处理函数开始运行的时间，只有第一次调用生效
+  /// 由回调返回时的 JavaScript 线程或第一次发送响应时调用，以先到者为准
+  pub fn mark_handler_entered(&self) {
+    let nanos = (self.dispatched_at.elapsed().as_nanos() as u64).max(1);
+    let _ =
+      self
+        .handler_entered_nanos
+        .compare_exchange(0, nanos, Ordering::Relaxed, Ordering::Relaxed);
+  }
+
+  /// 排队等待时间，处理函数还没有被调用时返回 None
+  pub fn queue_wait(&self) -> Option<Duration> {
+    match self.handler_entered_nanos.load(Ordering::Relaxed) {
+      0 => None,
+      nanos => Some(Duration::from_nanos(nanos)),
+    }
+  }
+
+  /// 把排队等待时间和处理函数耗时附加到响应上，供慢请求日志和访问日志读取
+  pub fn attach_to(&self, response: &mut HttpResponse) {
+    if let Some(queue_wait) = self.queue_wait() {
+      response.extensions_mut().insert(HandlerTiming {
+        queue_wait,
+        handler: self.dispatched_at.elapsed().saturating_sub(queue_wait),
+      });
+    }
+  }
+}
+
+/// 附加在响应扩展中的耗时拆分
+#[derive(Clone, Copy)]
+pub struct HandlerTiming {
+  /// 从放入回调队列到 JavaScript 处理函数开始运行
+  pub queue_wait: Duration,
+  /// 从处理函数开始运行到响应就绪
+  pub handler: Duration,
+}
+
+impl HandlerTiming {
+  pub fn from_response<B>(response: &HttpResponse<B>) -> Option<Self> {
+    response.extensions().get::<HandlerTiming>().copied()
+  }
+
+  pub fn queue_ms(&self) -> f64 {
+    self.queue_wait.as_secs_f64() * 1000.0
+  }
+
+  pub fn handler_ms(&self) -> f64 {
+    self.handler.as_secs_f64() * 1000.0
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_handler_entry_recorded_once() {
+    let timing = DispatchTiming::start();
+    assert!(timing.queue_wait().is_none());
+
+    timing.mark_handler_entered();
+    let first = timing.queue_wait().unwrap();
+    std::thread::sleep(Duration::from_millis(2));
+    timing.mark_handler_entered();
+    assert_eq!(timing.queue_wait(), Some(first));
+  }
+}
@@ -0,0 +1,201 @@
+use napi::bindgen_prelude::*;
+use parking_lot::RwLock;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// 上传文件目录管理
+// multipart 上传的文件以 UUID 文件名保存在上传目录中，没有自动清理，
+// 这里提供列出和按修改时间清理的接口，供运维回收磁盘空间
+
+/// 默认的上传文件保存目录（相对于进程工作目录）
+pub const DEFAULT_UPLOAD_DIR: &str = "static";
+
+/// 通过 setUploadDir 设置的上传目录，未设置时使用 DEFAULT_UPLOAD_DIR
+static UPLOAD_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// 当前的上传文件保存目录
+pub fn upload_dir() -> PathBuf {
+  UPLOAD_DIR
+    .read()
+    .clone()
+    .unwrap_or_else(|| PathBuf::from(DEFAULT_UPLOAD_DIR))
+}
+
+#[napi]
+/// 设置 multipart 上传文件的保存目录（默认为工作目录下的 static），相对路径相对于进程工作目录
+/// 目录在保存文件时按需创建；无法创建时对应的文件字段返回 { type: "error", originalName, error }
+pub fn set_upload_dir(path: String) -> Result<()> {
+  if path.trim().is_empty() {
+    return Err(Error::from_reason("上传目录不能为空"));
+  }
+  *UPLOAD_DIR.write() = Some(PathBuf::from(path));
+  Ok(())
+}
+
+/// 为 true 时 multipart 文件字段不写入磁盘，内容以 base64 放在文件信息的 data 字段中
+static MULTIPART_IN_MEMORY: AtomicBool = AtomicBool::new(false);
+
+/// 是否以内存模式处理 multipart 文件字段
+pub fn multipart_in_memory() -> bool {
+  MULTIPART_IN_MEMORY.load(Ordering::Relaxed)
+}
+
+#[napi]
+/// 设置 multipart 文件字段是否只保存在内存中（默认 false，写入上传目录）
+/// 开启后文件信息不包含 path，文件内容以 base64 放在 data 字段中，由处理函数自行决定保存到哪里；
+/// 适用于 serverless 等没有持久磁盘的环境，注意整个文件会随请求一起保存在内存中
+pub fn set_multipart_in_memory(enabled: bool) {
+  MULTIPART_IN_MEMORY.store(enabled, Ordering::Relaxed);
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+  b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// 标准 base64 编码（RFC 4648，带填充）
+pub(crate) fn encode_base64(data: &[u8]) -> String {
+  let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+  for chunk in data.chunks(3) {
+    let bytes = [
+      chunk[0],
+      *chunk.get(1).unwrap_or(&0),
+      *chunk.get(2).unwrap_or(&0),
+    ];
+    let triple = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+    for i in 0..4 {
+      if i <= chunk.len() {
+        encoded.push(BASE64_ALPHABET[(triple >> (18 - 6 * i) & 0x3f) as usize] as char);
+      } else {
+        encoded.push('=');
+      }
+    }
+  }
+  encoded
+}
+
+#[napi(object)]
+/// 上传目录中的文件
+pub struct UploadedFile {
+  pub filename: String,
+  /// 文件大小（字节）
+  pub size: i64,
+  /// 最后修改时间的毫秒时间戳
+  pub modified: i64,
+}
+
+fn list_dir(dir: &Path) -> io::Result<Vec<UploadedFile>> {
+  let entries = match fs::read_dir(dir) {
+    Ok(entries) => entries,
+    // 还没有上传过文件
+    Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+    Err(e) => return Err(e),
+  };
+
+  let mut files = Vec::new();
+  for entry in entries {
+    let entry = entry?;
+    let metadata = entry.metadata()?;
+    if !metadata.is_file() {
+      continue;
+    }
+    let modified = metadata
+      .modified()?
+      .duration_since(UNIX_EPOCH)
+      .map(|elapsed| elapsed.as_millis() as i64)
+      .unwrap_or_default();
+    files.push(UploadedFile {
+      filename: entry.file_name().to_string_lossy().into_owned(),
+      size: metadata.len() as i64,
+      modified,
+    });
+  }
+  files.sort_by_key(|file| file.modified);
+  Ok(files)
+}
+
+/// 删除 dir 中最后修改时间早于 cutoff 的文件，返回删除的文件数
+fn purge_dir(dir: &Path, cutoff: SystemTime) -> io::Result<u32> {
+  let entries = match fs::read_dir(dir) {
+    Ok(entries) => entries,
+    Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+    Err(e) => return Err(e),
+  };
+
+  let mut removed = 0;
+  for entry in entries {
+    let entry = entry?;
+    let metadata = entry.metadata()?;
+    if !metadata.is_file() || metadata.modified()? >= cutoff {
+      continue;
+    }
+    // 单个文件删除失败（例如正在被其他进程占用）不影响其余文件
+    match fs::remove_file(entry.path()) {
+      Ok(()) => removed += 1,
+      Err(e) => eprintln!("删除上传文件 {:?} 失败: {}", entry.path(), e),
+    }
+  }
+  Ok(removed)
+}
+
+#[napi]
+/// 列出上传目录中的文件，按修改时间从旧到新排序；目录不存在时返回空数组
+pub fn list_uploads() -> Result<Vec<UploadedFile>> {
+  list_dir(&upload_dir()).map_err(|e| Error::from_reason(format!("读取上传目录失败: {}", e)))
+}
+
+#[napi]
+/// 删除上传目录中最后修改时间早于 olderThanSecs 秒之前的文件，返回删除的文件数
+pub fn purge_uploads(older_than_secs: u32) -> Result<u32> {
+  let cutoff = SystemTime::now() - Duration::from_secs(older_than_secs.into());
+  purge_dir(&upload_dir(), cutoff)
+    .map_err(|e| Error::from_reason(format!("清理上传目录失败: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_list_and_purge_uploads() {
+    let dir = std::env::temp_dir().join(format!("actix-js-uploads-{}", uuid::Uuid::new_v4()));
+    assert!(list_dir(&dir).unwrap().is_empty());
+
+    fs::create_dir_all(dir.join("nested")).unwrap();
+    fs::write(dir.join("a.png"), b"1234").unwrap();
+    let files = list_dir(&dir).unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].filename, "a.png");
+    assert_eq!(files[0].size, 4);
+
+    // 截止时间之后修改的文件保留
+    let past = SystemTime::now() - Duration::from_secs(60);
+    assert_eq!(purge_dir(&dir, past).unwrap(), 0);
+    let future = SystemTime::now() + Duration::from_secs(60);
+    assert_eq!(purge_dir(&dir, future).unwrap(), 1);
+    assert!(list_dir(&dir).unwrap().is_empty());
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn test_encode_base64() {
+    assert_eq!(encode_base64(b""), "");
+    assert_eq!(encode_base64(b"f"), "Zg==");
+    assert_eq!(encode_base64(b"fo"), "Zm8=");
+    assert_eq!(encode_base64(b"foo"), "Zm9v");
+    assert_eq!(encode_base64(b"foobar"), "Zm9vYmFy");
+    assert_eq!(encode_base64(&[0xff, 0xfe, 0x00]), "//4A");
+  }
+
+  #[test]
+  fn test_set_upload_dir() {
+    assert!(set_upload_dir(" ".to_string()).is_err());
+    assert_eq!(upload_dir(), PathBuf::from(DEFAULT_UPLOAD_DIR));
+
+    set_upload_dir("/var/data/uploads".to_string()).unwrap();
+    assert_eq!(upload_dir(), PathBuf::from("/var/data/uploads"));
+    *UPLOAD_DIR.write() = None;
+  }
+}
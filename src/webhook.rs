@@ -0,0 +1,137 @@
+use hmac::Mac;
+
+use crate::csrf::{decode_hex, hmac_sha256};
+
+// Webhook 签名校验
+// 签名都是对原始请求体字节计算的 HMAC-SHA256，比较使用 verify_slice（常量时间），
+// 校验失败只返回 false，不输出密钥或期望的签名
+
+/// Stripe 签名时间戳的默认容差（秒），与 Stripe 官方 SDK 一致
+pub const DEFAULT_STRIPE_TOLERANCE_SECS: u64 = 300;
+
+/// 校验形如 "sha256=<hex>"（GitHub）或 "<hex>" 的签名头
+/// 指定 prefix 时签名头必须以其开头；十六进制不区分大小写
+pub fn verify_hmac_sha256(secret: &[u8], body: &[u8], header: &str, prefix: Option<&str>) -> bool {
+  let header = header.trim();
+  let signature = match prefix {
+    Some(prefix) => match header.strip_prefix(prefix) {
+      Some(signature) => signature,
+      None => return false,
+    },
+    None => header,
+  };
+  let Some(signature) = decode_hex(&signature.to_ascii_lowercase()) else {
+    return false;
+  };
+
+  let mut mac = hmac_sha256(secret);
+  mac.update(body);
+  mac.verify_slice(&signature).is_ok()
+}
+
+/// 校验 Stripe-Signature 头："t=<时间戳>,v1=<hex>[,v1=<hex>...]"
+/// 签名内容为 "<时间戳>.<请求体>"，任意一个 v1 匹配且时间戳与 now 相差不超过 tolerance_secs 时通过
+pub fn verify_stripe_signature(
+  secret: &[u8],
+  body: &[u8],
+  header: &str,
+  now: u64,
+  tolerance_secs: u64,
+) -> bool {
+  let mut timestamp = None;
+  let mut signatures = Vec::new();
+  for part in header.split(',') {
+    match part.trim().split_once('=') {
+      Some(("t", value)) => timestamp = value.parse::<u64>().ok(),
+      Some(("v1", value)) => signatures.extend(decode_hex(&value.to_ascii_lowercase())),
+      _ => {}
+    }
+  }
+
+  let Some(timestamp) = timestamp else {
+    return false;
+  };
+  if now.abs_diff(timestamp) > tolerance_secs {
+    return false;
+  }
+
+  let mut mac = hmac_sha256(secret);
+  mac.update(timestamp.to_string().as_bytes());
+  mac.update(b".");
+  mac.update(body);
+  signatures
+    .iter()
+    .any(|signature| mac.clone().verify_slice(signature).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn sign(secret: &[u8], payload: &[u8]) -> String {
+    let mut mac = hmac_sha256(secret);
+    mac.update(payload);
+    mac
+      .finalize()
+      .into_bytes()
+      .iter()
+      .map(|byte| format!("{:02x}", byte))
+      .collect()
+  }
+
+  #[test]
+  fn test_verify_webhook_signatures() {
+    let body = br#"{"action":"opened"}"#;
+    let signature = sign(b"secret", body);
+
+    let github = format!("sha256={}", signature);
+    assert!(verify_hmac_sha256(
+      b"secret",
+      body,
+      &github,
+      Some("sha256=")
+    ));
+    assert!(verify_hmac_sha256(
+      b"secret",
+      body,
+      &signature.to_uppercase(),
+      None
+    ));
+    assert!(!verify_hmac_sha256(
+      b"secret",
+      body,
+      &signature,
+      Some("sha256=")
+    ));
+    assert!(!verify_hmac_sha256(
+      b"other",
+      body,
+      &github,
+      Some("sha256=")
+    ));
+
+    let stripe_signature = sign(b"secret", &[b"1700000000.".as_slice(), body].concat());
+    let stripe = format!("t=1700000000,v1=deadbeef,v1={}", stripe_signature);
+    assert!(verify_stripe_signature(
+      b"secret",
+      body,
+      &stripe,
+      1_700_000_100,
+      300
+    ));
+    assert!(!verify_stripe_signature(
+      b"secret",
+      body,
+      &stripe,
+      1_700_000_400,
+      300
+    ));
+    assert!(!verify_stripe_signature(
+      b"secret",
+      b"{}",
+      &stripe,
+      1_700_000_100,
+      300
+    ));
+  }
+}